@@ -13,6 +13,7 @@ pub fn compile(query: &Query) -> String {
         Query::Update(u) => compile_update(u),
         Query::Delete(d) => compile_delete(d),
         Query::With(cte) => compile_cte(cte),
+        Query::Values(rows) => compile_values(rows),
         Query::Raw(sql) => sql.clone(),
     }
 }
@@ -70,6 +71,8 @@ fn compile_select(select: &SelectQuery) -> String {
         ));
     }
 
+    push_modifiers(&mut parts, &select.order_by, &select.limit, &select.offset);
+
     // Set operations (UNION, INTERSECT, EXCEPT)
     if let Some(ref set_op) = select.set_op {
         let op_str = match set_op.op {
@@ -78,29 +81,53 @@ fn compile_select(select: &SelectQuery) -> String {
             SetOperator::Except => "EXCEPT",
         };
         let all_str = if set_op.all { " ALL" } else { "" };
-        parts.push(format!("{}{} {}", op_str, all_str, compile(&set_op.right)));
+
+        // The left operand's own ORDER BY/LIMIT/OFFSET need parentheses so
+        // they aren't read as applying to the combined result.
+        let left = parts.join(" ");
+        let left = if has_modifiers(select) {
+            format!("({})", left)
+        } else {
+            left
+        };
+        let right = match &set_op.right {
+            Query::Select(r) if has_modifiers(r) || r.set_op.is_some() => {
+                format!("({})", compile(&set_op.right))
+            }
+            other => compile(other),
+        };
+
+        parts = vec![format!("{} {}{} {}", left, op_str, all_str, right)];
+        push_modifiers(&mut parts, &set_op.order_by, &set_op.limit, &set_op.offset);
     }
 
-    // ORDER BY
-    if !select.order_by.is_empty() {
-        let orders: Vec<String> = select.order_by.iter().map(compile_order_by).collect();
+    parts.join(" ")
+}
+
+fn has_modifiers(select: &SelectQuery) -> bool {
+    !select.order_by.is_empty() || select.limit.is_some() || select.offset.is_some()
+}
+
+/// Append ORDER BY, LIMIT and OFFSET clauses.
+fn push_modifiers(
+    parts: &mut Vec<String>,
+    order_by: &[OrderByExpr],
+    limit: &Option<Expression>,
+    offset: &Option<Expression>,
+) {
+    if !order_by.is_empty() {
+        let orders: Vec<String> = order_by.iter().map(compile_order_by).collect();
         parts.push(format!("ORDER BY {}", orders.join(", ")));
     }
-
-    // LIMIT
-    if let Some(ref limit) = select.limit {
+    if let Some(limit) = limit {
         parts.push(format!("LIMIT {}", compile_expr(limit)));
     }
-
-    // OFFSET
-    if let Some(ref offset) = select.offset {
+    if let Some(offset) = offset {
         parts.push(format!("OFFSET {}", compile_expr(offset)));
     }
-
-    parts.join(" ")
 }
 
-fn compile_select_item(item: &SelectItem) -> String {
+pub(super) fn compile_select_item(item: &SelectItem) -> String {
     match item {
         SelectItem::Wildcard => "*".to_string(),
         SelectItem::QualifiedWildcard(table) => format!("{}.*", table),
@@ -114,7 +141,7 @@ fn compile_select_item(item: &SelectItem) -> String {
     }
 }
 
-fn compile_table_ref(table: &TableRef) -> String {
+pub(super) fn compile_table_ref(table: &TableRef) -> String {
     match table {
         TableRef::Table {
             schema,
@@ -144,7 +171,7 @@ fn compile_table_ref(table: &TableRef) -> String {
     }
 }
 
-fn compile_join(join: &Join) -> String {
+pub(super) fn compile_join(join: &Join) -> String {
     let type_str = match join.join_type {
         JoinType::Inner => "JOIN",
         JoinType::Left => "LEFT JOIN",
@@ -166,7 +193,7 @@ fn compile_join(join: &Join) -> String {
     format!("{} {}{}", type_str, table_str, condition_str)
 }
 
-fn compile_expr(expr: &Expression) -> String {
+pub(super) fn compile_expr(expr: &Expression) -> String {
     match expr {
         Expression::Column { table, name } => match table {
             Some(t) => format!("{}.{}", t, name),
@@ -316,6 +343,10 @@ fn compile_expr(expr: &Expression) -> String {
             let items: Vec<String> = elems.iter().map(compile_expr).collect();
             format!("ARRAY[{}]", items.join(", "))
         }
+        Expression::Row(elems) => {
+            let items: Vec<String> = elems.iter().map(compile_expr).collect();
+            format!("({})", items.join(", "))
+        }
         Expression::JsonAccess {
             expr,
             path,
@@ -397,7 +428,7 @@ fn compile_window_frame_bound(bound: &WindowFrameBound) -> String {
     }
 }
 
-fn compile_order_by(order: &OrderByExpr) -> String {
+pub(super) fn compile_order_by(order: &OrderByExpr) -> String {
     let mut s = compile_expr(&order.expr);
     match order.asc {
         Some(true) => s.push_str(" ASC"),
@@ -436,6 +467,52 @@ fn compile_cte(cte: &CTEQuery) -> String {
     )
 }
 
+/// Compile a VALUES list. A single row stays inline; multiple rows are laid
+/// out one per line with their columns aligned.
+fn compile_values(rows: &[Vec<Expression>]) -> String {
+    let rows: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| row.iter().map(compile_expr).collect())
+        .collect();
+
+    if rows.len() <= 1 {
+        let row = rows.first().map(|r| r.join(", ")).unwrap_or_default();
+        return format!("VALUES ({})", row);
+    }
+
+    let column_count = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..column_count)
+        .map(|i| {
+            rows.iter()
+                .filter_map(|r| r.get(i))
+                .map(|v| v.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let lines: Vec<String> = rows
+        .iter()
+        .map(|row| {
+            let cells: Vec<String> = row
+                .iter()
+                .enumerate()
+                .map(|(i, v)| {
+                    if i + 1 == row.len() {
+                        v.clone()
+                    } else {
+                        // Pad after the comma so values line up across rows
+                        format!("{:<width$}", format!("{},", v), width = widths[i] + 1)
+                    }
+                })
+                .collect();
+            format!("    ({})", cells.join(" "))
+        })
+        .collect();
+
+    format!("VALUES\n{}", lines.join(",\n"))
+}
+
 fn compile_insert(insert: &InsertQuery) -> String {
     let table = compile_table_ref(&insert.table);
     let columns = if insert.columns.is_empty() {
@@ -445,16 +522,7 @@ fn compile_insert(insert: &InsertQuery) -> String {
     };
 
     let source = match &insert.source {
-        InsertSource::Values(rows) => {
-            let row_strs: Vec<String> = rows
-                .iter()
-                .map(|row| {
-                    let vals: Vec<String> = row.iter().map(compile_expr).collect();
-                    format!("({})", vals.join(", "))
-                })
-                .collect();
-            format!("VALUES {}", row_strs.join(", "))
-        }
+        InsertSource::Values(rows) => compile_values(rows),
         InsertSource::Query(q) => compile(q),
     };
 
@@ -690,4 +758,45 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_compile_values_one_row_per_line() {
+        let compiled = round_trip("VALUES (1, 'a'), (100, 'bb')");
+        assert_eq!(compiled, "VALUES\n    (1,   'a'),\n    (100, 'bb')");
+        assert!(parse_single(&compiled).is_ok());
+    }
+
+    #[test]
+    fn test_compile_single_row_values_inline() {
+        let compiled = round_trip("VALUES (1, 2)");
+        assert_eq!(compiled, "VALUES (1, 2)");
+    }
+
+    #[test]
+    fn test_compile_union_order_by_limit() {
+        let compiled =
+            round_trip("(SELECT id FROM a) UNION (SELECT id FROM b) ORDER BY 1 LIMIT 10 OFFSET 20");
+        assert_eq!(
+            compiled,
+            "SELECT id FROM a UNION SELECT id FROM b ORDER BY 1 LIMIT 10 OFFSET 20"
+        );
+    }
+
+    #[test]
+    fn test_compile_union_operand_order_by_parenthesized() {
+        let compiled = round_trip(
+            "(SELECT id FROM a ORDER BY id LIMIT 5) UNION ALL (SELECT id FROM b LIMIT 1) ORDER BY 1",
+        );
+        assert_eq!(
+            compiled,
+            "(SELECT id FROM a ORDER BY id LIMIT 5) UNION ALL (SELECT id FROM b LIMIT 1) ORDER BY 1"
+        );
+        assert!(parse_single(&compiled).is_ok());
+    }
+
+    #[test]
+    fn test_compile_table_function_args() {
+        let compiled = round_trip("SELECT * FROM generate_series(1, 10) AS g");
+        assert_eq!(compiled, "SELECT * FROM generate_series(1, 10) AS g");
+    }
 }
@@ -29,7 +29,13 @@ pub mod types;
 // Re-export key types for convenience
 pub use adapter::{AdapterRegistry, DSLAdapter, QueryLanguageAdapter};
 pub use compiler::compile;
-pub use optimizer::{analyze_query, OptimizationPass, Optimizer, QueryAnalysis};
-pub use parser::{parse_single, parse_sql};
+pub use optimizer::{
+    analyze_query, OptimizationPass, Optimizer, QueryAnalysis, QueryWarning, WarningSeverity,
+};
+pub use parser::{
+    destructive_statement, is_read_only_query, parse_single, parse_sql, select_window,
+    single_source_table, with_select_window, without_select_order, without_select_window,
+    DestructiveStatement,
+};
 pub use plugin::{PluginRegistry, QueryPlugin};
 pub use types::*;
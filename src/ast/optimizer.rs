@@ -196,11 +196,13 @@ fn analyze_query_inner(query: &Query, analysis: &mut QueryAnalysis) {
             if let Some(ref filter) = s.filter {
                 check_expr_features(filter, analysis);
             }
+            check_implicit_cross_join(s, analysis);
         }
         Query::With(cte) => {
             analysis.has_cte = true;
             if cte.recursive {
                 analysis.has_recursive_cte = true;
+                check_recursion_bounds(cte, analysis);
             }
             for c in &cte.ctes {
                 analyze_query_inner(&c.query, analysis);
@@ -208,9 +210,229 @@ fn analyze_query_inner(query: &Query, analysis: &mut QueryAnalysis) {
             analyze_query_inner(&cte.body, analysis);
         }
         Query::Insert(_) => analysis.has_insert = true,
-        Query::Update(_) => analysis.has_update = true,
-        Query::Delete(_) => analysis.has_delete = true,
-        Query::Raw(_) => {}
+        Query::Update(u) => {
+            analysis.has_update = true;
+            if u.filter.is_none() {
+                analysis.warn(
+                    WarningSeverity::High,
+                    "UPDATE without WHERE affects every row".to_string(),
+                );
+            }
+        }
+        Query::Delete(d) => {
+            analysis.has_delete = true;
+            if d.filter.is_none() {
+                analysis.warn(
+                    WarningSeverity::High,
+                    "DELETE without WHERE removes every row".to_string(),
+                );
+            }
+        }
+        Query::Values(_) => {}
+        Query::Raw(_) => analysis.warn(
+            WarningSeverity::Low,
+            "Statement is not supported by the AST; passed through as raw SQL".to_string(),
+        ),
+    }
+}
+
+/// Warn when a comma-separated FROM lists tables that no WHERE or ON
+/// comparison links together: an accidental cartesian product. Gives up
+/// quietly when a comparison uses unqualified columns or a join is USING,
+/// NATURAL or CROSS, since what those link cannot be told from the AST.
+fn check_implicit_cross_join(select: &SelectQuery, analysis: &mut QueryAnalysis) {
+    let from_tables = select
+        .from
+        .iter()
+        .filter(|t| matches!(t, TableRef::Table { .. }))
+        .count();
+    if from_tables < 2 {
+        return;
+    }
+
+    // FROM tables come first, so their indices are 0..from_tables
+    let names: Vec<String> = select
+        .from
+        .iter()
+        .chain(select.joins.iter().map(|j| &j.table))
+        .filter_map(|table| match table {
+            TableRef::Table { name, alias, .. } => {
+                Some(alias.as_ref().unwrap_or(name).to_lowercase())
+            }
+            _ => None,
+        })
+        .collect();
+    let mut conditions: Vec<&Expression> = select.filter.iter().collect();
+    for join in &select.joins {
+        match &join.condition {
+            Some(JoinCondition::On(expr)) => conditions.push(expr),
+            _ => return,
+        }
+    }
+
+    let mut groups: Vec<usize> = (0..names.len()).collect();
+    for condition in conditions {
+        if !link_tables(condition, &names, &mut groups) {
+            return;
+        }
+    }
+    let root = find_group(&groups, 0);
+    let unlinked: Vec<&str> = (1..from_tables)
+        .filter(|&i| find_group(&groups, i) != root)
+        .map(|i| names[i].as_str())
+        .collect();
+    if !unlinked.is_empty() {
+        analysis.warn(
+            WarningSeverity::High,
+            format!(
+                "Implicit cross join: nothing links {} to {}; add a join condition",
+                unlinked.join(", "),
+                names[0]
+            ),
+        );
+    }
+}
+
+/// Merge the groups of tables compared against each other in `expr`.
+/// Returns false if a comparison involves unqualified columns or something
+/// this can't see into (e.g. a subquery), so no warning is given.
+fn link_tables(expr: &Expression, names: &[String], groups: &mut [usize]) -> bool {
+    let operands: Vec<&Expression> = match expr {
+        Expression::BinaryOp {
+            left,
+            op: BinaryOperator::And | BinaryOperator::Or,
+            right,
+        } => return link_tables(left, names, groups) && link_tables(right, names, groups),
+        Expression::Nested(inner) => return link_tables(inner, names, groups),
+        Expression::BinaryOp { left, right, .. } => vec![left, right],
+        Expression::InList { expr, list, .. } => {
+            std::iter::once(expr.as_ref()).chain(list).collect()
+        }
+        Expression::Between {
+            expr, low, high, ..
+        } => vec![expr, low, high],
+        Expression::Exists(_) | Expression::InSubquery { .. } | Expression::Subquery(_) => {
+            return false
+        }
+        _ => return true,
+    };
+    let mut qualifiers = Vec::new();
+    for operand in operands {
+        if !column_qualifiers(operand, &mut qualifiers) {
+            return false;
+        }
+    }
+    if qualifiers.len() >= 2 && qualifiers.iter().any(Option::is_none) {
+        return false;
+    }
+    let tables: Vec<usize> = qualifiers
+        .into_iter()
+        .flatten()
+        .filter_map(|q| {
+            let table = q.rsplit('.').next().unwrap_or(q).to_lowercase();
+            names.iter().position(|n| *n == table)
+        })
+        .collect();
+    for pair in tables.windows(2) {
+        let (a, b) = (find_group(groups, pair[0]), find_group(groups, pair[1]));
+        groups[a] = b;
+    }
+    true
+}
+
+/// Table qualifier of every column referenced in `expr`; `None` for
+/// unqualified columns. Returns false for node kinds it doesn't look into.
+fn column_qualifiers<'a>(expr: &'a Expression, out: &mut Vec<Option<&'a str>>) -> bool {
+    match expr {
+        Expression::Column { table, .. } => {
+            out.push(table.as_deref());
+            true
+        }
+        Expression::BinaryOp { left, right, .. } => {
+            column_qualifiers(left, out) && column_qualifiers(right, out)
+        }
+        Expression::Function { args, .. } | Expression::Row(args) | Expression::Array(args) => {
+            args.iter().all(|arg| column_qualifiers(arg, out))
+        }
+        Expression::UnaryOp { expr, .. }
+        | Expression::Nested(expr)
+        | Expression::Cast { expr, .. }
+        | Expression::TypeCast { expr, .. }
+        | Expression::JsonAccess { expr, .. } => column_qualifiers(expr, out),
+        Expression::Literal(_) | Expression::Parameter(_) | Expression::Wildcard => true,
+        _ => false,
+    }
+}
+
+fn find_group(groups: &[usize], mut i: usize) -> usize {
+    while groups[i] != i {
+        i = groups[i];
+    }
+    i
+}
+
+/// Warn about recursive CTEs whose recursive member has no obvious way to
+/// stop: no WHERE or LIMIT of its own and no LIMIT on the outer query. Such
+/// a CTE only terminates if the data runs out, and loops forever on cycles.
+fn check_recursion_bounds(cte: &CTEQuery, analysis: &mut QueryAnalysis) {
+    if query_has_limit(&cte.body) {
+        return;
+    }
+    for c in &cte.ctes {
+        let Query::Select(anchor) = &c.query else {
+            continue;
+        };
+        let Some(set_op) = &anchor.set_op else {
+            continue;
+        };
+        if set_op.limit.is_some() {
+            continue;
+        }
+        let unbounded = set_op_members(set_op)
+            .filter(|member| references_table(member, &c.name))
+            .any(|member| member.filter.is_none() && member.limit.is_none());
+        if unbounded {
+            analysis.warn(
+                WarningSeverity::High,
+                format!(
+                    "Recursive CTE \"{}\" has no WHERE or LIMIT to stop the recursion",
+                    c.name
+                ),
+            );
+        }
+    }
+}
+
+/// The SELECTs on the right-hand side of a chain of set operations.
+fn set_op_members(set_op: &SetOperation) -> impl Iterator<Item = &SelectQuery> {
+    std::iter::successors(
+        match &set_op.right {
+            Query::Select(s) => Some(s.as_ref()),
+            _ => None,
+        },
+        |s| match s.set_op.as_ref().map(|op| &op.right) {
+            Some(Query::Select(next)) => Some(next.as_ref()),
+            _ => None,
+        },
+    )
+}
+
+fn references_table(select: &SelectQuery, name: &str) -> bool {
+    select
+        .from
+        .iter()
+        .chain(select.joins.iter().map(|j| &j.table))
+        .any(|table| {
+            matches!(table, TableRef::Table { schema: None, name: n, .. } if n.eq_ignore_ascii_case(name))
+        })
+}
+
+fn query_has_limit(query: &Query) -> bool {
+    match query {
+        Query::Select(s) => {
+            s.limit.is_some() || s.set_op.as_ref().is_some_and(|op| op.limit.is_some())
+        }
+        _ => false,
     }
 }
 
@@ -271,6 +493,55 @@ pub struct QueryAnalysis {
     pub has_recursive_cte: bool,
     pub has_set_operations: bool,
     pub has_json_operations: bool,
+    /// Potential problems spotted while walking the query.
+    pub warnings: Vec<QueryWarning>,
+}
+
+/// How likely a warning is to point at a costly mistake.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WarningSeverity {
+    /// Worth knowing, e.g. a statement the AST does not model.
+    Low,
+    /// Likely to touch or produce far more rows than intended; shown before
+    /// the query runs.
+    High,
+}
+
+/// A potential problem found by `analyze_query`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryWarning {
+    pub severity: WarningSeverity,
+    pub message: String,
+}
+
+impl std::fmt::Display for QueryWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl QueryAnalysis {
+    fn warn(&mut self, severity: WarningSeverity, message: String) {
+        self.warnings.push(QueryWarning { severity, message });
+    }
+
+    /// Human-readable names of the structural features present in the query.
+    pub fn features(&self) -> Vec<&'static str> {
+        [
+            (self.has_distinct, "DISTINCT"),
+            (self.has_joins, "joins"),
+            (self.has_aggregation, "aggregation"),
+            (self.has_window_functions, "window functions"),
+            (self.has_subqueries, "subqueries"),
+            (self.has_cte, "CTE"),
+            (self.has_recursive_cte, "recursive CTE"),
+            (self.has_set_operations, "set operations"),
+            (self.has_json_operations, "JSON operators"),
+        ]
+        .into_iter()
+        .filter_map(|(present, name)| present.then_some(name))
+        .collect()
+    }
 }
 
 #[cfg(test)]
@@ -367,6 +638,74 @@ mod tests {
         assert!(analysis.has_recursive_cte);
     }
 
+    #[test]
+    fn test_analyze_unbounded_recursive_cte_warns() {
+        let q = parse_single(
+            "WITH RECURSIVE nums AS (SELECT 1 AS n UNION ALL SELECT n + 1 FROM nums) SELECT * FROM nums",
+        )
+        .unwrap();
+        let analysis = analyze_query(&q);
+        assert_eq!(analysis.warnings.len(), 1);
+        assert!(analysis.warnings[0].message.contains("\"nums\""));
+        assert_eq!(analysis.warnings[0].severity, WarningSeverity::High);
+
+        // A walk over a join still needs a stopping condition
+        let q = parse_single(
+            "WITH RECURSIVE tree AS (SELECT id FROM nodes WHERE parent IS NULL \
+             UNION ALL SELECT n.id FROM nodes n JOIN tree t ON n.parent = t.id) SELECT * FROM tree",
+        )
+        .unwrap();
+        assert_eq!(analyze_query(&q).warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_analyze_bounded_recursive_cte() {
+        for sql in [
+            "WITH RECURSIVE nums AS (SELECT 1 AS n UNION ALL SELECT n + 1 FROM nums WHERE n < 10) SELECT * FROM nums",
+            "WITH RECURSIVE nums AS (SELECT 1 AS n UNION ALL SELECT n + 1 FROM nums) SELECT * FROM nums LIMIT 10",
+            "WITH RECURSIVE a AS (SELECT 1 UNION SELECT 2) SELECT * FROM a",
+        ] {
+            let q = parse_single(sql).unwrap();
+            assert!(analyze_query(&q).warnings.is_empty(), "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_analyze_implicit_cross_join_warns() {
+        let q = parse_single("SELECT * FROM orders o, customers c WHERE o.total > 10").unwrap();
+        let analysis = analyze_query(&q);
+        assert_eq!(analysis.warnings.len(), 1);
+        assert_eq!(analysis.warnings[0].severity, WarningSeverity::High);
+        assert!(analysis.warnings[0].message.contains("c to o"));
+
+        // Two of three tables linked still leaves one unlinked
+        let q = parse_single("SELECT * FROM a, b, c WHERE a.id = b.a_id").unwrap();
+        let analysis = analyze_query(&q);
+        assert_eq!(analysis.warnings.len(), 1);
+        assert!(analysis.warnings[0]
+            .message
+            .contains("nothing links c to a"));
+    }
+
+    #[test]
+    fn test_analyze_linked_comma_join() {
+        for sql in [
+            "SELECT * FROM orders o, customers c WHERE o.customer_id = c.id",
+            "SELECT * FROM a, b, c WHERE a.id = b.a_id AND lower(b.code) = c.code",
+            "SELECT * FROM a, b JOIN c ON c.b_id = b.id WHERE c.a_id = a.id",
+            // Unqualified columns could belong to either table
+            "SELECT * FROM a, b WHERE a_id = b_id",
+            "SELECT * FROM a JOIN b ON a.id = b.a_id",
+            // Row values, and conditions this can't see into
+            "SELECT * FROM a, b WHERE (a.id, 1) = (b.id, 1)",
+            "SELECT * FROM a, b WHERE a.x BETWEEN b.lo AND b.hi",
+            "SELECT * FROM a, b WHERE EXISTS (SELECT 1 FROM c WHERE c.a = a.id AND c.b = b.id)",
+        ] {
+            let q = parse_single(sql).unwrap();
+            assert!(analyze_query(&q).warnings.is_empty(), "{}", sql);
+        }
+    }
+
     #[test]
     fn test_analyze_subquery() {
         let q =
@@ -381,4 +720,21 @@ mod tests {
         let analysis = analyze_query(&q);
         assert!(analysis.has_set_operations);
     }
+
+    #[test]
+    fn test_analyze_unfiltered_delete_warns() {
+        let q = parse_single("DELETE FROM users").unwrap();
+        let analysis = analyze_query(&q);
+        assert_eq!(analysis.warnings.len(), 1);
+
+        let q = parse_single("DELETE FROM users WHERE id = 1").unwrap();
+        assert!(analyze_query(&q).warnings.is_empty());
+    }
+
+    #[test]
+    fn test_analyze_features() {
+        let q = parse_single("SELECT DISTINCT a.id FROM a JOIN b ON a.id = b.id").unwrap();
+        let analysis = analyze_query(&q);
+        assert_eq!(analysis.features(), vec!["DISTINCT", "joins"]);
+    }
 }
@@ -29,6 +29,178 @@ pub fn parse_single(sql: &str) -> Result<Query> {
     Ok(queries.remove(0))
 }
 
+/// A statement that irreversibly removes data, and the object(s) it targets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DestructiveStatement {
+    /// e.g. "DROP TABLE" or "TRUNCATE"
+    pub action: String,
+    /// Target name(s) as written, e.g. "public.users"; several are joined by ", ".
+    pub target: String,
+}
+
+/// Detect `DROP ...` and `TRUNCATE` statements in `sql`. Returns the first one
+/// found, or `None` if there is none or the SQL does not parse.
+pub fn destructive_statement(sql: &str) -> Option<DestructiveStatement> {
+    let statements = SqlParser::parse_sql(&PostgreSqlDialect {}, sql).ok()?;
+    statements.into_iter().find_map(|stmt| match stmt {
+        sp::Statement::Drop {
+            object_type, names, ..
+        } => Some(DestructiveStatement {
+            action: format!("DROP {}", object_type),
+            target: join_names(names.iter()),
+        }),
+        sp::Statement::Truncate { table_names, .. } => Some(DestructiveStatement {
+            action: "TRUNCATE".to_string(),
+            target: join_names(table_names.iter().map(|t| &t.name)),
+        }),
+        _ => None,
+    })
+}
+
+/// `sql` as one query that only reads: a SELECT, VALUES or set operation,
+/// without `SELECT INTO` or data-modifying CTEs. Kept on `sqlparser`'s own
+/// AST, whose `Display` prints the statement back faithfully, so rewrites
+/// of it are safe to execute.
+fn parse_read_only_query(sql: &str) -> Option<sp::Query> {
+    let mut statements = SqlParser::parse_sql(&PostgreSqlDialect {}, sql).ok()?;
+    if statements.len() != 1 {
+        return None;
+    }
+    match statements.remove(0) {
+        sp::Statement::Query(query) if query_reads_only(&query) => Some(*query),
+        _ => None,
+    }
+}
+
+/// Whether `sql` is a single query that only reads data, so running it
+/// again can't repeat a write.
+pub fn is_read_only_query(sql: &str) -> bool {
+    parse_read_only_query(sql).is_some()
+}
+
+fn query_reads_only(query: &sp::Query) -> bool {
+    let ctes_read_only = query.with.as_ref().is_none_or(|with| {
+        with.cte_tables
+            .iter()
+            .all(|cte| query_reads_only(&cte.query))
+    });
+    ctes_read_only && set_expr_reads_only(&query.body)
+}
+
+fn set_expr_reads_only(body: &sp::SetExpr) -> bool {
+    match body {
+        sp::SetExpr::Select(select) => select.into.is_none(),
+        sp::SetExpr::Query(query) => query_reads_only(query),
+        sp::SetExpr::SetOperation { left, right, .. } => {
+            set_expr_reads_only(left) && set_expr_reads_only(right)
+        }
+        sp::SetExpr::Values(_) | sp::SetExpr::Table(_) => true,
+        sp::SetExpr::Insert(_) | sp::SetExpr::Update(_) => false,
+    }
+}
+
+fn integer_literal(expr: &sp::Expr) -> Option<i64> {
+    match expr {
+        sp::Expr::Value(sp::Value::Number(n, _)) => n.parse().ok(),
+        _ => None,
+    }
+}
+
+/// LIMIT and OFFSET of the read-only query `sql` (the combined result's,
+/// for a set operation). `None` when it is not such a query, uses FETCH, or
+/// either clause is not an integer literal; an absent OFFSET reads as 0.
+pub fn select_window(sql: &str) -> Option<(Option<i64>, i64)> {
+    let query = parse_read_only_query(sql)?;
+    if query.fetch.is_some() {
+        return None;
+    }
+    let limit = match &query.limit {
+        None => None,
+        Some(expr) => Some(integer_literal(expr)?),
+    };
+    let offset = match &query.offset {
+        None => 0,
+        Some(offset) => integer_literal(&offset.value)?,
+    };
+    Some((limit, offset))
+}
+
+/// `sql` with its LIMIT and OFFSET replaced; an OFFSET of 0 is dropped.
+/// `None` where `select_window` can't read the current ones. Everything
+/// else is printed back as parsed.
+pub fn with_select_window(sql: &str, limit: i64, offset: i64) -> Option<String> {
+    select_window(sql)?;
+    let mut query = parse_read_only_query(sql)?;
+    query.limit = Some(sp::Expr::Value(sp::Value::Number(limit.to_string(), false)));
+    query.offset = (offset > 0).then(|| sp::Offset {
+        value: sp::Expr::Value(sp::Value::Number(offset.to_string(), false)),
+        rows: sp::OffsetRows::None,
+    });
+    Some(query.to_string())
+}
+
+/// The read-only query `sql` without LIMIT, OFFSET or FETCH, i.e. every
+/// row it would return. `None` when it is not such a query.
+pub fn without_select_window(sql: &str) -> Option<String> {
+    let mut query = parse_read_only_query(sql)?;
+    query.limit = None;
+    query.offset = None;
+    query.fetch = None;
+    Some(query.to_string())
+}
+
+/// The read-only query `sql` without its ORDER BY (the combined result's,
+/// for a set operation), which a row count doesn't need. `None` when it is
+/// not such a query, or `FETCH ... WITH TIES` makes the order matter.
+pub fn without_select_order(sql: &str) -> Option<String> {
+    let mut query = parse_read_only_query(sql)?;
+    if query.fetch.as_ref().is_some_and(|fetch| fetch.with_ties) {
+        return None;
+    }
+    query.order_by = None;
+    Some(query.to_string())
+}
+
+/// `(schema, table)` when `sql` is a SELECT reading a single plain table
+/// with no joins, i.e. its rows could be inserted back into that table.
+/// Unquoted names are folded to lower case, as PostgreSQL does.
+pub fn single_source_table(sql: &str) -> Option<(Option<String>, String)> {
+    let query = parse_read_only_query(sql)?;
+    let sp::SetExpr::Select(select) = *query.body else {
+        return None;
+    };
+    let [from] = select.from.as_slice() else {
+        return None;
+    };
+    if !from.joins.is_empty() {
+        return None;
+    }
+    let sp::TableFactor::Table {
+        name, args: None, ..
+    } = &from.relation
+    else {
+        return None;
+    };
+    match name.0.as_slice() {
+        [table] => Some((None, folded_ident(table))),
+        [schema, table] => Some((Some(folded_ident(schema)), folded_ident(table))),
+        _ => None,
+    }
+}
+
+/// The name an identifier resolves to: as written when quoted, else in
+/// lower case.
+fn folded_ident(ident: &sp::Ident) -> String {
+    match ident.quote_style {
+        Some(_) => ident.value.clone(),
+        None => ident.value.to_lowercase(),
+    }
+}
+
+fn join_names<'a>(names: impl Iterator<Item = &'a sp::ObjectName>) -> String {
+    names.map(|n| n.to_string()).collect::<Vec<_>>().join(", ")
+}
+
 fn convert_statement(stmt: sp::Statement) -> Result<Query> {
     match stmt {
         sp::Statement::Query(q) => convert_query(*q),
@@ -83,17 +255,27 @@ fn apply_query_modifiers(
 ) -> Result<Query> {
     // Only apply modifiers to Select queries
     if let Query::Select(mut select) = query {
+        let order_by = order_by
+            .iter()
+            .map(|o| convert_order_by(o.clone()))
+            .collect::<Result<Vec<_>>>()?;
+        let limit = limit.clone().map(convert_expr).transpose()?;
+        let offset = offset.clone().map(|o| convert_expr(o.value)).transpose()?;
+
+        // On a set operation the trailing clauses order/limit the combined
+        // result, not just the left-hand SELECT.
+        let (target_order_by, target_limit, target_offset) = match select.set_op.as_mut() {
+            Some(set_op) => (&mut set_op.order_by, &mut set_op.limit, &mut set_op.offset),
+            None => (&mut select.order_by, &mut select.limit, &mut select.offset),
+        };
         if !order_by.is_empty() {
-            select.order_by = order_by
-                .iter()
-                .map(|o| convert_order_by(o.clone()))
-                .collect::<Result<Vec<_>>>()?;
+            *target_order_by = order_by;
         }
-        if let Some(l) = limit {
-            select.limit = Some(convert_expr(l.clone())?);
+        if limit.is_some() {
+            *target_limit = limit;
         }
-        if let Some(o) = offset {
-            select.offset = Some(convert_expr(o.value.clone())?);
+        if offset.is_some() {
+            *target_offset = offset;
         }
         Ok(Query::Select(select))
     } else {
@@ -145,6 +327,9 @@ fn convert_set_expr(expr: sp::SetExpr) -> Result<Query> {
                 },
                 all,
                 right: right_query,
+                order_by: vec![],
+                limit: None,
+                offset: None,
             };
 
             match left_query {
@@ -166,14 +351,23 @@ fn convert_set_expr(expr: sp::SetExpr) -> Result<Query> {
                 }
             }
         }
-        sp::SetExpr::Values(values) => {
-            // VALUES as a standalone query - wrap in raw
-            Ok(Query::Raw(format!("VALUES {}", values)))
-        }
+        sp::SetExpr::Values(values) => Ok(Query::Values(convert_values_rows(values)?)),
         _ => Ok(Query::Raw(expr.to_string())),
     }
 }
 
+fn convert_values_rows(values: sp::Values) -> Result<Vec<Vec<Expression>>> {
+    values
+        .rows
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(convert_expr)
+                .collect::<Result<Vec<_>>>()
+        })
+        .collect()
+}
+
 fn convert_select(select: sp::Select) -> Result<Query> {
     let distinct = select.distinct.is_some();
 
@@ -246,6 +440,17 @@ fn convert_table_with_joins(twj: sp::TableWithJoins) -> Result<(TableRef, Vec<Jo
 
 fn convert_table_factor(tf: sp::TableFactor) -> Result<TableRef> {
     match tf {
+        // `generate_series(1, 10)` and friends come through as a table with args
+        sp::TableFactor::Table {
+            name,
+            alias,
+            args: Some(table_args),
+            ..
+        } => Ok(TableRef::Function {
+            name: name.to_string(),
+            args: convert_function_args(table_args.args)?,
+            alias: alias.map(|a| a.name.value),
+        }),
         sp::TableFactor::Table { name, alias, .. } => {
             let parts: Vec<&str> = name.0.iter().map(|p| p.value.as_str()).collect();
             let (schema, table_name) = match parts.len() {
@@ -270,9 +475,42 @@ fn convert_table_factor(tf: sp::TableFactor) -> Result<TableRef> {
                 alias: alias_name,
             })
         }
-        sp::TableFactor::TableFunction { expr, alias } => Ok(TableRef::Function {
-            name: expr.to_string(),
-            args: vec![],
+        sp::TableFactor::TableFunction { expr, alias } => {
+            let alias = alias.map(|a| a.name.value);
+            match expr {
+                sp::Expr::Function(func) => {
+                    let args = match func.args {
+                        sp::FunctionArguments::List(list) => convert_function_args(list.args)?,
+                        _ => vec![],
+                    };
+                    Ok(TableRef::Function {
+                        name: func.name.to_string(),
+                        args,
+                        alias,
+                    })
+                }
+                other => Ok(TableRef::Function {
+                    name: other.to_string(),
+                    args: vec![],
+                    alias,
+                }),
+            }
+        }
+        sp::TableFactor::Function {
+            name, args, alias, ..
+        } => Ok(TableRef::Function {
+            name: name.to_string(),
+            args: convert_function_args(args)?,
+            alias: alias.map(|a| a.name.value),
+        }),
+        sp::TableFactor::UNNEST {
+            alias, array_exprs, ..
+        } => Ok(TableRef::Function {
+            name: "UNNEST".into(),
+            args: array_exprs
+                .into_iter()
+                .map(convert_expr)
+                .collect::<Result<Vec<_>>>()?,
             alias: alias.map(|a| a.name.value),
         }),
         _ => Ok(TableRef::Table {
@@ -494,6 +732,12 @@ fn convert_expr(expr: sp::Expr) -> Result<Expression> {
                 .collect::<Result<Vec<_>>>()?;
             Ok(Expression::Array(elems))
         }
+        sp::Expr::Tuple(elems) => Ok(Expression::Row(
+            elems
+                .into_iter()
+                .map(convert_expr)
+                .collect::<Result<Vec<_>>>()?,
+        )),
         sp::Expr::JsonAccess { value, path } => convert_json_access(*value, path),
         _ => {
             // Fallback: store as a literal string representation
@@ -594,22 +838,7 @@ fn convert_function(func: sp::Function) -> Result<Expression> {
                 arg_list.duplicate_treatment,
                 Some(sp::DuplicateTreatment::Distinct)
             );
-            let args = arg_list
-                .args
-                .into_iter()
-                .filter_map(|a| match a {
-                    sp::FunctionArg::Unnamed(sp::FunctionArgExpr::Expr(e)) => Some(convert_expr(e)),
-                    sp::FunctionArg::Unnamed(sp::FunctionArgExpr::Wildcard) => {
-                        Some(Ok(Expression::Wildcard))
-                    }
-                    sp::FunctionArg::Named {
-                        arg: sp::FunctionArgExpr::Expr(e),
-                        ..
-                    } => Some(convert_expr(e)),
-                    _ => None,
-                })
-                .collect::<Result<Vec<_>>>()?;
-            (args, distinct)
+            (convert_function_args(arg_list.args)?, distinct)
         }
         sp::FunctionArguments::None => (vec![], false),
         sp::FunctionArguments::Subquery(q) => (
@@ -674,6 +903,22 @@ fn convert_function(func: sp::Function) -> Result<Expression> {
     }
 }
 
+fn convert_function_args(args: Vec<sp::FunctionArg>) -> Result<Vec<Expression>> {
+    args.into_iter()
+        .filter_map(|a| match a {
+            sp::FunctionArg::Unnamed(sp::FunctionArgExpr::Expr(e)) => Some(convert_expr(e)),
+            sp::FunctionArg::Unnamed(sp::FunctionArgExpr::Wildcard) => {
+                Some(Ok(Expression::Wildcard))
+            }
+            sp::FunctionArg::Named {
+                arg: sp::FunctionArgExpr::Expr(e),
+                ..
+            } => Some(convert_expr(e)),
+            _ => None,
+        })
+        .collect()
+}
+
 fn convert_window_spec(spec: sp::WindowSpec) -> Result<WindowSpec> {
     let partition_by = spec
         .partition_by
@@ -765,18 +1010,7 @@ fn convert_insert(insert: sp::Insert) -> Result<Query> {
 
     let source = if let Some(src) = insert.source {
         match *src.body {
-            sp::SetExpr::Values(values) => {
-                let rows = values
-                    .rows
-                    .into_iter()
-                    .map(|row| {
-                        row.into_iter()
-                            .map(convert_expr)
-                            .collect::<Result<Vec<_>>>()
-                    })
-                    .collect::<Result<Vec<_>>>()?;
-                InsertSource::Values(rows)
-            }
+            sp::SetExpr::Values(values) => InsertSource::Values(convert_values_rows(values)?),
             other => {
                 let query = convert_set_expr(other)?;
                 InsertSource::Query(Box::new(query))
@@ -869,6 +1103,32 @@ fn convert_delete(delete: sp::Delete) -> Result<Query> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_single_source_table() {
+        assert_eq!(
+            single_source_table("SELECT * FROM app.users t WHERE id > 1"),
+            Some((Some("app".into()), "users".into()))
+        );
+        assert_eq!(
+            single_source_table("select id from Users"),
+            Some((None, "users".into()))
+        );
+        assert_eq!(
+            single_source_table(r#"SELECT * FROM "MySchema"."MyTable""#),
+            Some((Some("MySchema".into()), "MyTable".into()))
+        );
+        assert_eq!(single_source_table("SELECT * FROM a, b"), None);
+        assert_eq!(
+            single_source_table("SELECT * FROM a JOIN b ON a.id = b.id"),
+            None
+        );
+        assert_eq!(
+            single_source_table("SELECT * FROM generate_series(1, 3)"),
+            None
+        );
+        assert_eq!(single_source_table("SHOW ALL"), None);
+    }
+
     #[test]
     fn test_parse_simple_select() {
         let q = parse_single("SELECT * FROM users").unwrap();
@@ -1156,4 +1416,199 @@ mod tests {
             _ => panic!("Expected Select query"),
         }
     }
+
+    #[test]
+    fn test_parse_standalone_values() {
+        let q = parse_single("VALUES (1, 'a'), (2, 'b')").unwrap();
+        match q {
+            Query::Values(rows) => {
+                assert_eq!(rows.len(), 2);
+                assert_eq!(rows[0][0], Expression::Literal(Literal::Integer(1)));
+            }
+            _ => panic!("Expected Values query"),
+        }
+    }
+
+    #[test]
+    fn test_parse_values_in_from() {
+        let q = parse_single("SELECT * FROM (VALUES (1), (2)) AS t").unwrap();
+        match q {
+            Query::Select(s) => match &s.from[0] {
+                TableRef::Subquery { query, alias } => {
+                    assert_eq!(alias, "t");
+                    assert!(matches!(**query, Query::Values(_)));
+                }
+                _ => panic!("Expected subquery"),
+            },
+            _ => panic!("Expected Select query"),
+        }
+    }
+
+    #[test]
+    fn test_parse_union_order_by_applies_to_set_operation() {
+        let q =
+            parse_single("SELECT id FROM a UNION SELECT id FROM b ORDER BY 1 LIMIT 10").unwrap();
+        match q {
+            Query::Select(s) => {
+                assert!(s.order_by.is_empty());
+                assert!(s.limit.is_none());
+                let set_op = s.set_op.unwrap();
+                assert_eq!(set_op.order_by.len(), 1);
+                assert!(set_op.limit.is_some());
+            }
+            _ => panic!("Expected Select query"),
+        }
+    }
+
+    #[test]
+    fn test_parse_table_function_args() {
+        let q = parse_single("SELECT * FROM generate_series(1, 10) AS g").unwrap();
+        match q {
+            Query::Select(s) => match &s.from[0] {
+                TableRef::Function { name, args, alias } => {
+                    assert_eq!(name, "generate_series");
+                    assert_eq!(
+                        args,
+                        &vec![
+                            Expression::Literal(Literal::Integer(1)),
+                            Expression::Literal(Literal::Integer(10)),
+                        ]
+                    );
+                    assert_eq!(alias.as_deref(), Some("g"));
+                }
+                _ => panic!("Expected table function"),
+            },
+            _ => panic!("Expected Select query"),
+        }
+    }
+
+    #[test]
+    fn test_parse_unnest_args() {
+        let q = parse_single("SELECT * FROM unnest(tags) AS t").unwrap();
+        match q {
+            Query::Select(s) => match &s.from[0] {
+                TableRef::Function { args, .. } => assert_eq!(args.len(), 1),
+                _ => panic!("Expected table function"),
+            },
+            _ => panic!("Expected Select query"),
+        }
+    }
+
+    #[test]
+    fn test_is_read_only_query() {
+        assert!(is_read_only_query("SELECT * FROM t"));
+        assert!(is_read_only_query("WITH x AS (SELECT 1) SELECT * FROM x"));
+        assert!(is_read_only_query("VALUES (1), (2)"));
+        assert!(!is_read_only_query("SELECT * INTO copy FROM t"));
+        assert!(!is_read_only_query("INSERT INTO t VALUES (1)"));
+        assert!(!is_read_only_query(
+            "WITH d AS (DELETE FROM t RETURNING *) SELECT * FROM d"
+        ));
+        assert!(!is_read_only_query("SELECT 1; DELETE FROM t"));
+    }
+
+    #[test]
+    fn test_select_window() {
+        assert_eq!(select_window("SELECT * FROM t"), Some((None, 0)));
+        assert_eq!(
+            select_window("SELECT * FROM t LIMIT 10 OFFSET 20"),
+            Some((Some(10), 20))
+        );
+        assert_eq!(
+            select_window("WITH c AS (SELECT 1) SELECT * FROM c LIMIT 5"),
+            Some((Some(5), 0))
+        );
+        assert_eq!(
+            select_window("SELECT 1 UNION SELECT 2 LIMIT 5"),
+            Some((Some(5), 0))
+        );
+        assert!(select_window("SELECT * FROM t LIMIT $1").is_none());
+        assert!(select_window("SELECT * FROM t LIMIT 5 FETCH FIRST 3 ROWS ONLY").is_none());
+        assert!(select_window("SELECT * INTO copy FROM t").is_none());
+        assert!(select_window("UPDATE t SET a = 1").is_none());
+        assert!(select_window("SELECT 1; SELECT 2").is_none());
+    }
+
+    #[test]
+    fn test_with_select_window_keeps_the_query() {
+        assert_eq!(
+            with_select_window("SELECT * FROM \"MyTable\" LIMIT 10", 20, 40).unwrap(),
+            "SELECT * FROM \"MyTable\" LIMIT 20 OFFSET 40"
+        );
+        assert_eq!(
+            with_select_window(
+                "SELECT DISTINCT ON (a) a, b FROM t ORDER BY a, b LIMIT 10 OFFSET 10",
+                10,
+                0
+            )
+            .unwrap(),
+            "SELECT DISTINCT ON (a) a, b FROM t ORDER BY a, b LIMIT 10"
+        );
+        let paged = with_select_window(
+            "SELECT count(*) FILTER (WHERE a = ANY(ARRAY[1, 2])) FROM t",
+            100,
+            0,
+        )
+        .unwrap();
+        assert!(
+            paged.contains("FILTER (WHERE a = ANY(ARRAY[1, 2]))"),
+            "{paged}"
+        );
+        assert!(paged.ends_with("LIMIT 100"));
+        assert!(with_select_window("SELECT * FROM t FETCH FIRST 3 ROWS ONLY", 10, 0).is_none());
+    }
+
+    #[test]
+    fn test_without_select_window() {
+        assert_eq!(
+            without_select_window(
+                "SELECT * FROM \"MyTable\" WHERE a = ANY(ARRAY[1, 2]) LIMIT 10 OFFSET 5"
+            )
+            .unwrap(),
+            "SELECT * FROM \"MyTable\" WHERE a = ANY(ARRAY[1, 2])"
+        );
+        assert_eq!(
+            without_select_window(
+                "WITH x AS (SELECT 1 AS a) SELECT DISTINCT ON (a) a FROM x FETCH FIRST 3 ROWS ONLY"
+            )
+            .unwrap(),
+            "WITH x AS (SELECT 1 AS a) SELECT DISTINCT ON (a) a FROM x"
+        );
+        assert!(without_select_window("UPDATE t SET a = 1").is_none());
+    }
+
+    #[test]
+    fn test_without_select_order() {
+        assert_eq!(
+            without_select_order("SELECT a FROM t ORDER BY a DESC LIMIT 5").unwrap(),
+            "SELECT a FROM t LIMIT 5"
+        );
+        assert_eq!(
+            without_select_order("SELECT DISTINCT ON (a) a, b FROM \"Orders\" ORDER BY a, b")
+                .unwrap(),
+            "SELECT DISTINCT ON (a) a, b FROM \"Orders\""
+        );
+        let union = without_select_order("SELECT a FROM t UNION SELECT a FROM u ORDER BY 1");
+        assert_eq!(union.unwrap(), "SELECT a FROM t UNION SELECT a FROM u");
+        assert!(
+            without_select_order("SELECT a FROM t ORDER BY a FETCH FIRST 3 ROWS WITH TIES")
+                .is_none()
+        );
+        assert!(without_select_order("DELETE FROM t").is_none());
+    }
+
+    #[test]
+    fn test_destructive_statement() {
+        let drop = destructive_statement("DROP TABLE IF EXISTS public.users CASCADE").unwrap();
+        assert_eq!(drop.action, "DROP TABLE");
+        assert_eq!(drop.target, "public.users");
+
+        let truncate = destructive_statement("TRUNCATE orders, order_items").unwrap();
+        assert_eq!(truncate.action, "TRUNCATE");
+        assert_eq!(truncate.target, "orders, order_items");
+
+        assert!(destructive_statement("DELETE FROM users").is_none());
+        assert!(destructive_statement("SELECT * FROM users").is_none());
+        assert!(destructive_statement("DROP TABLE").is_none());
+    }
 }
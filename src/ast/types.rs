@@ -15,10 +15,58 @@ pub enum Query {
     Delete(DeleteQuery),
     /// Common Table Expressions wrapping an inner query.
     With(CTEQuery),
+    /// Standalone `VALUES (...), (...)` list, one inner vec per row.
+    Values(Vec<Vec<Expression>>),
     /// Raw SQL passthrough for unsupported or complex statements.
     Raw(String),
 }
 
+impl Query {
+    /// Short statement-kind label, e.g. `SELECT` or `RAW`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Query::Select(_) => "SELECT",
+            Query::Insert(_) => "INSERT",
+            Query::Update(_) => "UPDATE",
+            Query::Delete(_) => "DELETE",
+            Query::With(_) => "WITH",
+            Query::Values(_) => "VALUES",
+            Query::Raw(_) => "RAW",
+        }
+    }
+
+    /// Per output column of a SELECT, the table or alias qualifying it when
+    /// the item is a plain `t.col` reference. `None` when the projection
+    /// has a wildcard, since its columns cannot be matched up by position.
+    pub fn projection_qualifiers(&self) -> Option<Vec<Option<String>>> {
+        let select = match self {
+            Query::Select(select) => select,
+            Query::With(cte) => return cte.body.projection_qualifiers(),
+            _ => return None,
+        };
+        select
+            .projections
+            .iter()
+            .map(|item| match item {
+                SelectItem::Expression {
+                    expr: Expression::Column { table, .. },
+                    alias: None,
+                } => Some(table.clone()),
+                SelectItem::Expression { .. } => Some(None),
+                SelectItem::Wildcard | SelectItem::QualifiedWildcard(_) => None,
+            })
+            .collect()
+    }
+
+    /// Render the query as an indented tree, one line per node, using the
+    /// same `├─`/`└─` connectors as the EXPLAIN plan view.
+    pub fn describe_tree(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        describe_query(self).render("", true, true, &mut lines);
+        lines
+    }
+}
+
 /// A SELECT query with all standard SQL clauses.
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct SelectQuery {
@@ -175,6 +223,8 @@ pub enum Expression {
     Parameter(usize),
     /// Array expression: `ARRAY[...]`.
     Array(Vec<Expression>),
+    /// Row constructor: `(a, b)`, e.g. in a row-value comparison.
+    Row(Vec<Expression>),
     /// JSON access: `expr->key`, `expr->>key`.
     JsonAccess {
         expr: Box<Expression>,
@@ -287,6 +337,11 @@ pub struct SetOperation {
     pub op: SetOperator,
     pub all: bool,
     pub right: Query,
+    /// Trailing ORDER BY/LIMIT/OFFSET applying to the combined result. The
+    /// left-hand `SelectQuery`'s own clauses belong to that operand only.
+    pub order_by: Vec<OrderByExpr>,
+    pub limit: Option<Expression>,
+    pub offset: Option<Expression>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -349,6 +404,306 @@ pub struct DeleteQuery {
     pub returning: Vec<SelectItem>,
 }
 
+/// Intermediate node used by [`Query::describe_tree`].
+struct DescribeNode {
+    label: String,
+    children: Vec<DescribeNode>,
+}
+
+impl DescribeNode {
+    fn leaf(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            children: vec![],
+        }
+    }
+
+    fn branch(label: impl Into<String>, children: Vec<DescribeNode>) -> Self {
+        Self {
+            label: label.into(),
+            children,
+        }
+    }
+
+    fn render(&self, prefix: &str, is_last: bool, is_root: bool, lines: &mut Vec<String>) {
+        let connector = match (is_root, is_last) {
+            (true, _) => "",
+            (false, true) => "└─ ",
+            (false, false) => "├─ ",
+        };
+        lines.push(format!("{}{}{}", prefix, connector, self.label));
+
+        let child_prefix = match (is_root, is_last) {
+            (true, _) => String::new(),
+            (false, true) => format!("{}   ", prefix),
+            (false, false) => format!("{}│  ", prefix),
+        };
+        for (i, child) in self.children.iter().enumerate() {
+            child.render(&child_prefix, i == self.children.len() - 1, false, lines);
+        }
+    }
+}
+
+fn describe_query(query: &Query) -> DescribeNode {
+    use super::compiler::{compile_expr, compile_select_item};
+
+    let expr_list = |label: &str, exprs: &[Expression]| {
+        DescribeNode::branch(
+            label,
+            exprs
+                .iter()
+                .map(|e| DescribeNode::leaf(compile_expr(e)))
+                .collect(),
+        )
+    };
+    let returning = |items: &[SelectItem]| {
+        DescribeNode::branch(
+            "returning",
+            items
+                .iter()
+                .map(|i| DescribeNode::leaf(compile_select_item(i)))
+                .collect(),
+        )
+    };
+
+    match query {
+        Query::Select(s) => describe_select(s),
+        Query::With(cte) => {
+            let mut children: Vec<DescribeNode> = cte
+                .ctes
+                .iter()
+                .map(|c| {
+                    let label = if c.columns.is_empty() {
+                        format!("cte {}", c.name)
+                    } else {
+                        format!("cte {} ({})", c.name, c.columns.join(", "))
+                    };
+                    DescribeNode::branch(label, vec![describe_query(&c.query)])
+                })
+                .collect();
+            children.push(DescribeNode::branch(
+                "body",
+                vec![describe_query(&cte.body)],
+            ));
+            let label = if cte.recursive {
+                "WITH RECURSIVE"
+            } else {
+                "WITH"
+            };
+            DescribeNode::branch(label, children)
+        }
+        Query::Insert(i) => {
+            let mut children = vec![describe_table_ref(&i.table)];
+            if !i.columns.is_empty() {
+                children.push(DescribeNode::leaf(format!(
+                    "columns: {}",
+                    i.columns.join(", ")
+                )));
+            }
+            children.push(match &i.source {
+                InsertSource::Values(rows) => DescribeNode::branch("values", describe_rows(rows)),
+                InsertSource::Query(q) => DescribeNode::branch("source", vec![describe_query(q)]),
+            });
+            if !i.returning.is_empty() {
+                children.push(returning(&i.returning));
+            }
+            DescribeNode::branch("INSERT", children)
+        }
+        Query::Update(u) => {
+            let mut children = vec![describe_table_ref(&u.table)];
+            children.push(DescribeNode::branch(
+                "set",
+                u.assignments
+                    .iter()
+                    .map(|a| {
+                        DescribeNode::leaf(format!("{} = {}", a.column, compile_expr(&a.value)))
+                    })
+                    .collect(),
+            ));
+            if let Some(filter) = &u.filter {
+                children.push(expr_list("filter", std::slice::from_ref(filter)));
+            }
+            if !u.returning.is_empty() {
+                children.push(returning(&u.returning));
+            }
+            DescribeNode::branch("UPDATE", children)
+        }
+        Query::Delete(d) => {
+            let mut children = vec![describe_table_ref(&d.table)];
+            if let Some(filter) = &d.filter {
+                children.push(expr_list("filter", std::slice::from_ref(filter)));
+            }
+            if !d.returning.is_empty() {
+                children.push(returning(&d.returning));
+            }
+            DescribeNode::branch("DELETE", children)
+        }
+        Query::Values(rows) => DescribeNode::branch("VALUES", describe_rows(rows)),
+        Query::Raw(sql) => DescribeNode::branch(
+            "RAW (not represented in the AST)",
+            sql.lines().map(DescribeNode::leaf).collect(),
+        ),
+    }
+}
+
+fn describe_rows(rows: &[Vec<Expression>]) -> Vec<DescribeNode> {
+    rows.iter()
+        .map(|row| {
+            let values: Vec<String> = row.iter().map(super::compiler::compile_expr).collect();
+            DescribeNode::leaf(format!("({})", values.join(", ")))
+        })
+        .collect()
+}
+
+fn describe_select(select: &SelectQuery) -> DescribeNode {
+    use super::compiler::{compile_expr, compile_join, compile_order_by, compile_select_item};
+
+    let mut children = Vec::new();
+    children.push(DescribeNode::branch(
+        "projections",
+        select
+            .projections
+            .iter()
+            .map(|p| DescribeNode::leaf(compile_select_item(p)))
+            .collect(),
+    ));
+    if !select.from.is_empty() {
+        children.push(DescribeNode::branch(
+            "from",
+            select.from.iter().map(describe_table_ref).collect(),
+        ));
+    }
+    if !select.joins.is_empty() {
+        children.push(DescribeNode::branch(
+            "joins",
+            select
+                .joins
+                .iter()
+                .map(|j| match &j.table {
+                    TableRef::Subquery { query, .. } => {
+                        DescribeNode::branch(compile_join(j), vec![describe_query(query)])
+                    }
+                    _ => DescribeNode::leaf(compile_join(j)),
+                })
+                .collect(),
+        ));
+    }
+    if let Some(filter) = &select.filter {
+        children.push(DescribeNode::branch(
+            "filter",
+            vec![DescribeNode::leaf(compile_expr(filter))],
+        ));
+    }
+    if !select.group_by.is_empty() {
+        children.push(DescribeNode::branch(
+            "group by",
+            select
+                .group_by
+                .iter()
+                .map(|e| DescribeNode::leaf(compile_expr(e)))
+                .collect(),
+        ));
+    }
+    if let Some(having) = &select.having {
+        children.push(DescribeNode::branch(
+            "having",
+            vec![DescribeNode::leaf(compile_expr(having))],
+        ));
+    }
+    if !select.windows.is_empty() {
+        children.push(DescribeNode::branch(
+            "windows",
+            select
+                .windows
+                .iter()
+                .map(|w| DescribeNode::leaf(w.name.clone()))
+                .collect(),
+        ));
+    }
+    if let Some(set_op) = &select.set_op {
+        let op = match set_op.op {
+            SetOperator::Union => "UNION",
+            SetOperator::Intersect => "INTERSECT",
+            SetOperator::Except => "EXCEPT",
+        };
+        let label = if set_op.all {
+            format!("{} ALL", op)
+        } else {
+            op.to_string()
+        };
+        let mut set_children = vec![describe_query(&set_op.right)];
+        if !set_op.order_by.is_empty() {
+            set_children.push(DescribeNode::branch(
+                "order by (combined)",
+                set_op
+                    .order_by
+                    .iter()
+                    .map(|o| DescribeNode::leaf(compile_order_by(o)))
+                    .collect(),
+            ));
+        }
+        if let Some(limit) = &set_op.limit {
+            set_children.push(DescribeNode::leaf(format!(
+                "limit (combined): {}",
+                compile_expr(limit)
+            )));
+        }
+        if let Some(offset) = &set_op.offset {
+            set_children.push(DescribeNode::leaf(format!(
+                "offset (combined): {}",
+                compile_expr(offset)
+            )));
+        }
+        children.push(DescribeNode::branch(label, set_children));
+    }
+    if !select.order_by.is_empty() {
+        children.push(DescribeNode::branch(
+            "order by",
+            select
+                .order_by
+                .iter()
+                .map(|o| DescribeNode::leaf(compile_order_by(o)))
+                .collect(),
+        ));
+    }
+    if let Some(limit) = &select.limit {
+        children.push(DescribeNode::leaf(format!(
+            "limit: {}",
+            compile_expr(limit)
+        )));
+    }
+    if let Some(offset) = &select.offset {
+        children.push(DescribeNode::leaf(format!(
+            "offset: {}",
+            compile_expr(offset)
+        )));
+    }
+
+    let label = if select.distinct {
+        "SELECT DISTINCT"
+    } else {
+        "SELECT"
+    };
+    DescribeNode::branch(label, children)
+}
+
+fn describe_table_ref(table: &TableRef) -> DescribeNode {
+    match table {
+        TableRef::Subquery { query, alias } => DescribeNode::branch(
+            format!("subquery AS {}", alias),
+            vec![describe_query(query)],
+        ),
+        TableRef::Function { .. } => DescribeNode::leaf(format!(
+            "function: {}",
+            super::compiler::compile_table_ref(table)
+        )),
+        TableRef::Table { .. } => DescribeNode::leaf(format!(
+            "table: {}",
+            super::compiler::compile_table_ref(table)
+        )),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -425,4 +780,50 @@ mod tests {
         assert_eq!(cte.ctes.len(), 1);
         assert_eq!(cte.ctes[0].name, "recursive_cte");
     }
+
+    #[test]
+    fn test_describe_tree_select() {
+        let q = Query::Select(Box::new(SelectQuery {
+            projections: vec![SelectItem::Wildcard],
+            from: vec![TableRef::Table {
+                schema: None,
+                name: "users".into(),
+                alias: None,
+            }],
+            limit: Some(Expression::Literal(Literal::Integer(10))),
+            ..Default::default()
+        }));
+        assert_eq!(
+            q.describe_tree(),
+            vec![
+                "SELECT",
+                "├─ projections",
+                "│  └─ *",
+                "├─ from",
+                "│  └─ table: users",
+                "└─ limit: 10",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_projection_qualifiers() {
+        use crate::ast::parse_single;
+
+        let q = parse_single("SELECT u.id, o.id, o.total AS t, count(*) FROM u JOIN o ON true")
+            .unwrap();
+        assert_eq!(
+            q.projection_qualifiers(),
+            Some(vec![Some("u".into()), Some("o".into()), None, None])
+        );
+        let star = parse_single("SELECT u.id, * FROM u").unwrap();
+        assert!(star.projection_qualifiers().is_none());
+    }
+
+    #[test]
+    fn test_describe_tree_raw() {
+        let lines = Query::Raw("VACUUM".into()).describe_tree();
+        assert_eq!(lines[0], "RAW (not represented in the AST)");
+        assert_eq!(lines[1], "└─ VACUUM");
+    }
 }
@@ -0,0 +1,119 @@
+//! Opt-in audit trail: one JSON line per executed statement, appended to the
+//! file configured under `[audit]` in `config.toml`. Unlike the query
+//! history it is never trimmed or rewritten by pgrsql.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::db::{ConnectionConfig, QueryResult};
+
+#[derive(Debug, Serialize)]
+pub struct AuditRecord<'a> {
+    pub timestamp: DateTime<Utc>,
+    pub connection: &'a str,
+    pub host: &'a str,
+    pub database: &'a str,
+    pub user: &'a str,
+    pub query: &'a str,
+    pub duration_ms: u64,
+    pub success: bool,
+    /// Rows returned, or affected by a DML statement.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rows: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<&'a str>,
+}
+
+impl<'a> AuditRecord<'a> {
+    /// A record of `query` having run on `config` with `result`.
+    pub fn new(config: &'a ConnectionConfig, query: &'a str, result: &'a QueryResult) -> Self {
+        let rows = result.affected_rows.unwrap_or(result.row_count as u64);
+        Self {
+            timestamp: Utc::now(),
+            connection: &config.name,
+            host: &config.host,
+            database: &config.database,
+            user: &config.username,
+            query,
+            duration_ms: result.execution_time.as_millis() as u64,
+            success: result.error.is_none(),
+            rows: result.error.is_none().then_some(rows),
+            error: result.error.as_ref().map(|err| err.message.as_str()),
+        }
+    }
+}
+
+/// Append `record` as one line to the JSONL file at `path`, creating the
+/// file and its directory if needed.
+pub fn append(path: &Path, record: &AuditRecord) -> std::io::Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut line = serde_json::to_string(record)?;
+    line.push('\n');
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    // One write per line so concurrent sessions don't interleave records
+    file.write_all(line.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::StructuredError;
+    use std::time::Duration;
+
+    fn config() -> ConnectionConfig {
+        ConnectionConfig {
+            name: "prod".to_string(),
+            host: "db.example.com".to_string(),
+            database: "app".to_string(),
+            username: "alice".to_string(),
+            ..ConnectionConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_records_append_as_json_lines() {
+        let path = std::env::temp_dir().join(format!("pgrsql_audit_{}.jsonl", std::process::id()));
+        let config = config();
+
+        let mut ok = QueryResult::from_text_rows(&["n"], vec![vec!["1".into()]]);
+        ok.execution_time = Duration::from_millis(12);
+        append(&path, &AuditRecord::new(&config, "SELECT 1", &ok)).unwrap();
+
+        let failed = QueryResult::error(
+            StructuredError::from_string("relation \"nope\" does not exist".to_string()),
+            Duration::from_millis(3),
+        );
+        append(
+            &path,
+            &AuditRecord::new(&config, "SELECT * FROM nope", &failed),
+        )
+        .unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let lines: Vec<serde_json::Value> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["connection"], "prod");
+        assert_eq!(lines[0]["user"], "alice");
+        assert_eq!(lines[0]["query"], "SELECT 1");
+        assert_eq!(lines[0]["duration_ms"], 12);
+        assert_eq!(lines[0]["success"], true);
+        assert_eq!(lines[0]["rows"], 1);
+        assert!(lines[0].get("error").is_none());
+        assert_eq!(lines[1]["success"], false);
+        assert!(lines[1].get("rows").is_none());
+        assert!(lines[1]["error"]
+            .as_str()
+            .unwrap()
+            .contains("does not exist"));
+    }
+}
@@ -0,0 +1,420 @@
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::db::ConnectionConfig;
+use crate::export::CsvOptions;
+use crate::ui::FormatRule;
+
+/// User preferences persisted to `config.toml` in the pgrsql config directory.
+/// Missing keys fall back to their defaults so older files keep loading.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub editor: EditorSettings,
+    pub query: QuerySettings,
+    pub connection: ConnectionSettings,
+    pub export: ExportSettings,
+    pub sidebar: SidebarSettings,
+    pub audit: AuditSettings,
+    /// Conditional formatting of result cells; the first matching rule wins.
+    pub format_rules: Vec<FormatRule>,
+    /// Action name (e.g. `clear_editor`) to the key or keys that trigger it,
+    /// replacing that action's default bindings.
+    pub keybindings: BTreeMap<String, KeySpec>,
+}
+
+/// One key combination or a list of them, e.g. `"Ctrl+K"` or
+/// `["F5", "Ctrl+Enter"]`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum KeySpec {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeySpec {
+    pub fn keys(&self) -> Vec<&str> {
+        match self {
+            KeySpec::One(key) => vec![key.as_str()],
+            KeySpec::Many(keys) => keys.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct EditorSettings {
+    /// Number of columns per indentation level.
+    pub tab_width: usize,
+    /// Insert a literal tab character instead of spaces.
+    pub use_hard_tabs: bool,
+    /// Strip trailing whitespace from the buffer before a query runs.
+    pub trim_trailing_whitespace: bool,
+    /// Start the editor in vim-style modal editing.
+    pub vim_mode: bool,
+    /// Uppercase SQL keywords as soon as a word boundary is typed after them.
+    pub uppercase_keywords: bool,
+    /// Characters of a word to type before autocomplete pops up on its own.
+    pub autocomplete_min_chars: usize,
+}
+
+impl Default for EditorSettings {
+    fn default() -> Self {
+        Self {
+            tab_width: 4,
+            use_hard_tabs: false,
+            trim_trailing_whitespace: false,
+            vim_mode: false,
+            uppercase_keywords: false,
+            autocomplete_min_chars: 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct QuerySettings {
+    /// Times to re-run a query that failed with a transient error
+    /// (connection loss or serialization failure). 0 disables retries.
+    pub retry_attempts: u32,
+    /// Delay before the first retry; doubled for each further attempt.
+    pub retry_backoff_ms: u64,
+    /// Stop an "execute all" run at the first failing statement.
+    pub stop_on_error: bool,
+}
+
+impl Default for QuerySettings {
+    fn default() -> Self {
+        Self {
+            retry_attempts: 0,
+            retry_backoff_ms: 200,
+            stop_on_error: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ConnectionSettings {
+    /// Ping an idle session with `SELECT 1` at least this often and
+    /// reconnect when the ping fails. 0 disables the keepalive.
+    pub keepalive_secs: u64,
+}
+
+impl ConnectionSettings {
+    pub fn keepalive(&self) -> Option<Duration> {
+        (self.keepalive_secs > 0).then(|| Duration::from_secs(self.keepalive_secs))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ExportSettings {
+    /// Dialect for CSV file exports and CSV clipboard copies.
+    pub csv: CsvOptions,
+    /// Start SQL INSERT exports with a comment listing each column's type.
+    pub sql_type_comments: bool,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        Self {
+            csv: CsvOptions::default(),
+            sql_type_comments: true,
+        }
+    }
+}
+
+/// What Enter does on a table in the sidebar's Tables tab.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TableEnterAction {
+    /// Insert `schema.table` into the editor.
+    #[default]
+    Insert,
+    /// Run `SELECT *` on the table and show the rows in Results.
+    Peek,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SidebarSettings {
+    /// Enter on a table: `"insert"` its name or `"peek"` at its rows.
+    pub table_enter: TableEnterAction,
+    /// Rows fetched when peeking at a table.
+    pub peek_limit: usize,
+}
+
+impl Default for SidebarSettings {
+    fn default() -> Self {
+        Self {
+            table_enter: TableEnterAction::Insert,
+            peek_limit: 100,
+        }
+    }
+}
+
+/// Opt-in JSONL log of every executed statement; see `crate::audit`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AuditSettings {
+    /// File to append to; empty disables the log. A leading `~/` is the
+    /// home directory.
+    pub path: String,
+    /// Connection or database names to log; empty logs every connection.
+    pub connections: Vec<String>,
+}
+
+impl AuditSettings {
+    /// The audit log file for statements run on `config`, if they are
+    /// logged.
+    pub fn log_path(&self, config: &ConnectionConfig) -> Option<PathBuf> {
+        let path = self.path.trim();
+        if path.is_empty() {
+            return None;
+        }
+        let included = self.connections.is_empty()
+            || self
+                .connections
+                .iter()
+                .any(|name| *name == config.name || *name == config.database);
+        if !included {
+            return None;
+        }
+        Some(match (path.strip_prefix("~/"), dirs::home_dir()) {
+            (Some(rest), Some(home)) => home.join(rest),
+            _ => PathBuf::from(path),
+        })
+    }
+}
+
+impl QuerySettings {
+    /// Delay before retry number `attempt` (1-based).
+    pub fn retry_delay(&self, attempt: u32) -> Duration {
+        let factor = 1u64 << attempt.saturating_sub(1).min(16);
+        Duration::from_millis(self.retry_backoff_ms.saturating_mul(factor))
+    }
+}
+
+#[allow(dead_code)]
+impl Settings {
+    pub fn config_path() -> PathBuf {
+        config_file("config.toml")
+    }
+
+    pub fn load() -> Result<Self> {
+        load_toml(&Self::config_path())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        save_toml(&Self::config_path(), self)
+    }
+}
+
+/// Layout and view state remembered between sessions in `prefs.toml`.
+/// Unlike `Settings`, this file is rewritten by pgrsql on quit.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct UiPrefs {
+    /// Color theme name: "dark" or "light".
+    pub theme: String,
+    pub sidebar_width: u16,
+    pub editor_height_percent: u16,
+    pub wrap_selected_row: bool,
+    /// Show timestamps in results relative to now.
+    pub relative_time: bool,
+    /// Pop up autocomplete while typing; Ctrl+Space works either way.
+    pub autocomplete: bool,
+}
+
+impl Default for UiPrefs {
+    fn default() -> Self {
+        Self {
+            theme: "dark".to_string(),
+            sidebar_width: 35,
+            editor_height_percent: 40,
+            wrap_selected_row: false,
+            relative_time: false,
+            autocomplete: true,
+        }
+    }
+}
+
+impl UiPrefs {
+    pub fn prefs_path() -> PathBuf {
+        config_file("prefs.toml")
+    }
+
+    pub fn load() -> Result<Self> {
+        load_toml(&Self::prefs_path())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        save_toml(&Self::prefs_path(), self)
+    }
+}
+
+fn config_file(name: &str) -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("pgrsql")
+        .join(name)
+}
+
+fn load_toml<T: DeserializeOwned + Default>(path: &Path) -> Result<T> {
+    if !path.exists() {
+        return Ok(T::default());
+    }
+    let content = std::fs::read_to_string(path)?;
+    Ok(toml::from_str(&content)?)
+}
+
+fn save_toml<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let content = toml::to_string_pretty(value)?;
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_settings() {
+        let settings = Settings::default();
+        assert_eq!(settings.editor.tab_width, 4);
+        assert!(!settings.editor.use_hard_tabs);
+        assert!(!settings.editor.trim_trailing_whitespace);
+        assert!(!settings.editor.vim_mode);
+        assert!(!settings.editor.uppercase_keywords);
+        assert!(settings.query.stop_on_error);
+        assert_eq!(settings.editor.autocomplete_min_chars, 2);
+        assert_eq!(settings.sidebar.table_enter, TableEnterAction::Insert);
+        assert_eq!(settings.sidebar.peek_limit, 100);
+        assert_eq!(settings.connection.keepalive(), None);
+    }
+
+    #[test]
+    fn test_keepalive_setting() {
+        let settings: Settings = toml::from_str("[connection]\nkeepalive_secs = 60\n").unwrap();
+        assert_eq!(
+            settings.connection.keepalive(),
+            Some(Duration::from_secs(60))
+        );
+    }
+
+    #[test]
+    fn test_audit_log_path() {
+        let config = ConnectionConfig {
+            name: "prod".to_string(),
+            database: "app".to_string(),
+            ..ConnectionConfig::default()
+        };
+        assert_eq!(Settings::default().audit.log_path(&config), None);
+
+        let settings: Settings =
+            toml::from_str("[audit]\npath = \"/var/log/pgrsql.jsonl\"\n").unwrap();
+        assert_eq!(
+            settings.audit.log_path(&config),
+            Some(PathBuf::from("/var/log/pgrsql.jsonl"))
+        );
+
+        let mut audit = settings.audit;
+        audit.connections = vec!["staging".to_string()];
+        assert_eq!(audit.log_path(&config), None);
+        audit.connections.push("app".to_string());
+        assert!(audit.log_path(&config).is_some());
+    }
+
+    #[test]
+    fn test_sidebar_settings() {
+        let settings: Settings = toml::from_str("[sidebar]\ntable_enter = \"peek\"\n").unwrap();
+        assert_eq!(settings.sidebar.table_enter, TableEnterAction::Peek);
+        assert_eq!(settings.sidebar.peek_limit, 100);
+        assert!(toml::from_str::<Settings>("[sidebar]\ntable_enter = \"open\"\n").is_err());
+    }
+
+    #[test]
+    fn test_partial_config_uses_defaults() {
+        let settings: Settings = toml::from_str("[editor]\ntab_width = 2\n").unwrap();
+        assert_eq!(settings.editor.tab_width, 2);
+        assert!(!settings.editor.use_hard_tabs);
+
+        let settings: Settings = toml::from_str("").unwrap();
+        assert_eq!(settings, Settings::default());
+    }
+
+    #[test]
+    fn test_format_rules() {
+        let settings: Settings = toml::from_str(
+            "[[format_rules]]\ncolumn = \"balance\"\nwhen = \"< 0\"\ncolor = \"red\"\n\n\
+             [[format_rules]]\nwhen = \"IS NULL\"\ncolor = \"yellow\"\nbold = true\n",
+        )
+        .unwrap();
+        assert_eq!(settings.format_rules.len(), 2);
+        assert_eq!(settings.format_rules[0].column, "balance");
+        assert!(settings.format_rules[1].column.is_empty());
+        assert!(settings.format_rules[1].bold);
+    }
+
+    #[test]
+    fn test_csv_export_settings() {
+        let settings: Settings =
+            toml::from_str("[export.csv]\ndelimiter = \";\"\ncrlf = true\n").unwrap();
+        assert_eq!(settings.export.csv.delimiter, ';');
+        assert_eq!(settings.export.csv.quote, '"');
+        assert!(settings.export.csv.crlf);
+        assert!(!settings.export.csv.always_quote);
+        assert!(settings.export.sql_type_comments);
+    }
+
+    #[test]
+    fn test_keybindings_one_or_many() {
+        let settings: Settings = toml::from_str(
+            "[keybindings]\nclear_editor = \"Ctrl+K\"\nexecute_query = [\"F5\", \"Ctrl+R\"]\n",
+        )
+        .unwrap();
+        assert_eq!(settings.keybindings["clear_editor"].keys(), vec!["Ctrl+K"]);
+        assert_eq!(
+            settings.keybindings["execute_query"].keys(),
+            vec!["F5", "Ctrl+R"]
+        );
+    }
+
+    #[test]
+    fn test_retry_delay_doubles() {
+        let query = QuerySettings {
+            retry_attempts: 3,
+            retry_backoff_ms: 100,
+            ..Default::default()
+        };
+        assert_eq!(query.retry_delay(1), Duration::from_millis(100));
+        assert_eq!(query.retry_delay(2), Duration::from_millis(200));
+        assert_eq!(query.retry_delay(3), Duration::from_millis(400));
+    }
+
+    #[test]
+    fn test_ui_prefs_defaults_and_partial() {
+        let prefs: UiPrefs = toml::from_str("sidebar_width = 50\n").unwrap();
+        assert_eq!(prefs.sidebar_width, 50);
+        assert_eq!(prefs.theme, "dark");
+        assert_eq!(prefs.editor_height_percent, 40);
+        assert!(prefs.autocomplete);
+    }
+
+    #[test]
+    fn test_settings_round_trip() {
+        let mut settings = Settings::default();
+        settings.editor.use_hard_tabs = true;
+        let content = toml::to_string_pretty(&settings).unwrap();
+        let parsed: Settings = toml::from_str(&content).unwrap();
+        assert_eq!(parsed, settings);
+    }
+}
@@ -1,9 +1,17 @@
 use anyhow::{Context, Result};
+use futures_util::StreamExt;
 use postgres_native_tls::MakeTlsConnector;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
-use std::time::Duration;
-use tokio_postgres::{Client, NoTls};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinHandle;
+use tokio_postgres::tls::TlsStream;
+use tokio_postgres::{AsyncMessage, CancelToken, Client, Connection, NoTls};
+
+use super::{pgpass_password, quote_ident};
 
 /// AWS RDS root certificate bundle (global-bundle.pem)
 /// Contains all AWS RDS Certificate Authority certificates for all regions.
@@ -12,7 +20,8 @@ use tokio_postgres::{Client, NoTls};
 /// Source: https://truststore.pki.rds.amazonaws.com/global/global-bundle.pem
 const AWS_RDS_CA_BUNDLE: &[u8] = include_bytes!("aws-rds-global-bundle.pem");
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// `Debug` masks the password so configs can be logged safely.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ConnectionConfig {
     pub name: String,
     pub host: String,
@@ -35,6 +44,54 @@ pub struct ConnectionConfig {
     /// This is automatically enabled when connecting to *.rds.amazonaws.com hosts.
     #[serde(default)]
     pub use_aws_rds_certs: bool,
+    /// Database the user last worked in on this connection, restored on
+    /// the next connect.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_database: Option<String>,
+    /// Schema (`search_path`) last used on this connection.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_schema: Option<String>,
+    /// Reported to the server as `application_name`, shown in
+    /// `pg_stat_activity`. Empty sends none.
+    #[serde(
+        default = "default_application_name",
+        skip_serializing_if = "is_default_application_name"
+    )]
+    pub application_name: String,
+    /// Which environment this server belongs to; tints the whole frame so
+    /// a production session is hard to mistake for a development one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<Environment>,
+}
+
+fn default_application_name() -> String {
+    "pgrsql".to_string()
+}
+
+fn is_default_application_name(name: &String) -> bool {
+    *name == default_application_name()
+}
+
+impl std::fmt::Debug for ConnectionConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let password = if self.password.is_empty() { "" } else { "***" };
+        f.debug_struct("ConnectionConfig")
+            .field("name", &self.name)
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("database", &self.database)
+            .field("username", &self.username)
+            .field("password", &password)
+            .field("ssl_mode", &self.ssl_mode)
+            .field("accept_invalid_certs", &self.accept_invalid_certs)
+            .field("ca_cert_path", &self.ca_cert_path)
+            .field("use_aws_rds_certs", &self.use_aws_rds_certs)
+            .field("last_database", &self.last_database)
+            .field("last_schema", &self.last_schema)
+            .field("application_name", &self.application_name)
+            .field("environment", &self.environment)
+            .finish()
+    }
 }
 
 /// SSL/TLS connection modes for PostgreSQL.
@@ -55,6 +112,44 @@ pub enum SslMode {
     VerifyFull,
 }
 
+/// Deployment environment tag of a connection, written in lowercase in
+/// `connections.toml` (`environment = "prod"`).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Environment {
+    Dev,
+    Staging,
+    Prod,
+}
+
+impl Environment {
+    pub fn label(self) -> &'static str {
+        match self {
+            Environment::Dev => "DEV",
+            Environment::Staging => "STAGING",
+            Environment::Prod => "PROD",
+        }
+    }
+
+    /// The tag after `current` when cycling through none, dev, staging and
+    /// prod (or backwards).
+    pub fn cycle(current: Option<Environment>, forward: bool) -> Option<Environment> {
+        let order = [
+            None,
+            Some(Environment::Dev),
+            Some(Environment::Staging),
+            Some(Environment::Prod),
+        ];
+        let pos = order.iter().position(|env| *env == current).unwrap_or(0);
+        let next = if forward {
+            (pos + 1) % order.len()
+        } else {
+            (pos + order.len() - 1) % order.len()
+        };
+        order[next]
+    }
+}
+
 impl ConnectionConfig {
     pub fn connection_string(&self) -> String {
         let sslmode = match self.ssl_mode {
@@ -64,7 +159,7 @@ impl ConnectionConfig {
             SslMode::VerifyCa => "verify-ca",
             SslMode::VerifyFull => "verify-full",
         };
-        format!(
+        let mut conn_string = format!(
             "host={} port={} dbname={} user={} password={} sslmode={} connect_timeout=10",
             quote_conn_value(&self.host),
             self.port,
@@ -72,7 +167,36 @@ impl ConnectionConfig {
             quote_conn_value(&self.username),
             quote_conn_value(&self.password),
             sslmode
-        )
+        );
+        if !self.application_name.is_empty() {
+            conn_string.push_str(" application_name=");
+            conn_string.push_str(&quote_conn_value(&self.application_name));
+        }
+        conn_string
+    }
+
+    /// This config pointed at the database last used on it, if any.
+    pub fn resume(&self) -> ConnectionConfig {
+        let mut config = self.clone();
+        if let Some(db) = &self.last_database {
+            config.database = db.clone();
+        }
+        config
+    }
+
+    /// `edited` as the replacement for this saved entry. The remembered
+    /// database and schema carry over unless the database was changed.
+    pub fn with_edits(&self, edited: &ConnectionConfig) -> ConnectionConfig {
+        let mut config = edited.clone();
+        if edited.database == self.resume().database {
+            config.database = self.database.clone();
+            config.last_database = self.last_database.clone();
+            config.last_schema = self.last_schema.clone();
+        } else {
+            config.last_database = None;
+            config.last_schema = None;
+        }
+        config
     }
 
     pub fn display_string(&self) -> String {
@@ -109,65 +233,348 @@ impl Default for ConnectionConfig {
             accept_invalid_certs: false,
             ca_cert_path: None,
             use_aws_rds_certs: false,
+            last_database: None,
+            last_schema: None,
+            application_name: default_application_name(),
+            environment: None,
+        }
+    }
+}
+
+/// A NOTICE, WARNING or other non-error message raised by the server, e.g.
+/// from `RAISE NOTICE` in PL/pgSQL.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notice {
+    /// Severity as reported by the server, e.g. "NOTICE" or "WARNING".
+    pub severity: String,
+    pub message: String,
+    pub detail: Option<String>,
+    pub hint: Option<String>,
+}
+
+impl std::fmt::Display for Notice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)?;
+        if let Some(detail) = &self.detail {
+            write!(f, " ({})", detail)?;
         }
+        if let Some(hint) = &self.hint {
+            write!(f, " HINT: {}", hint)?;
+        }
+        Ok(())
     }
 }
 
 pub struct ConnectionManager {
     pub config: ConnectionConfig,
-    pub client: Option<Client>,
+    /// Shared so running queries can execute on a background task.
+    pub client: Option<Arc<Client>>,
+    /// Second connection for catalog queries (schema browser, inspector),
+    /// so they don't queue behind a long-running query on `client`.
+    catalog_client: Option<Arc<Client>>,
+    /// The catalog connection being opened; see `poll_catalog_client`.
+    pending_catalog: Option<JoinHandle<Result<Client>>>,
     pub current_database: String,
     pub current_schema: String,
+    /// Round-trip time of the most recent successful `ping`.
+    pub last_ping: Option<Duration>,
+    /// When `ping` last ran, successful or not.
+    pub last_ping_at: Option<Instant>,
+    /// Short server version, e.g. "PostgreSQL 16.2".
+    pub server_version: Option<String>,
+    /// Effective role the session runs as.
+    pub current_role: Option<String>,
+    /// Whether `current_role` has `rolsuper`.
+    pub is_superuser: bool,
+    /// Schemas on the session `search_path`, in resolution order.
+    pub search_path: Vec<String>,
+    /// Whether the user opened a transaction on the session (`BEGIN`) that
+    /// hasn't ended yet. Reset whenever the session is replaced.
+    pub in_transaction: bool,
+    /// Notices from every connection made through this manager.
+    notice_tx: UnboundedSender<Notice>,
+    notice_rx: UnboundedReceiver<Notice>,
 }
 
+/// How long a ping may take before the connection is reported as stalled.
+const PING_TIMEOUT: Duration = Duration::from_secs(2);
+
 #[allow(dead_code)]
 impl ConnectionManager {
     pub fn new() -> Self {
+        let (notice_tx, notice_rx) = mpsc::unbounded_channel();
         Self {
             config: ConnectionConfig::default(),
             client: None,
+            catalog_client: None,
+            pending_catalog: None,
             current_database: String::from("postgres"),
             current_schema: String::from("public"),
+            last_ping: None,
+            last_ping_at: None,
+            server_version: None,
+            current_role: None,
+            is_superuser: false,
+            search_path: vec![String::from("public")],
+            in_transaction: false,
+            notice_tx,
+            notice_rx,
         }
     }
 
+    /// Sender to pass to `create_client` so the new connection's notices
+    /// arrive in `drain_notices`.
+    pub fn notice_sender(&self) -> UnboundedSender<Notice> {
+        self.notice_tx.clone()
+    }
+
+    /// Take every notice received since the last call, oldest first.
+    pub fn drain_notices(&mut self) -> Vec<Notice> {
+        let mut notices = Vec::new();
+        while let Ok(notice) = self.notice_rx.try_recv() {
+            notices.push(notice);
+        }
+        notices
+    }
+
     pub fn apply_client(&mut self, config: ConnectionConfig, client: Client) {
         self.current_database = config.database.clone();
         self.config = config;
-        self.client = Some(client);
+        self.client = Some(Arc::new(client));
+        self.catalog_client = None;
+        if let Some(handle) = self.pending_catalog.take() {
+            handle.abort();
+        }
+        self.last_ping = None;
+        self.last_ping_at = None;
+        self.server_version = None;
+        self.current_role = None;
+        self.is_superuser = false;
+        self.search_path.clear();
+        self.in_transaction = false;
     }
 
     pub async fn connect(&mut self, config: ConnectionConfig) -> Result<()> {
-        let client = create_client(&config).await?;
+        let client = create_client(&config, Some(self.notice_sender())).await?;
         self.apply_client(config, client);
+        let _ = self.load_server_info().await;
+        self.open_catalog_client();
+        Ok(())
+    }
+
+    /// Start opening the catalog connection with the current settings on a
+    /// background task. Until `poll_catalog_client` picks it up (or if it
+    /// fails) catalog queries share the session connection.
+    pub fn open_catalog_client(&mut self) {
+        if let Some(handle) = self.pending_catalog.take() {
+            handle.abort();
+        }
+        let config = self.config.clone();
+        self.pending_catalog = Some(tokio::spawn(
+            async move { create_client(&config, None).await },
+        ));
+    }
+
+    /// Adopt the catalog connection once `open_catalog_client` has opened
+    /// it, giving it the session's search_path.
+    pub async fn poll_catalog_client(&mut self) {
+        let Some(handle) = self.pending_catalog.take_if(|h| h.is_finished()) else {
+            return;
+        };
+        if let Ok(Ok(client)) = handle.await {
+            self.catalog_client = Some(Arc::new(client));
+            self.sync_catalog_search_path();
+        }
+    }
+
+    /// Give the catalog connection the session's search_path, so unqualified
+    /// names resolve there as they do for the user.
+    fn sync_catalog_search_path(&self) {
+        let (Some(client), false) = (self.catalog_client.clone(), self.search_path.is_empty())
+        else {
+            return;
+        };
+        let schemas: Vec<String> = self.search_path.iter().map(|s| quote_ident(s)).collect();
+        let sql = format!("SET search_path TO {}", schemas.join(", "));
+        // Statements on one connection run in order, so later catalog
+        // queries see the new path without waiting here
+        tokio::spawn(async move {
+            let _ = client.batch_execute(&sql).await;
+        });
+    }
+
+    /// Connection to run catalog queries on: the dedicated one while it is
+    /// alive, else the session connection. Inside a transaction the session
+    /// is used too, so catalog reads see its uncommitted DDL.
+    pub fn catalog_client(&self) -> Option<&Arc<Client>> {
+        self.catalog_client
+            .as_ref()
+            .filter(|client| !client.is_closed() && !self.in_transaction)
+            .or(self.client.as_ref())
+    }
+
+    /// Fetch the server version, effective role and its superuser flag for
+    /// display in the header.
+    pub async fn load_server_info(&mut self) -> Result<()> {
+        let client = self.client.as_ref().context("Not connected")?;
+        let row = client
+            .query_one(
+                "SELECT version(), current_user::text, \
+                 COALESCE((SELECT rolsuper FROM pg_roles WHERE rolname = current_user), false), \
+                 current_schemas(false)::text[]",
+                &[],
+            )
+            .await?;
+        let version: String = row.get(0);
+        self.server_version = Some(short_server_version(&version));
+        self.current_role = Some(row.get(1));
+        self.is_superuser = row.get(2);
+        self.search_path = row.get(3);
+        self.sync_catalog_search_path();
         Ok(())
     }
 
     pub async fn disconnect(&mut self) {
         self.client = None;
+        self.catalog_client = None;
+        if let Some(handle) = self.pending_catalog.take() {
+            handle.abort();
+        }
+        self.last_ping = None;
+        self.last_ping_at = None;
+        self.in_transaction = false;
+    }
+
+    /// Time a `SELECT 1` round trip on a background task, so a stalled
+    /// server can't block the caller. Pass the outcome to `record_ping`.
+    pub fn start_ping(&mut self) -> Option<JoinHandle<Result<Duration>>> {
+        let client = self.client.clone()?;
+        self.last_ping_at = Some(Instant::now());
+        Some(tokio::spawn(async move {
+            let start = Instant::now();
+            tokio::time::timeout(PING_TIMEOUT, client.simple_query("SELECT 1"))
+                .await
+                .context("Ping timed out")??;
+            Ok(start.elapsed())
+        }))
+    }
+
+    /// Record a finished ping in `last_ping`. A failed or timed-out ping
+    /// clears it so the UI can show the connection as stalled.
+    pub fn record_ping(&mut self, outcome: &Result<Duration>) {
+        self.last_ping = outcome.as_ref().ok().copied();
+    }
+
+    /// Open a transaction on the session connection.
+    pub async fn begin_transaction(&mut self) -> Result<()> {
+        let client = self.client.as_ref().context("Not connected")?;
+        client.batch_execute("BEGIN").await?;
+        self.in_transaction = true;
+        Ok(())
+    }
+
+    /// End the transaction; a failed COMMIT ends it too.
+    pub async fn commit_transaction(&mut self) -> Result<()> {
+        let client = self.client.as_ref().context("Not connected")?;
+        self.in_transaction = false;
+        client.batch_execute("COMMIT").await?;
+        Ok(())
+    }
+
+    pub async fn rollback_transaction(&mut self) -> Result<()> {
+        let client = self.client.as_ref().context("Not connected")?;
+        self.in_transaction = false;
+        client.batch_execute("ROLLBACK").await?;
+        Ok(())
+    }
+
+    /// Whether `interval` has passed since the last ping.
+    pub fn ping_due(&self, interval: Duration) -> bool {
+        self.last_ping_at
+            .map(|at| at.elapsed() >= interval)
+            .unwrap_or(true)
     }
 
     pub fn is_connected(&self) -> bool {
         self.client.is_some()
     }
 
+    /// Cancel whatever the session is running, roll back an open
+    /// transaction and drop the connection. The connection is dropped even
+    /// when the cancel request fails. Returns the outcomes of the cancel
+    /// request and of the ROLLBACK.
+    pub async fn cancel_all_and_disconnect(&mut self) -> (Result<()>, Result<()>) {
+        let Some(client) = self.client.take() else {
+            return (Ok(()), Ok(()));
+        };
+        let cancelled = tokio::time::timeout(
+            PING_TIMEOUT,
+            cancel_query(&self.config, client.cancel_token()),
+        )
+        .await
+        .unwrap_or_else(|_| Err(anyhow::anyhow!("Cancel request timed out")));
+
+        // Runs once the cancelled statement has ended; outside a transaction
+        // the server only warns. Closing the session would roll back anyway.
+        let rolled_back =
+            match tokio::time::timeout(PING_TIMEOUT, client.batch_execute("ROLLBACK")).await {
+                Ok(done) => done.map_err(Into::into),
+                Err(_) => Err(anyhow::anyhow!("ROLLBACK timed out")),
+            };
+        self.drain_notices();
+
+        drop(client);
+        self.disconnect().await;
+        self.server_version = None;
+        self.current_role = None;
+        self.is_superuser = false;
+        (cancelled, rolled_back)
+    }
+
+    /// Reconnect to `database` with the current settings. The existing
+    /// session is kept if the new connection fails.
     pub async fn switch_database(&mut self, database: &str) -> Result<()> {
         let mut new_config = self.config.clone();
         new_config.database = database.to_string();
-        self.disconnect().await;
-        self.connect(new_config).await
+        let client = create_client(&new_config, Some(self.notice_sender()))
+            .await
+            .with_context(|| format!("Could not connect to database \"{}\"", database))?;
+        self.apply_client(new_config, client);
+        let _ = self.load_server_info().await;
+        self.open_catalog_client();
+        Ok(())
     }
 
     pub async fn switch_schema(&mut self, schema: &str) -> Result<()> {
         if let Some(client) = &self.client {
             client
-                .execute(&format!("SET search_path TO {}", schema), &[])
+                .execute(&format!("SET search_path TO {}", quote_ident(schema)), &[])
                 .await?;
             self.current_schema = schema.to_string();
+            self.search_path = vec![schema.to_string()];
+            self.sync_catalog_search_path();
         }
         Ok(())
     }
 
+    /// Re-read `current_schema` and `search_path` from the server, e.g. after
+    /// the user ran `SET search_path` themselves.
+    pub async fn refresh_current_schema(&mut self) -> Result<()> {
+        let client = self.client.as_ref().context("Not connected")?;
+        let row = client
+            .query_one(
+                "SELECT current_schema()::text, current_schemas(false)::text[]",
+                &[],
+            )
+            .await?;
+        if let Some(schema) = row.get::<_, Option<String>>(0) {
+            self.current_schema = schema;
+        }
+        self.search_path = row.get(1);
+        self.sync_catalog_search_path();
+        Ok(())
+    }
+
     pub fn get_config_path() -> PathBuf {
         dirs::config_dir()
             .unwrap_or_else(|| PathBuf::from("."))
@@ -190,11 +597,7 @@ impl ConnectionManager {
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent)?;
         }
-        let saved = SavedConnections {
-            connections: connections.to_vec(),
-        };
-        let content = toml::to_string_pretty(&saved)?;
-        std::fs::write(&path, content)?;
+        std::fs::write(&path, connections_toml(connections)?)?;
         Ok(())
     }
 
@@ -226,10 +629,43 @@ struct SavedConnections {
     connections: Vec<ConnectionConfig>,
 }
 
+/// The saved-connections file content. Passwords are cleared before
+/// serializing, on top of the field being skipped, so none reach disk.
+fn connections_toml(connections: &[ConnectionConfig]) -> Result<String> {
+    let saved = SavedConnections {
+        connections: connections
+            .iter()
+            .map(|config| ConnectionConfig {
+                password: String::new(),
+                ..config.clone()
+            })
+            .collect(),
+    };
+    Ok(toml::to_string_pretty(&saved)?)
+}
+
 /// Create a PostgreSQL client without needing a ConnectionManager.
 /// This is `Send` so it can be used with `tokio::spawn`.
-pub async fn create_client(config: &ConnectionConfig) -> Result<Client> {
-    let conn_string = config.connection_string();
+///
+/// Server notices are forwarded to `notices` when given and dropped otherwise.
+/// An empty password is looked up in the user's `.pgpass` file, as psql does.
+pub async fn create_client(
+    config: &ConnectionConfig,
+    notices: Option<UnboundedSender<Notice>>,
+) -> Result<Client> {
+    let pgpass = if config.password.is_empty() {
+        pgpass_password(config)
+    } else {
+        None
+    };
+    let conn_string = match pgpass {
+        Some(password) => ConnectionConfig {
+            password,
+            ..config.clone()
+        }
+        .connection_string(),
+        None => config.connection_string(),
+    };
     let timeout = Duration::from_secs(15);
 
     let client = match config.ssl_mode {
@@ -239,11 +675,7 @@ pub async fn create_client(config: &ConnectionConfig) -> Result<Client> {
                     .await
                     .map_err(|_| anyhow::anyhow!("Connection timed out after 15s"))?
                     .context("Failed to connect to PostgreSQL")?;
-            tokio::spawn(async move {
-                if let Err(e) = connection.await {
-                    eprintln!("Connection error: {}", e);
-                }
-            });
+            spawn_connection(connection, notices);
             client
         }
         SslMode::Prefer | SslMode::Require => {
@@ -254,11 +686,7 @@ pub async fn create_client(config: &ConnectionConfig) -> Result<Client> {
                     .await
                     .map_err(|_| anyhow::anyhow!("Connection timed out after 15s"))?
                     .context("Failed to connect to PostgreSQL")?;
-            tokio::spawn(async move {
-                if let Err(e) = connection.await {
-                    eprintln!("Connection error: {}", e);
-                }
-            });
+            spawn_connection(connection, notices);
             client
         }
         SslMode::VerifyCa | SslMode::VerifyFull => {
@@ -269,11 +697,7 @@ pub async fn create_client(config: &ConnectionConfig) -> Result<Client> {
                     .await
                     .map_err(|_| anyhow::anyhow!("Connection timed out after 15s"))?
                     .context("Failed to connect to PostgreSQL with certificate verification")?;
-            tokio::spawn(async move {
-                if let Err(e) = connection.await {
-                    eprintln!("Connection error: {}", e);
-                }
-            });
+            spawn_connection(connection, notices);
             client
         }
     };
@@ -281,6 +705,71 @@ pub async fn create_client(config: &ConnectionConfig) -> Result<Client> {
     Ok(client)
 }
 
+/// Connect with `config`, ask for the server version and disconnect again,
+/// returning e.g. "PostgreSQL 16.2 (42ms)".
+pub async fn test_connection(config: &ConnectionConfig) -> Result<String> {
+    let started = Instant::now();
+    let client = create_client(config, None).await?;
+    let row = client.query_one("SELECT version()", &[]).await?;
+    let version: String = row.get(0);
+    Ok(format!(
+        "{} ({}ms)",
+        short_server_version(&version),
+        started.elapsed().as_millis()
+    ))
+}
+
+/// Ask the server to cancel the statement running on `token`'s session,
+/// over a new connection secured like the original one.
+async fn cancel_query(config: &ConnectionConfig, token: CancelToken) -> Result<()> {
+    match config.ssl_mode {
+        SslMode::Disable => token.cancel_query(NoTls).await?,
+        SslMode::Prefer | SslMode::Require => {
+            token
+                .cancel_query(build_tls_connector(config, false)?)
+                .await?
+        }
+        SslMode::VerifyCa | SslMode::VerifyFull => {
+            token
+                .cancel_query(build_tls_connector(config, true)?)
+                .await?
+        }
+    }
+    Ok(())
+}
+
+/// Drive `connection` on a background task, forwarding notices to `notices`.
+fn spawn_connection<S, T>(
+    mut connection: Connection<S, T>,
+    notices: Option<UnboundedSender<Notice>>,
+) where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    T: TlsStream + Unpin + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut messages = futures_util::stream::poll_fn(move |cx| connection.poll_message(cx));
+        while let Some(message) = messages.next().await {
+            match message {
+                Ok(AsyncMessage::Notice(notice)) => {
+                    if let Some(tx) = &notices {
+                        let _ = tx.send(Notice {
+                            severity: notice.severity().to_string(),
+                            message: notice.message().to_string(),
+                            detail: notice.detail().map(str::to_string),
+                            hint: notice.hint().map(str::to_string),
+                        });
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    eprintln!("Connection error: {}", e);
+                    break;
+                }
+            }
+        }
+    });
+}
+
 /// Build a TLS connector with appropriate certificate configuration.
 ///
 /// # Arguments
@@ -403,6 +892,16 @@ fn base64_decode(input: &str) -> Result<Vec<u8>> {
     Ok(output)
 }
 
+/// Trim `version()` output such as "PostgreSQL 16.2 (Debian 16.2-1) on
+/// x86_64-pc-linux-gnu, compiled by ..." down to "PostgreSQL 16.2".
+fn short_server_version(version: &str) -> String {
+    version
+        .split_whitespace()
+        .take(2)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 /// Quote a value for use in a libpq key=value connection string.
 /// Wraps in single quotes and escapes backslashes and single quotes.
 fn quote_conn_value(value: &str) -> String {
@@ -414,6 +913,22 @@ fn quote_conn_value(value: &str) -> String {
 mod tests {
     use super::*;
 
+    // --- Server info ---
+
+    #[test]
+    fn test_short_server_version() {
+        assert_eq!(
+            short_server_version(
+                "PostgreSQL 16.2 (Debian 16.2-1.pgdg120+2) on x86_64-pc-linux-gnu, compiled by gcc"
+            ),
+            "PostgreSQL 16.2"
+        );
+        assert_eq!(
+            short_server_version("PostgreSQL 9.6.24"),
+            "PostgreSQL 9.6.24"
+        );
+    }
+
     // --- ConnectionConfig ---
 
     #[test]
@@ -448,6 +963,53 @@ mod tests {
         assert!(s.contains("user='myuser'"));
         assert!(s.contains("password='mypass'"));
         assert!(s.contains("sslmode=require"));
+        assert!(s.contains("application_name='pgrsql'"));
+    }
+
+    #[test]
+    fn test_application_name() {
+        let config = ConnectionConfig {
+            application_name: "etl job".into(),
+            ..Default::default()
+        };
+        assert!(config
+            .connection_string()
+            .contains("application_name='etl job'"));
+
+        let config = ConnectionConfig {
+            application_name: String::new(),
+            ..Default::default()
+        };
+        assert!(!config.connection_string().contains("application_name"));
+
+        // Missing from older files means the default, which is not written back
+        let config: ConnectionConfig = toml::from_str(
+            "name = \"a\"\nhost = \"h\"\nport = 5432\ndatabase = \"d\"\nusername = \"u\"\nssl_mode = \"Prefer\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.application_name, "pgrsql");
+        assert!(!toml::to_string(&config)
+            .unwrap()
+            .contains("application_name"));
+    }
+
+    #[test]
+    fn test_environment() {
+        let config: ConnectionConfig = toml::from_str(
+            "name = \"a\"\nhost = \"h\"\nport = 5432\ndatabase = \"d\"\nusername = \"u\"\nssl_mode = \"Prefer\"\nenvironment = \"prod\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.environment, Some(Environment::Prod));
+        assert!(toml::to_string(&config)
+            .unwrap()
+            .contains("environment = \"prod\""));
+        assert!(!toml::to_string(&ConnectionConfig::default())
+            .unwrap()
+            .contains("environment"));
+
+        assert_eq!(Environment::cycle(None, true), Some(Environment::Dev));
+        assert_eq!(Environment::cycle(Some(Environment::Prod), true), None);
+        assert_eq!(Environment::cycle(None, false), Some(Environment::Prod));
     }
 
     #[test]
@@ -545,6 +1107,7 @@ mod tests {
     fn test_new_manager() {
         let mgr = ConnectionManager::new();
         assert!(!mgr.is_connected());
+        assert!(mgr.catalog_client().is_none());
         assert_eq!(mgr.current_database, "postgres");
         assert_eq!(mgr.current_schema, "public");
     }
@@ -614,5 +1177,116 @@ mod tests {
         assert_eq!(config.name, "Test");
         assert_eq!(config.ssl_mode, SslMode::Require);
         assert!(config.password.is_empty());
+        assert!(config.last_database.is_none());
+        assert_eq!(config.resume().database, "testdb");
+    }
+
+    #[test]
+    fn test_resume_last_location() {
+        let config = ConnectionConfig {
+            database: "postgres".into(),
+            last_database: Some("analytics".into()),
+            last_schema: Some("reporting".into()),
+            ..Default::default()
+        };
+        let toml_str = toml::to_string(&config).unwrap();
+        assert!(toml_str.contains("last_schema = \"reporting\""));
+        let parsed: ConnectionConfig = toml::from_str(&toml_str).unwrap();
+        assert_eq!(parsed.resume().database, "analytics");
+        assert_eq!(parsed.database, "postgres");
+    }
+
+    // --- Notices ---
+
+    #[test]
+    fn test_notice_display() {
+        let mut notice = Notice {
+            severity: "NOTICE".into(),
+            message: "table \"t\" does not exist, skipping".into(),
+            detail: None,
+            hint: None,
+        };
+        assert_eq!(
+            notice.to_string(),
+            "NOTICE: table \"t\" does not exist, skipping"
+        );
+        notice.severity = "WARNING".into();
+        notice.hint = Some("Run VACUUM".into());
+        assert!(notice.to_string().starts_with("WARNING: "));
+        assert!(notice.to_string().ends_with(" HINT: Run VACUUM"));
+    }
+
+    #[test]
+    fn test_drain_notices() {
+        let mut manager = ConnectionManager::new();
+        assert!(manager.drain_notices().is_empty());
+        let tx = manager.notice_sender();
+        for message in ["one", "two"] {
+            tx.send(Notice {
+                severity: "NOTICE".into(),
+                message: message.into(),
+                detail: None,
+                hint: None,
+            })
+            .unwrap();
+        }
+        let drained = manager.drain_notices();
+        assert_eq!(drained.len(), 2);
+        assert_eq!(drained[0].message, "one");
+        assert!(manager.drain_notices().is_empty());
+    }
+
+    #[test]
+    fn test_with_edits_keeps_resume_location() {
+        let saved = ConnectionConfig {
+            name: "prod".into(),
+            host: "db1".into(),
+            database: "postgres".into(),
+            last_database: Some("analytics".into()),
+            last_schema: Some("reporting".into()),
+            ..Default::default()
+        };
+
+        // The dialog shows the resumed database; saving it keeps the entry's own
+        let mut edited = saved.resume();
+        edited.host = "db2".into();
+        let updated = saved.with_edits(&edited);
+        assert_eq!(updated.host, "db2");
+        assert_eq!(updated.database, "postgres");
+        assert_eq!(updated.last_schema.as_deref(), Some("reporting"));
+
+        // Picking another database drops the remembered location
+        edited.database = "billing".into();
+        let updated = saved.with_edits(&edited);
+        assert_eq!(updated.database, "billing");
+        assert!(updated.last_database.is_none());
+        assert!(updated.last_schema.is_none());
+    }
+
+    #[test]
+    fn test_saved_connections_never_contain_password() {
+        let connections = vec![ConnectionConfig {
+            name: "prod".into(),
+            password: "s3cret-pw".into(),
+            ..Default::default()
+        }];
+        let content = connections_toml(&connections).unwrap();
+        assert!(!content.contains("s3cret-pw"));
+        assert!(!content.contains("password"));
+
+        let loaded: SavedConnections = toml::from_str(&content).unwrap();
+        assert_eq!(loaded.connections[0].name, "prod");
+        assert!(loaded.connections[0].password.is_empty());
+    }
+
+    #[test]
+    fn test_debug_masks_password() {
+        let config = ConnectionConfig {
+            password: "s3cret-pw".into(),
+            ..Default::default()
+        };
+        let debug = format!("{:?}", config);
+        assert!(!debug.contains("s3cret-pw"));
+        assert!(debug.contains("***"));
     }
 }
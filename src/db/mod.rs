@@ -1,7 +1,9 @@
 mod connection;
+mod pgpass;
 mod query;
 mod schema;
 
 pub use connection::*;
+pub use pgpass::*;
 pub use query::*;
 pub use schema::*;
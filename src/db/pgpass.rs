@@ -0,0 +1,143 @@
+use std::path::PathBuf;
+
+use super::ConnectionConfig;
+
+/// One `hostname:port:database:username:password` line of a password file.
+/// `*` in any of the first four fields matches anything.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgpassEntry {
+    pub host: String,
+    pub port: String,
+    pub database: String,
+    pub username: String,
+    pub password: String,
+}
+
+impl PgpassEntry {
+    fn matches(&self, config: &ConnectionConfig) -> bool {
+        let field = |pattern: &str, value: &str| pattern == "*" || pattern == value;
+        field(&self.host, &config.host)
+            && field(&self.port, &config.port.to_string())
+            && field(&self.database, &config.database)
+            && field(&self.username, &config.username)
+    }
+}
+
+/// Parse password file content. Blank lines, `#` comments and lines
+/// without all five fields are skipped; `\:` and `\\` are unescaped.
+pub fn parse_pgpass(content: &str) -> Vec<PgpassEntry> {
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut fields = split_fields(line).into_iter();
+            Some(PgpassEntry {
+                host: fields.next()?,
+                port: fields.next()?,
+                database: fields.next()?,
+                username: fields.next()?,
+                password: fields.next()?,
+            })
+        })
+        .collect()
+}
+
+/// The password of the first entry matching `config`, as libpq picks it.
+pub fn find_pgpass_password(entries: &[PgpassEntry], config: &ConnectionConfig) -> Option<String> {
+    entries
+        .iter()
+        .find(|entry| entry.matches(config))
+        .map(|entry| entry.password.clone())
+}
+
+/// Look up `config` in the user's password file: `$PGPASSFILE`, else
+/// `~/.pgpass` (`%APPDATA%\postgresql\pgpass.conf` on Windows). As with
+/// libpq, a file others can read is ignored on Unix.
+pub fn pgpass_password(config: &ConnectionConfig) -> Option<String> {
+    let path = pgpass_path()?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if std::fs::metadata(&path).ok()?.permissions().mode() & 0o077 != 0 {
+            return None;
+        }
+    }
+    let content = std::fs::read_to_string(path).ok()?;
+    find_pgpass_password(&parse_pgpass(&content), config)
+}
+
+fn pgpass_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("PGPASSFILE") {
+        return Some(PathBuf::from(path));
+    }
+    if cfg!(windows) {
+        dirs::config_dir().map(|dir| dir.join("postgresql").join("pgpass.conf"))
+    } else {
+        dirs::home_dir().map(|dir| dir.join(".pgpass"))
+    }
+}
+
+/// Split on unescaped `:`; the password keeps any further colons.
+fn split_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(next) = chars.next() {
+                    fields.last_mut().unwrap().push(next);
+                }
+            }
+            ':' if fields.len() < 5 => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(host: &str, port: u16, database: &str, username: &str) -> ConnectionConfig {
+        ConnectionConfig {
+            host: host.into(),
+            port,
+            database: database.into(),
+            username: username.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_pgpass() {
+        let entries = parse_pgpass(
+            "# comment\n\ndb1:5432:app:alice:pw1\n*:*:*:bob:p\\:w\\\\2\nincomplete:line\n",
+        );
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].host, "db1");
+        assert_eq!(entries[0].password, "pw1");
+        assert_eq!(entries[1].host, "*");
+        assert_eq!(entries[1].password, "p:w\\2");
+    }
+
+    #[test]
+    fn test_password_keeps_unescaped_colons() {
+        let entries = parse_pgpass("h:1:d:u:a:b\n");
+        assert_eq!(entries[0].password, "a:b");
+    }
+
+    #[test]
+    fn test_first_matching_entry_wins() {
+        let entries = parse_pgpass("db1:5432:app:alice:exact\n*:*:*:alice:fallback\n");
+        assert_eq!(
+            find_pgpass_password(&entries, &config("db1", 5432, "app", "alice")).as_deref(),
+            Some("exact")
+        );
+        assert_eq!(
+            find_pgpass_password(&entries, &config("db2", 6543, "other", "alice")).as_deref(),
+            Some("fallback")
+        );
+        assert!(find_pgpass_password(&entries, &config("db1", 5432, "app", "carol")).is_none());
+    }
+}
@@ -1,9 +1,13 @@
 use anyhow::Result;
-use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use futures_util::TryStreamExt;
+use std::collections::{HashMap, HashSet};
 use std::error::Error as StdError;
 use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::{Duration, Instant};
-use tokio_postgres::{types::Type, Client, Row};
+use tokio_postgres::types::{FromSql, Type};
+use tokio_postgres::{Client, Row};
 
 /// Categorized error types for SQL query failures.
 #[derive(Debug, Clone, PartialEq)]
@@ -150,6 +154,17 @@ impl StructuredError {
         }
     }
 
+    /// Whether re-running the same statement may succeed: connection failures
+    /// and serialization failures (40001). Syntax and semantic errors never are.
+    pub fn is_transient(&self) -> bool {
+        self.category == ErrorCategory::Connection || self.code == "40001"
+    }
+
+    /// Plain-language explanation of the SQLSTATE code, if it is a common one.
+    pub fn suggestion(&self) -> Option<&'static str> {
+        sqlstate_suggestion(&self.code)
+    }
+
     /// Format as a single display string (for status bar, history, etc.)
     pub fn display_message(&self) -> String {
         self.message.clone()
@@ -205,6 +220,11 @@ impl StructuredError {
             lines.push(format!("  Context: {}", where_));
         }
 
+        // Friendly explanation of the SQLSTATE
+        if let Some(suggestion) = self.suggestion() {
+            lines.push(format!("  Suggestion: {}", suggestion));
+        }
+
         lines.join("\n")
     }
 }
@@ -276,6 +296,101 @@ fn categorize_sqlstate(code: &str) -> ErrorCategory {
     }
 }
 
+/// Map common SQLSTATE codes to an actionable explanation for the user.
+fn sqlstate_suggestion(code: &str) -> Option<&'static str> {
+    let suggestion = match code {
+        // Class 23: Integrity Constraint Violation
+        "23502" => "A required column was left NULL; supply a value or give the column a DEFAULT.",
+        "23503" => "The referenced row does not exist (or is still referenced); check the FOREIGN KEY target.",
+        "23505" => "A row with this key already exists; the value violates a UNIQUE constraint.",
+        "23514" => "The row fails a CHECK constraint; adjust the values to satisfy it.",
+        // Class 22: Data Exception
+        "22001" => "The value is too long for the column's type; shorten it or widen the column.",
+        "22003" => "A number is out of range for its type; use a wider numeric type.",
+        "22007" | "22008" => "The date/time value is malformed or out of range; check its format.",
+        "22012" => "Division by zero; guard the divisor with NULLIF(divisor, 0).",
+        "22P02" => "A value could not be converted to the target type; check the literal or add an explicit cast.",
+        // Class 42: Syntax Error or Access Rule Violation
+        "42601" => "The statement has a syntax error near the reported position.",
+        "42501" => "The current role lacks the privilege for this operation; ask for a GRANT or switch roles.",
+        "42703" => "The column does not exist; check its spelling, quoting, and table alias.",
+        "42P01" => "The table or view does not exist; check its name, schema, and search_path.",
+        "42883" => "No function matches the name and argument types; add explicit casts to the arguments.",
+        "42702" => "The column name is ambiguous; qualify it with a table name or alias.",
+        "42803" => "A column must appear in GROUP BY or be used inside an aggregate function.",
+        "42804" => "The expression's type does not match what is expected; add an explicit cast.",
+        "42P07" => "An object with this name already exists; use IF NOT EXISTS or pick another name.",
+        // Class 25/40: Transactions
+        "25P02" => "The transaction was aborted by an earlier error; run ROLLBACK before continuing.",
+        "40001" => "Serialization failure under concurrent load; retry the transaction.",
+        "40P01" => "Deadlock detected; retry the transaction and take locks in a consistent order.",
+        // Class 53/57: Resources and operator intervention
+        "53100" => "The server is out of disk space.",
+        "57014" => "The statement was canceled, usually by statement_timeout or a user request.",
+        _ => return None,
+    };
+    Some(suggestion)
+}
+
+/// One row-level difference between two result sets, as display strings.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RowDiff {
+    Added(Vec<String>),
+    Removed(Vec<String>),
+    Changed {
+        before: Vec<String>,
+        after: Vec<String>,
+    },
+}
+
+/// Row-level diff of `before` -> `after`, matching rows on column `key_col`.
+/// Returns `None` unless both results have the same column names and
+/// `key_col` exists. Removed and changed rows come in `before` order, then
+/// added rows in `after` order; for duplicate keys the first row wins.
+pub fn diff_results(
+    before: &QueryResult,
+    after: &QueryResult,
+    key_col: usize,
+) -> Option<Vec<RowDiff>> {
+    let names = |r: &QueryResult| r.columns.iter().map(|c| c.name.clone()).collect::<Vec<_>>();
+    if names(before) != names(after) || key_col >= before.columns.len() {
+        return None;
+    }
+
+    let display = |row: &[CellValue]| row.iter().map(|c| c.display()).collect::<Vec<_>>();
+    let mut after_by_key: HashMap<String, Vec<String>> = HashMap::new();
+    for row in &after.rows {
+        after_by_key
+            .entry(row[key_col].display())
+            .or_insert_with(|| display(row));
+    }
+
+    let mut diffs = Vec::new();
+    let mut seen = HashSet::new();
+    for row in &before.rows {
+        let key = row[key_col].display();
+        if !seen.insert(key.clone()) {
+            continue;
+        }
+        let before_row = display(row);
+        match after_by_key.get(&key) {
+            None => diffs.push(RowDiff::Removed(before_row)),
+            Some(after_row) if *after_row != before_row => diffs.push(RowDiff::Changed {
+                before: before_row,
+                after: after_row.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    for row in &after.rows {
+        let key = row[key_col].display();
+        if seen.insert(key) {
+            diffs.push(RowDiff::Added(display(row)));
+        }
+    }
+    Some(diffs)
+}
+
 #[derive(Debug, Clone)]
 pub struct QueryResult {
     pub columns: Vec<ColumnInfo>,
@@ -304,6 +419,8 @@ pub enum CellValue {
     Int64(i64),
     Float32(f32),
     Float64(f64),
+    /// A NUMERIC in its exact text form, as `psql` shows it.
+    Numeric(String),
     Text(String),
     Bytes(Vec<u8>),
     Date(NaiveDate),
@@ -316,6 +433,20 @@ pub enum CellValue {
 }
 
 impl CellValue {
+    /// The value as a number, for integer, floating-point and NUMERIC
+    /// cells. NUMERIC values may lose precision here.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            CellValue::Int16(i) => Some(*i as f64),
+            CellValue::Int32(i) => Some(*i as f64),
+            CellValue::Int64(i) => Some(*i as f64),
+            CellValue::Float32(f) => Some(*f as f64),
+            CellValue::Float64(f) => Some(*f),
+            CellValue::Numeric(n) => n.parse().ok(),
+            _ => None,
+        }
+    }
+
     pub fn display(&self) -> String {
         match self {
             CellValue::Null => "NULL".to_string(),
@@ -325,6 +456,7 @@ impl CellValue {
             CellValue::Int64(i) => i.to_string(),
             CellValue::Float32(f) => f.to_string(),
             CellValue::Float64(f) => f.to_string(),
+            CellValue::Numeric(n) => n.clone(),
             CellValue::Text(s) => s.clone(),
             CellValue::Bytes(b) => format!("[{} bytes]", b.len()),
             CellValue::Date(d) => d.to_string(),
@@ -340,6 +472,41 @@ impl CellValue {
         }
     }
 
+    /// `display` adjusted by on-screen `options`; `now` anchors relative
+    /// times. Exports and copies keep using `display`.
+    pub fn display_with(&self, options: DisplayOptions, now: DateTime<Utc>) -> String {
+        let at = match self {
+            CellValue::TimestampTz(dt) if options.relative_time => Some(*dt),
+            // Without a time zone, read the timestamp as local time
+            CellValue::DateTime(dt) if options.relative_time => Local
+                .from_local_datetime(dt)
+                .earliest()
+                .map(|local| local.with_timezone(&Utc)),
+            _ => None,
+        };
+        match at {
+            Some(at) => relative_time(now.signed_duration_since(at)),
+            None => self.display(),
+        }
+    }
+
+    /// Pretty-printed JSON for `Json` cells, and for text holding a JSON
+    /// object or array (JSON kept in a `text` column).
+    pub fn pretty_json(&self) -> Option<String> {
+        match self {
+            CellValue::Json(value) => serde_json::to_string_pretty(value).ok(),
+            CellValue::Text(text) | CellValue::Unknown(text) => {
+                let trimmed = text.trim_start();
+                if !trimmed.starts_with('{') && !trimmed.starts_with('[') {
+                    return None;
+                }
+                let value: serde_json::Value = serde_json::from_str(text).ok()?;
+                serde_json::to_string_pretty(&value).ok()
+            }
+            _ => None,
+        }
+    }
+
     pub fn display_width(&self) -> usize {
         unicode_width::UnicodeWidthStr::width(self.display().as_str())
     }
@@ -358,6 +525,188 @@ impl QueryResult {
         }
     }
 
+    /// Build a result from plain text rows, e.g. for output generated locally
+    /// rather than fetched from the server.
+    pub fn from_text_rows(column_names: &[&str], rows: Vec<Vec<String>>) -> Self {
+        let mut columns: Vec<ColumnInfo> = column_names
+            .iter()
+            .map(|name| ColumnInfo {
+                name: name.to_string(),
+                type_name: "text".to_string(),
+                max_width: name.len(),
+            })
+            .collect();
+        let rows: Vec<Vec<CellValue>> = rows
+            .into_iter()
+            .map(|row| row.into_iter().map(CellValue::Text).collect())
+            .collect();
+        for row in &rows {
+            for (col, cell) in columns.iter_mut().zip(row) {
+                col.max_width = col.max_width.max(cell.display_width());
+            }
+        }
+        Self {
+            columns,
+            row_count: rows.len(),
+            rows,
+            execution_time: Duration::ZERO,
+            affected_rows: None,
+            error: None,
+        }
+    }
+
+    /// Approximate size of the fetched data: the bytes of each cell's text
+    /// form, or of its raw contents for `bytea`. NULLs count as nothing.
+    pub fn approx_bytes(&self) -> usize {
+        self.rows
+            .iter()
+            .flatten()
+            .map(|cell| match cell {
+                CellValue::Null => 0,
+                CellValue::Text(s) | CellValue::Unknown(s) => s.len(),
+                CellValue::Bytes(b) => b.len(),
+                other => other.display().len(),
+            })
+            .sum()
+    }
+
+    /// Whether column `col` holds numbers: every non-NULL cell converts with
+    /// `CellValue::as_f64`, and there is at least one.
+    pub fn is_numeric_column(&self, col: usize) -> bool {
+        let mut cells = self
+            .rows
+            .iter()
+            .filter_map(|row| row.get(col))
+            .filter(|cell| !matches!(cell, CellValue::Null))
+            .peekable();
+        cells.peek().is_some() && cells.all(|cell| cell.as_f64().is_some())
+    }
+
+    /// Summarize how `self` differs from an earlier `before` result: row
+    /// count, execution time, and added, removed or retyped columns.
+    pub fn compare(&self, before: &QueryResult) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        let status = |r: &QueryResult| match &r.error {
+            Some(err) => format!("error ({})", err.category),
+            None => "ok".to_string(),
+        };
+        if before.error.is_some() || self.error.is_some() {
+            lines.push(format!("Status: {} -> {}", status(before), status(self)));
+        }
+
+        let delta = self.row_count as i64 - before.row_count as i64;
+        lines.push(format!(
+            "Rows: {} -> {} ({:+})",
+            before.row_count, self.row_count, delta
+        ));
+        if let (Some(a), Some(b)) = (before.affected_rows, self.affected_rows) {
+            lines.push(format!("Rows affected: {} -> {}", a, b));
+        }
+        lines.push(format!(
+            "Time: {:.2}ms -> {:.2}ms",
+            before.execution_time.as_secs_f64() * 1000.0,
+            self.execution_time.as_secs_f64() * 1000.0
+        ));
+
+        let find = |cols: &[ColumnInfo], name: &str| {
+            cols.iter()
+                .find(|c| c.name == name)
+                .map(|c| c.type_name.clone())
+        };
+        let added: Vec<&str> = self
+            .columns
+            .iter()
+            .filter(|c| find(&before.columns, &c.name).is_none())
+            .map(|c| c.name.as_str())
+            .collect();
+        let removed: Vec<&str> = before
+            .columns
+            .iter()
+            .filter(|c| find(&self.columns, &c.name).is_none())
+            .map(|c| c.name.as_str())
+            .collect();
+        let retyped: Vec<String> = self
+            .columns
+            .iter()
+            .filter_map(|c| {
+                let old = find(&before.columns, &c.name)?;
+                (old != c.type_name).then(|| format!("{} {} -> {}", c.name, old, c.type_name))
+            })
+            .collect();
+
+        if added.is_empty() && removed.is_empty() && retyped.is_empty() {
+            lines.push(format!("Columns: unchanged ({})", self.columns.len()));
+        }
+        if !added.is_empty() {
+            lines.push(format!("Columns added: {}", added.join(", ")));
+        }
+        if !removed.is_empty() {
+            lines.push(format!("Columns removed: {}", removed.join(", ")));
+        }
+        if !retyped.is_empty() {
+            lines.push(format!("Column types changed: {}", retyped.join(", ")));
+        }
+        lines
+    }
+
+    /// Header labels that tell apart columns sharing a name, as a join
+    /// selecting `id` from two tables returns. A repeated name is qualified
+    /// with its table from `qualifiers` (one per column) when known, else
+    /// numbered from the second occurrence: `id`, `id:2`.
+    pub fn column_labels(&self, qualifiers: Option<&[Option<String>]>) -> Vec<String> {
+        let mut seen: HashMap<&str, usize> = HashMap::new();
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(i, col)| {
+                let name = col.name.as_str();
+                let occurrence = seen.entry(name).or_insert(0);
+                *occurrence += 1;
+                let duplicated = self.columns.iter().filter(|c| c.name == name).count() > 1;
+                let qualifier = qualifiers
+                    .filter(|q| q.len() == self.columns.len())
+                    .and_then(|q| q[i].as_deref());
+                match (duplicated, qualifier) {
+                    (false, _) => name.to_string(),
+                    (true, Some(table)) => format!("{}.{}", table, name),
+                    (true, None) if *occurrence == 1 => name.to_string(),
+                    (true, None) => format!("{}:{}", name, occurrence),
+                }
+            })
+            .collect()
+    }
+
+    /// Cell-by-cell changes since an earlier run `before` of the same
+    /// query. `None` unless both have the same columns and row count.
+    pub fn cell_changes(&self, before: &QueryResult) -> Option<Vec<Vec<CellChange>>> {
+        let same_columns = self.columns.len() == before.columns.len()
+            && self
+                .columns
+                .iter()
+                .zip(&before.columns)
+                .all(|(a, b)| a.name == b.name);
+        if self.error.is_some()
+            || before.error.is_some()
+            || !same_columns
+            || self.rows.len() != before.rows.len()
+        {
+            return None;
+        }
+        let changes = self
+            .rows
+            .iter()
+            .zip(&before.rows)
+            .map(|(row, old_row)| {
+                row.iter()
+                    .zip(old_row)
+                    .map(|(new, old)| CellChange::between(old, new))
+                    .collect()
+            })
+            .collect();
+        Some(changes)
+    }
+
     pub fn error(err: StructuredError, execution_time: Duration) -> Self {
         Self {
             columns: vec![],
@@ -370,7 +719,100 @@ impl QueryResult {
     }
 }
 
-pub async fn execute_query(client: &Client, sql: &str) -> Result<QueryResult> {
+/// On-screen display toggles for result cells.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DisplayOptions {
+    /// Show timestamps as "3 hours ago" / "in 2 days".
+    pub relative_time: bool,
+}
+
+/// Humanize how long ago (positive `elapsed`) or ahead (negative) a moment is.
+pub fn relative_time(elapsed: chrono::Duration) -> String {
+    let secs = elapsed.num_seconds();
+    let abs = secs.unsigned_abs();
+    if abs < 45 {
+        return "just now".to_string();
+    }
+    const UNITS: [(u64, &str); 6] = [
+        (365 * 86_400, "year"),
+        (30 * 86_400, "month"),
+        (86_400, "day"),
+        (3_600, "hour"),
+        (60, "minute"),
+        (1, "second"),
+    ];
+    let (size, unit) = UNITS
+        .iter()
+        .copied()
+        .find(|(size, _)| abs >= *size)
+        .unwrap_or((1, "second"));
+    let n = abs / size;
+    let amount = format!("{} {}{}", n, unit, if n == 1 { "" } else { "s" });
+    if secs < 0 {
+        format!("in {}", amount)
+    } else {
+        format!("{} ago", amount)
+    }
+}
+
+/// How a result cell differs from the same cell in the previous run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CellChange {
+    Unchanged,
+    Increased,
+    Decreased,
+    /// Changed, but not comparable as numbers.
+    Changed,
+}
+
+impl CellChange {
+    fn between(old: &CellValue, new: &CellValue) -> Self {
+        let (old, new) = (old.display(), new.display());
+        if old == new {
+            return CellChange::Unchanged;
+        }
+        match (old.parse::<f64>(), new.parse::<f64>()) {
+            (Ok(a), Ok(b)) if b > a => CellChange::Increased,
+            (Ok(a), Ok(b)) if b < a => CellChange::Decreased,
+            _ => CellChange::Changed,
+        }
+    }
+}
+
+/// Format a row count with thousands separators, e.g. 12400 -> "12,400".
+pub fn format_row_count(n: usize) -> String {
+    let digits = n.to_string();
+    digits
+        .as_bytes()
+        .rchunks(3)
+        .rev()
+        .map(|group| std::str::from_utf8(group).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Format a byte count with a binary unit, e.g. 1536 -> "1.5 KB".
+pub fn format_bytes(n: usize) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if n < 1024 {
+        return format!("{} B", n);
+    }
+    let mut size = n as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Run `sql`, streaming result rows so `rows_received` can be watched while
+/// the query is still in flight.
+pub async fn execute_query(
+    client: &Client,
+    sql: &str,
+    rows_received: &AtomicUsize,
+) -> Result<QueryResult> {
     let start = Instant::now();
     let sql_trimmed = sql.trim();
 
@@ -383,7 +825,7 @@ pub async fn execute_query(client: &Client, sql: &str) -> Result<QueryResult> {
         || sql_upper.starts_with("TABLE");
 
     if is_select {
-        match client.query(sql_trimmed, &[]).await {
+        match fetch_rows(client, sql_trimmed, rows_received).await {
             Ok(rows) => {
                 let execution_time = start.elapsed();
                 let result = parse_rows(&rows, execution_time);
@@ -417,6 +859,21 @@ pub async fn execute_query(client: &Client, sql: &str) -> Result<QueryResult> {
     }
 }
 
+async fn fetch_rows(
+    client: &Client,
+    sql: &str,
+    rows_received: &AtomicUsize,
+) -> Result<Vec<Row>, tokio_postgres::Error> {
+    let stream = client.query_raw(sql, std::iter::empty::<i32>()).await?;
+    let mut stream = std::pin::pin!(stream);
+    let mut rows = Vec::new();
+    while let Some(row) = stream.try_next().await? {
+        rows.push(row);
+        rows_received.fetch_add(1, Ordering::Relaxed);
+    }
+    Ok(rows)
+}
+
 fn parse_rows(rows: &[Row], execution_time: Duration) -> QueryResult {
     if rows.is_empty() {
         return QueryResult {
@@ -477,6 +934,7 @@ fn parse_rows(rows: &[Row], execution_time: Duration) -> QueryResult {
 }
 
 #[cfg(test)]
+#[allow(clippy::items_after_test_module)]
 mod tests {
     use super::*;
 
@@ -501,6 +959,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_float_display() {
         assert_eq!(CellValue::Float32(3.14).display(), "3.14");
         assert_eq!(CellValue::Float64(2.718).display(), "2.718");
@@ -638,6 +1097,294 @@ mod tests {
         assert_eq!(categorize_sqlstate("08006"), ErrorCategory::Connection);
         assert_eq!(categorize_sqlstate("XX000"), ErrorCategory::Unknown);
     }
+
+    #[test]
+    fn test_format_row_count() {
+        assert_eq!(format_row_count(0), "0");
+        assert_eq!(format_row_count(999), "999");
+        assert_eq!(format_row_count(12400), "12,400");
+        assert_eq!(format_row_count(1234567), "1,234,567");
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1536), "1.5 KB");
+        assert_eq!(format_bytes(20 * 1024 * 1024), "20.0 MB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 * 1024), "3.0 GB");
+    }
+
+    #[test]
+    fn test_approx_bytes() {
+        let mut result = QueryResult::from_text_rows(&["a", "b"], vec![]);
+        result.rows = vec![
+            vec![CellValue::Text("héllo".into()), CellValue::Null],
+            vec![CellValue::Int32(12345), CellValue::Bytes(vec![0; 10])],
+        ];
+        assert_eq!(result.approx_bytes(), 6 + 5 + 10);
+    }
+
+    fn numeric_bytes(weight: i16, sign: u16, dscale: u16, digits: &[u16]) -> Vec<u8> {
+        let mut raw = Vec::new();
+        for word in [digits.len() as u16, weight as u16, sign, dscale]
+            .into_iter()
+            .chain(digits.iter().copied())
+        {
+            raw.extend_from_slice(&word.to_be_bytes());
+        }
+        raw
+    }
+
+    #[test]
+    fn test_decode_numeric() {
+        let decode = |weight, sign, dscale, digits: &[u16]| {
+            decode_numeric(&numeric_bytes(weight, sign, dscale, digits)).unwrap()
+        };
+        assert_eq!(decode(0, 0, 10, &[1, 0, 0, 100]), "1.0000000001");
+        assert_eq!(decode(1, 0, 2, &[1, 2345, 6700]), "12345.67");
+        assert_eq!(decode(-1, 0, 3, &[10]), "0.001");
+        assert_eq!(decode(1, 0x4000, 0, &[10]), "-100000");
+        assert_eq!(decode(0, 0, 2, &[]), "0.00");
+        assert_eq!(
+            decode(4, 0, 10, &[1234, 5678, 9012, 3456, 7890, 1234, 5678, 9]),
+            "12345678901234567890.1234567800"
+        );
+        assert_eq!(decode(0, 0xC000, 0, &[]), "NaN");
+        assert_eq!(decode(0, 0xF000, 0, &[]), "-Infinity");
+        assert!(decode_numeric(&[0, 1]).is_none());
+        assert!(decode_numeric(&numeric_bytes(0, 0, 0, &[10000])).is_none());
+    }
+
+    #[test]
+    fn test_numeric_display() {
+        let n = CellValue::Numeric("1.0000000001".into());
+        assert_eq!(n.display(), "1.0000000001");
+        assert_eq!(n.display_width(), 12);
+        assert_eq!(n.as_f64(), Some(1.0000000001));
+    }
+
+    #[test]
+    fn test_is_numeric_column() {
+        let mut result = QueryResult::from_text_rows(&["name", "n", "empty"], vec![]);
+        result.rows = vec![
+            vec![
+                CellValue::Text("a".into()),
+                CellValue::Int64(3),
+                CellValue::Null,
+            ],
+            vec![
+                CellValue::Text("b".into()),
+                CellValue::Null,
+                CellValue::Null,
+            ],
+            vec![
+                CellValue::Text("c".into()),
+                CellValue::Float64(1.5),
+                CellValue::Null,
+            ],
+        ];
+        assert!(!result.is_numeric_column(0));
+        assert!(result.is_numeric_column(1));
+        assert!(!result.is_numeric_column(2));
+        assert_eq!(result.rows[2][1].as_f64(), Some(1.5));
+    }
+
+    #[test]
+    fn test_is_transient() {
+        let mut err = StructuredError::from_string("could not serialize access".to_string());
+        assert!(!err.is_transient());
+        err.code = "40001".to_string();
+        assert!(err.is_transient());
+
+        err.code = "42601".to_string();
+        err.category = ErrorCategory::Syntax;
+        assert!(!err.is_transient());
+        err.code = String::new();
+        err.category = ErrorCategory::Connection;
+        assert!(err.is_transient());
+    }
+
+    #[test]
+    fn test_sqlstate_suggestion() {
+        let mut err = StructuredError::from_string("duplicate key".to_string());
+        assert!(err.suggestion().is_none());
+
+        err.code = "23505".to_string();
+        assert!(err.suggestion().unwrap().contains("UNIQUE"));
+        assert!(err.display_full().contains("Suggestion: "));
+        assert!(sqlstate_suggestion("XX000").is_none());
+    }
+
+    #[test]
+    fn test_from_text_rows_widths() {
+        let result = QueryResult::from_text_rows(
+            &["k", "value"],
+            vec![vec!["kind".into(), "SELECT".into()]],
+        );
+        assert_eq!(result.row_count, 1);
+        assert_eq!(result.columns[0].max_width, 4);
+        assert_eq!(result.columns[1].max_width, 6);
+        assert_eq!(result.rows[0][1].display(), "SELECT");
+    }
+
+    #[test]
+    fn test_compare_results() {
+        let before = QueryResult::from_text_rows(
+            &["id", "name"],
+            vec![vec!["1".into(), "a".into()], vec!["2".into(), "b".into()]],
+        );
+        let mut after =
+            QueryResult::from_text_rows(&["id", "email"], vec![vec!["1".into(), "x".into()]]);
+        after.columns[0].type_name = "int4".to_string();
+
+        let lines = after.compare(&before);
+        assert!(lines.contains(&"Rows: 2 -> 1 (-1)".to_string()));
+        assert!(lines.contains(&"Columns added: email".to_string()));
+        assert!(lines.contains(&"Columns removed: name".to_string()));
+        assert!(lines.contains(&"Column types changed: id text -> int4".to_string()));
+
+        let same = before.compare(&before);
+        assert!(same.contains(&"Rows: 2 -> 2 (+0)".to_string()));
+        assert!(same.contains(&"Columns: unchanged (2)".to_string()));
+    }
+
+    #[test]
+    fn test_pretty_json() {
+        let text = CellValue::Text(r#"{"id": 1, "tags": ["a"]}"#.into());
+        assert_eq!(
+            text.pretty_json().unwrap(),
+            "{\n  \"id\": 1,\n  \"tags\": [\n    \"a\"\n  ]\n}"
+        );
+        assert!(CellValue::Json(serde_json::json!([1]))
+            .pretty_json()
+            .is_some());
+        // Scalars and broken JSON are left alone
+        assert!(CellValue::Text("42".into()).pretty_json().is_none());
+        assert!(CellValue::Text("{not json".into()).pretty_json().is_none());
+        assert!(CellValue::Int32(1).pretty_json().is_none());
+    }
+
+    #[test]
+    fn test_relative_time() {
+        let secs = chrono::Duration::seconds;
+        assert_eq!(relative_time(secs(10)), "just now");
+        assert_eq!(relative_time(secs(-10)), "just now");
+        assert_eq!(relative_time(secs(90)), "1 minute ago");
+        assert_eq!(relative_time(secs(3 * 3600 + 59)), "3 hours ago");
+        assert_eq!(relative_time(secs(-2 * 86_400)), "in 2 days");
+        assert_eq!(relative_time(secs(400 * 86_400)), "1 year ago");
+    }
+
+    #[test]
+    fn test_display_with_relative_time() {
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let cell = CellValue::TimestampTz(now - chrono::Duration::hours(5));
+        let relative = DisplayOptions {
+            relative_time: true,
+        };
+        assert_eq!(cell.display_with(relative, now), "5 hours ago");
+        assert_eq!(
+            cell.display_with(DisplayOptions::default(), now),
+            cell.display()
+        );
+        // Other types are unaffected
+        let text = CellValue::Text("2024-05-01".into());
+        assert_eq!(text.display_with(relative, now), "2024-05-01");
+    }
+
+    #[test]
+    fn test_column_labels() {
+        let result = QueryResult::from_text_rows(&["id", "name", "id", "id"], vec![]);
+        assert_eq!(
+            result.column_labels(None),
+            vec!["id", "name", "id:2", "id:3"]
+        );
+
+        let qualifiers = vec![Some("u".to_string()), None, Some("o".to_string()), None];
+        assert_eq!(
+            result.column_labels(Some(&qualifiers)),
+            vec!["u.id", "name", "o.id", "id:3"]
+        );
+
+        let unique = QueryResult::from_text_rows(&["a", "b"], vec![]);
+        assert_eq!(unique.column_labels(None), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_cell_changes() {
+        let before = QueryResult::from_text_rows(
+            &["name", "total"],
+            vec![
+                vec!["a".into(), "10".into()],
+                vec!["b".into(), "2.5".into()],
+            ],
+        );
+        let after = QueryResult::from_text_rows(
+            &["name", "total"],
+            vec![vec!["a".into(), "12".into()], vec!["c".into(), "2".into()]],
+        );
+        let changes = after.cell_changes(&before).unwrap();
+        assert_eq!(
+            changes,
+            vec![
+                vec![CellChange::Unchanged, CellChange::Increased],
+                vec![CellChange::Changed, CellChange::Decreased],
+            ]
+        );
+
+        let fewer_rows = QueryResult::from_text_rows(&["name", "total"], vec![]);
+        assert!(fewer_rows.cell_changes(&before).is_none());
+        let renamed = QueryResult::from_text_rows(
+            &["name", "sum"],
+            vec![
+                vec!["a".into(), "10".into()],
+                vec!["b".into(), "2.5".into()],
+            ],
+        );
+        assert!(renamed.cell_changes(&before).is_none());
+    }
+
+    #[test]
+    fn test_diff_results() {
+        let before = QueryResult::from_text_rows(
+            &["id", "name"],
+            vec![
+                vec!["1".into(), "a".into()],
+                vec!["2".into(), "b".into()],
+                vec!["3".into(), "c".into()],
+            ],
+        );
+        let after = QueryResult::from_text_rows(
+            &["id", "name"],
+            vec![
+                vec!["1".into(), "a".into()],
+                vec!["3".into(), "C".into()],
+                vec!["4".into(), "d".into()],
+            ],
+        );
+
+        let diffs = diff_results(&before, &after, 0).unwrap();
+        assert_eq!(
+            diffs,
+            vec![
+                RowDiff::Removed(vec!["2".into(), "b".into()]),
+                RowDiff::Changed {
+                    before: vec!["3".into(), "c".into()],
+                    after: vec!["3".into(), "C".into()],
+                },
+                RowDiff::Added(vec!["4".into(), "d".into()]),
+            ]
+        );
+
+        // Keyed on name, the renamed row shows up as a removal plus an addition
+        let diffs = diff_results(&before, &after, 1).unwrap();
+        assert_eq!(diffs.len(), 4);
+
+        let other = QueryResult::from_text_rows(&["id"], vec![]);
+        assert!(diff_results(&before, &other, 0).is_none());
+        assert!(diff_results(&before, &after, 5).is_none());
+    }
 }
 
 fn extract_value(row: &Row, idx: usize, pg_type: &Type) -> CellValue {
@@ -673,12 +1420,18 @@ fn extract_value(row: &Row, idx: usize, pg_type: &Type) -> CellValue {
             .flatten()
             .map(CellValue::Float32)
             .unwrap_or(CellValue::Null),
-        Type::FLOAT8 | Type::NUMERIC => row
+        Type::FLOAT8 => row
             .try_get::<_, Option<f64>>(idx)
             .ok()
             .flatten()
             .map(CellValue::Float64)
             .unwrap_or(CellValue::Null),
+        Type::NUMERIC => row
+            .try_get::<_, Option<PgNumeric>>(idx)
+            .ok()
+            .flatten()
+            .map(|n| CellValue::Numeric(n.0))
+            .unwrap_or(CellValue::Null),
         Type::TEXT | Type::VARCHAR | Type::NAME | Type::CHAR | Type::BPCHAR => row
             .try_get::<_, Option<String>>(idx)
             .ok()
@@ -731,3 +1484,77 @@ fn extract_value(row: &Row, idx: usize, pg_type: &Type) -> CellValue {
         }
     }
 }
+
+/// A NUMERIC read in PostgreSQL's binary format and kept as its exact
+/// decimal text, since `f64` would round big or long-scaled values.
+struct PgNumeric(String);
+
+impl<'a> FromSql<'a> for PgNumeric {
+    fn from_sql(_: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        decode_numeric(raw)
+            .map(PgNumeric)
+            .ok_or_else(|| "invalid numeric value".into())
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        *ty == Type::NUMERIC
+    }
+}
+
+/// Decode the binary NUMERIC format: a header of digit count, weight (the
+/// power of 10000 of the first digit), sign and display scale, followed by
+/// base-10000 digits. Returns the text `psql` would show.
+fn decode_numeric(raw: &[u8]) -> Option<String> {
+    let word = |i: usize| {
+        raw.get(i * 2..i * 2 + 2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]))
+    };
+    let ndigits = word(0)? as usize;
+    let weight = word(1)? as i16 as i32;
+    let sign = word(2)?;
+    let dscale = word(3)? as usize;
+    match sign {
+        0x0000 | 0x4000 => {}
+        0xC000 => return Some("NaN".to_string()),
+        0xD000 => return Some("Infinity".to_string()),
+        0xF000 => return Some("-Infinity".to_string()),
+        _ => return None,
+    }
+    let digits: Vec<u16> = (0..ndigits).map(|i| word(4 + i)).collect::<Option<_>>()?;
+    if digits.iter().any(|&d| d > 9999) {
+        return None;
+    }
+    // Digits past either end of the stored ones are zero
+    let digit = |i: i32| {
+        usize::try_from(i)
+            .ok()
+            .and_then(|i| digits.get(i))
+            .copied()
+            .unwrap_or(0)
+    };
+
+    let mut text = String::new();
+    if sign == 0x4000 {
+        text.push('-');
+    }
+    if weight < 0 {
+        text.push('0');
+    } else {
+        text.push_str(&digit(0).to_string());
+        for i in 1..=weight {
+            text.push_str(&format!("{:04}", digit(i)));
+        }
+    }
+    if dscale > 0 {
+        let mut fraction = String::new();
+        let mut i = weight + 1;
+        while fraction.len() < dscale {
+            fraction.push_str(&format!("{:04}", digit(i)));
+            i += 1;
+        }
+        fraction.truncate(dscale);
+        text.push('.');
+        text.push_str(&fraction);
+    }
+    Some(text)
+}
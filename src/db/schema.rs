@@ -23,6 +23,162 @@ pub struct TableInfo {
     pub schema: String,
     pub table_type: TableType,
     pub row_estimate: i64,
+    /// `COMMENT ON TABLE` text.
+    pub comment: Option<String>,
+}
+
+/// PostgreSQL keywords that cannot be used as a column or table name without
+/// quoting (the "reserved" and "reserved (can be function or type)" classes).
+const RESERVED_KEYWORDS: &[&str] = &[
+    "all",
+    "analyse",
+    "analyze",
+    "and",
+    "any",
+    "array",
+    "as",
+    "asc",
+    "asymmetric",
+    "authorization",
+    "binary",
+    "both",
+    "case",
+    "cast",
+    "check",
+    "collate",
+    "collation",
+    "column",
+    "concurrently",
+    "constraint",
+    "create",
+    "cross",
+    "current_catalog",
+    "current_date",
+    "current_role",
+    "current_schema",
+    "current_time",
+    "current_timestamp",
+    "current_user",
+    "default",
+    "deferrable",
+    "desc",
+    "distinct",
+    "do",
+    "else",
+    "end",
+    "except",
+    "false",
+    "fetch",
+    "for",
+    "foreign",
+    "freeze",
+    "from",
+    "full",
+    "grant",
+    "group",
+    "having",
+    "ilike",
+    "in",
+    "initially",
+    "inner",
+    "intersect",
+    "into",
+    "is",
+    "isnull",
+    "join",
+    "lateral",
+    "leading",
+    "left",
+    "like",
+    "limit",
+    "localtime",
+    "localtimestamp",
+    "natural",
+    "not",
+    "notnull",
+    "null",
+    "offset",
+    "on",
+    "only",
+    "or",
+    "order",
+    "outer",
+    "overlaps",
+    "placing",
+    "primary",
+    "references",
+    "returning",
+    "right",
+    "select",
+    "session_user",
+    "similar",
+    "some",
+    "symmetric",
+    "system_user",
+    "table",
+    "tablesample",
+    "then",
+    "to",
+    "trailing",
+    "true",
+    "union",
+    "unique",
+    "user",
+    "using",
+    "variadic",
+    "verbose",
+    "when",
+    "where",
+    "window",
+    "with",
+];
+
+/// Quote `name` as a SQL identifier unless it is already a plain lowercase
+/// identifier that needs no quoting and is not a reserved keyword.
+pub fn quote_ident(name: &str) -> String {
+    let plain = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_lowercase() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        && !RESERVED_KEYWORDS.contains(&name);
+    if plain {
+        name.to_string()
+    } else {
+        format!("\"{}\"", name.replace('"', "\"\""))
+    }
+}
+
+impl TableInfo {
+    pub fn qualified_name(&self) -> String {
+        format!("{}.{}", self.schema, self.name)
+    }
+
+    /// Position of this table's schema on the session `search_path`.
+    pub fn search_path_rank(&self, search_path: &[String]) -> Option<usize> {
+        search_path.iter().position(|s| *s == self.schema)
+    }
+
+    /// Name to insert when completing this table: the bare name when an
+    /// unqualified reference resolves to it under `search_path`, otherwise
+    /// `schema.table`.
+    pub fn completion_name(&self, tables: &[TableInfo], search_path: &[String]) -> String {
+        let Some(rank) = self.search_path_rank(search_path) else {
+            return self.qualified_name();
+        };
+        let shadowed = tables.iter().any(|t| {
+            t.name == self.name
+                && t.search_path_rank(search_path)
+                    .is_some_and(|other| other < rank)
+        });
+        if shadowed {
+            self.qualified_name()
+        } else {
+            self.name.clone()
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -63,6 +219,8 @@ pub struct ColumnDetails {
     pub is_primary_key: bool,
     pub default_value: Option<String>,
     pub ordinal_position: i32,
+    /// `COMMENT ON COLUMN` text.
+    pub comment: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -75,6 +233,7 @@ pub struct IndexInfo {
 }
 
 #[cfg(test)]
+#[allow(clippy::items_after_test_module)]
 mod tests {
     use super::*;
 
@@ -100,6 +259,54 @@ mod tests {
         assert_ne!(TableType::Table, TableType::View);
     }
 
+    fn table(schema: &str, name: &str) -> TableInfo {
+        TableInfo {
+            name: name.into(),
+            schema: schema.into(),
+            table_type: TableType::Table,
+            row_estimate: 0,
+            comment: None,
+        }
+    }
+
+    #[test]
+    fn test_completion_name_uses_search_path() {
+        let tables = vec![
+            table("public", "users"),
+            table("audit", "users"),
+            table("audit", "events"),
+            table("app", "users"),
+        ];
+        let path = vec!["app".to_string(), "public".to_string()];
+
+        assert_eq!(tables[3].completion_name(&tables, &path), "users");
+        // Shadowed by app.users, which comes first on the path
+        assert_eq!(tables[0].completion_name(&tables, &path), "public.users");
+        // Not on the path at all
+        assert_eq!(tables[2].completion_name(&tables, &path), "audit.events");
+        assert_eq!(tables[1].search_path_rank(&path), None);
+        assert_eq!(tables[0].search_path_rank(&path), Some(1));
+    }
+
+    #[test]
+    fn test_quote_ident() {
+        assert_eq!(quote_ident("user_id"), "user_id");
+        assert_eq!(quote_ident("_tmp2"), "_tmp2");
+        assert_eq!(quote_ident("CreatedAt"), "\"CreatedAt\"");
+        assert_eq!(quote_ident("2nd"), "\"2nd\"");
+        assert_eq!(quote_ident("has space"), "\"has space\"");
+        assert_eq!(quote_ident("a\"b"), "\"a\"\"b\"");
+        assert_eq!(quote_ident("order"), "\"order\"");
+        assert_eq!(quote_ident("user"), "\"user\"");
+        assert_eq!(quote_ident("orders"), "orders");
+    }
+
+    #[test]
+    fn test_completion_name_empty_path() {
+        let tables = vec![table("public", "users")];
+        assert_eq!(tables[0].completion_name(&tables, &[]), "public.users");
+    }
+
     #[test]
     fn test_database_info_clone() {
         let db = DatabaseInfo {
@@ -128,6 +335,7 @@ mod tests {
             schema: "public".into(),
             table_type: TableType::Table,
             row_estimate: 1000,
+            comment: None,
         };
         let cloned = table.clone();
         assert_eq!(cloned.name, "users");
@@ -143,6 +351,7 @@ mod tests {
             is_primary_key: true,
             default_value: Some("nextval('users_id_seq')".into()),
             ordinal_position: 1,
+            comment: None,
         };
         assert_eq!(col.name, "id");
         assert!(col.is_primary_key);
@@ -160,6 +369,33 @@ mod tests {
         assert_eq!(idx.name, "users_pkey");
         assert!(idx.is_primary);
     }
+
+    #[test]
+    fn test_comment_statements() {
+        let column = |name: &str, comment: Option<&str>| ColumnDetails {
+            name: name.into(),
+            data_type: "text".into(),
+            is_nullable: true,
+            is_primary_key: false,
+            default_value: None,
+            ordinal_position: 1,
+            comment: comment.map(String::from),
+        };
+        let columns = vec![
+            column("id", None),
+            column("CreatedAt", Some("When the row's data arrived")),
+        ];
+
+        assert_eq!(
+            comment_statements("public", "users", Some("App users"), &columns),
+            "\nCOMMENT ON TABLE public.users IS 'App users';\n\
+             COMMENT ON COLUMN public.users.\"CreatedAt\" IS 'When the row''s data arrived';\n"
+        );
+        assert_eq!(
+            comment_statements("public", "users", None, &columns[..1]),
+            ""
+        );
+    }
 }
 
 pub async fn get_databases(client: &Client) -> Result<Vec<DatabaseInfo>> {
@@ -230,7 +466,8 @@ pub async fn get_tables(client: &Client, schema: &str) -> Result<Vec<TableInfo>>
                     WHEN 'f' THEN 'foreign_table'
                     ELSE 'other'
                 END as table_type,
-                COALESCE(c.reltuples::bigint, 0) as row_estimate
+                COALESCE(c.reltuples::bigint, 0) as row_estimate,
+                pg_catalog.obj_description(c.oid, 'pg_class') as comment
             FROM pg_catalog.pg_class c
             JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace
             WHERE n.nspname = $1
@@ -258,6 +495,7 @@ pub async fn get_tables(client: &Client, schema: &str) -> Result<Vec<TableInfo>>
                 schema: row.get("schema"),
                 table_type,
                 row_estimate: row.get("row_estimate"),
+                comment: row.get("comment"),
             }
         })
         .collect();
@@ -276,7 +514,11 @@ pub async fn get_columns(client: &Client, schema: &str, table: &str) -> Result<V
                 c.is_nullable = 'YES' as is_nullable,
                 COALESCE(tc.constraint_type = 'PRIMARY KEY', false) as is_primary_key,
                 c.column_default as default_value,
-                c.ordinal_position
+                c.ordinal_position,
+                pg_catalog.col_description(
+                    format('%I.%I', c.table_schema, c.table_name)::regclass,
+                    c.ordinal_position::int
+                ) as comment
             FROM information_schema.columns c
             LEFT JOIN information_schema.key_column_usage kcu
                 ON c.table_schema = kcu.table_schema
@@ -302,6 +544,7 @@ pub async fn get_columns(client: &Client, schema: &str, table: &str) -> Result<V
             is_primary_key: row.get("is_primary_key"),
             default_value: row.get("default_value"),
             ordinal_position: row.get("ordinal_position"),
+            comment: row.get("comment"),
         })
         .collect();
 
@@ -377,5 +620,75 @@ pub async fn get_table_ddl(client: &Client, schema: &str, table: &str) -> Result
 
     ddl.push_str(");\n");
 
+    let table_comment = get_table_comment(client, schema, table).await?;
+    ddl.push_str(&comment_statements(
+        schema,
+        table,
+        table_comment.as_deref(),
+        &columns,
+    ));
+
     Ok(ddl)
 }
+
+/// `COMMENT ON TABLE` text of `schema.table`.
+pub async fn get_table_comment(
+    client: &Client,
+    schema: &str,
+    table: &str,
+) -> Result<Option<String>> {
+    let row = client
+        .query_one(
+            "SELECT pg_catalog.obj_description(format('%I.%I', $1::text, $2::text)::regclass, 'pg_class')",
+            &[&schema, &table],
+        )
+        .await?;
+    Ok(row.get(0))
+}
+
+/// `COMMENT ON` statements recreating the comments of a table and its
+/// columns, preceded by a blank line; empty when there are none.
+fn comment_statements(
+    schema: &str,
+    table: &str,
+    table_comment: Option<&str>,
+    columns: &[ColumnDetails],
+) -> String {
+    let target = format!("{}.{}", quote_ident(schema), quote_ident(table));
+    let literal = |text: &str| format!("'{}'", text.replace('\'', "''"));
+
+    let mut statements = String::new();
+    if let Some(comment) = table_comment {
+        statements.push_str(&format!(
+            "COMMENT ON TABLE {} IS {};\n",
+            target,
+            literal(comment)
+        ));
+    }
+    for col in columns {
+        if let Some(comment) = &col.comment {
+            statements.push_str(&format!(
+                "COMMENT ON COLUMN {}.{} IS {};\n",
+                target,
+                quote_ident(&col.name),
+                literal(comment)
+            ));
+        }
+    }
+    if statements.is_empty() {
+        statements
+    } else {
+        format!("\n{}", statements)
+    }
+}
+
+/// `CREATE TABLE` statements for every base table in `schema`, in name order.
+pub async fn get_schema_ddl(client: &Client, schema: &str) -> Result<Vec<String>> {
+    let mut ddls = Vec::new();
+    for table in get_tables(client, schema).await? {
+        if table.table_type == TableType::Table {
+            ddls.push(get_table_ddl(client, schema, &table.name).await?);
+        }
+    }
+    Ok(ddls)
+}
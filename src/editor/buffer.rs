@@ -43,6 +43,16 @@ pub struct TextBuffer {
     pub selection_start: Option<(usize, usize)>,
     pub scroll_offset: usize,
     pub modified: bool,
+    /// Bumped on every change to the text, so callers can cache what they
+    /// derive from it.
+    revision: u64,
+    /// Columns per indentation level (and tab stop spacing for hard tabs).
+    pub tab_width: usize,
+    /// Insert `\t` instead of `tab_width` spaces.
+    pub use_hard_tabs: bool,
+    /// Revision and cursor position right after the editor put the cursor
+    /// inside a pair it inserted itself, which backspace removes together.
+    inserted_pair: Option<(u64, usize, usize)>,
     undo_history: UndoHistory,
 }
 
@@ -62,6 +72,10 @@ impl TextBuffer {
             selection_start: None,
             scroll_offset: 0,
             modified: false,
+            revision: 0,
+            tab_width: 4,
+            use_hard_tabs: false,
+            inserted_pair: None,
             undo_history: UndoHistory::new(),
         }
     }
@@ -79,10 +93,24 @@ impl TextBuffer {
             selection_start: None,
             scroll_offset: 0,
             modified: false,
+            revision: 0,
+            tab_width: 4,
+            use_hard_tabs: false,
+            inserted_pair: None,
             undo_history: UndoHistory::new(),
         }
     }
 
+    /// Counter that changes whenever the text does.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    fn set_modified(&mut self, modified: bool) {
+        self.modified = modified;
+        self.revision += 1;
+    }
+
     fn snapshot(&self) -> BufferSnapshot {
         BufferSnapshot {
             lines: self.lines.clone(),
@@ -129,7 +157,7 @@ impl TextBuffer {
             self.cursor_y = snap.cursor_y;
             self.selection_start = None;
             self.undo_history.last_action = None;
-            self.modified = !self.undo_history.undo_stack.is_empty();
+            self.set_modified(!self.undo_history.undo_stack.is_empty());
             true
         } else {
             false
@@ -145,7 +173,7 @@ impl TextBuffer {
             self.cursor_y = snap.cursor_y;
             self.selection_start = None;
             self.undo_history.last_action = None;
-            self.modified = true;
+            self.set_modified(true);
             true
         } else {
             false
@@ -184,7 +212,7 @@ impl TextBuffer {
             }
             self.cursor_x += 1;
         }
-        self.modified = true;
+        self.set_modified(true);
     }
 
     pub fn insert_newline(&mut self) {
@@ -199,7 +227,7 @@ impl TextBuffer {
         self.cursor_y += 1;
         self.cursor_x = 0;
         self.lines.insert(self.cursor_y, remainder);
-        self.modified = true;
+        self.set_modified(true);
     }
 
     pub fn insert_text(&mut self, text: &str) {
@@ -218,7 +246,23 @@ impl TextBuffer {
                 self.cursor_x += 1;
             }
         }
-        self.modified = true;
+        self.set_modified(true);
+    }
+
+    /// Step the cursor back between the empty pair just inserted before it,
+    /// e.g. the `()` of a completed function name, so that a backspace right
+    /// away deletes both halves.
+    pub fn enter_inserted_pair(&mut self) {
+        let cx = self.cursor_x;
+        let is_pair = cx >= 2
+            && self
+                .current_line()
+                .get(cx - 2..cx)
+                .is_some_and(|pair| matches!(pair, "()" | "[]" | "{}" | "''" | "\"\""));
+        if is_pair {
+            self.cursor_x -= 1;
+            self.inserted_pair = Some((self.revision, self.cursor_y, self.cursor_x));
+        }
     }
 
     pub fn backspace(&mut self) {
@@ -228,19 +272,25 @@ impl TextBuffer {
             return;
         }
 
+        // Only a pair the editor inserted, with nothing edited since
+        let in_pair =
+            self.inserted_pair.take() == Some((self.revision, self.cursor_y, self.cursor_x));
         self.save_undo(UndoActionType::Delete);
         if self.cursor_x > 0 {
             let cx = self.cursor_x;
             let line = self.current_line_mut();
+            if in_pair {
+                line.remove(cx);
+            }
             line.remove(cx - 1);
             self.cursor_x -= 1;
-            self.modified = true;
+            self.set_modified(true);
         } else if self.cursor_y > 0 {
             let current_line = self.lines.remove(self.cursor_y);
             self.cursor_y -= 1;
             self.cursor_x = self.lines[self.cursor_y].len();
             self.lines[self.cursor_y].push_str(&current_line);
-            self.modified = true;
+            self.set_modified(true);
         }
     }
 
@@ -256,11 +306,11 @@ impl TextBuffer {
         if self.cursor_x < line_len {
             let cx = self.cursor_x;
             self.current_line_mut().remove(cx);
-            self.modified = true;
+            self.set_modified(true);
         } else if self.cursor_y < self.lines.len() - 1 {
             let next_line = self.lines.remove(self.cursor_y + 1);
             self.current_line_mut().push_str(&next_line);
-            self.modified = true;
+            self.set_modified(true);
         }
     }
 
@@ -447,19 +497,84 @@ impl TextBuffer {
             self.cursor_x = start.0;
             self.cursor_y = start.1;
             self.clear_selection();
-            self.modified = true;
+            self.set_modified(true);
             true
         } else {
             false
         }
     }
 
+    // Case conversion
+    pub fn uppercase_selection(&mut self) -> bool {
+        self.convert_case(str::to_ascii_uppercase)
+    }
+
+    pub fn lowercase_selection(&mut self) -> bool {
+        self.convert_case(str::to_ascii_lowercase)
+    }
+
+    /// Apply `convert` to the selection, or to the word under the cursor when
+    /// nothing is selected, as a single undo step. Only ASCII letters change so
+    /// byte offsets (cursor and selection) stay valid.
+    fn convert_case(&mut self, convert: fn(&str) -> String) -> bool {
+        let (start, end) = match self.get_selection() {
+            Some(range) => range,
+            None => match self.word_range_at_cursor() {
+                Some((from, to)) => ((from, self.cursor_y), (to, self.cursor_y)),
+                None => return false,
+            },
+        };
+        if start == end {
+            return false;
+        }
+
+        self.save_undo_forced();
+        for y in start.1..=end.1 {
+            let line = &mut self.lines[y];
+            let from = if y == start.1 { start.0 } else { 0 };
+            let to = if y == end.1 { end.0 } else { line.len() };
+            let converted = convert(&line[from..to]);
+            line.replace_range(from..to, &converted);
+        }
+        self.set_modified(true);
+        true
+    }
+
+    /// Byte range of the identifier touching the cursor, if any.
+    fn word_range_at_cursor(&self) -> Option<(usize, usize)> {
+        let line = self.current_line().as_bytes();
+        let is_word = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+        let cx = min(self.cursor_x, line.len());
+        let start = cx - line[..cx].iter().rev().take_while(|b| is_word(**b)).count();
+        let end = cx + line[cx..].iter().take_while(|b| is_word(**b)).count();
+        (start < end).then_some((start, end))
+    }
+
     pub fn select_all(&mut self) {
         self.selection_start = Some((0, 0));
         self.cursor_y = self.lines.len() - 1;
         self.cursor_x = self.lines[self.cursor_y].len();
     }
 
+    /// Select the text between two byte offsets into `text()`, leaving the
+    /// cursor at `end`.
+    pub fn select_offsets(&mut self, start: usize, end: usize) {
+        self.selection_start = Some(self.position_of_offset(start));
+        (self.cursor_x, self.cursor_y) = self.position_of_offset(end);
+    }
+
+    /// Convert a byte offset into `text()` to a `(x, y)` cursor position.
+    fn position_of_offset(&self, mut offset: usize) -> (usize, usize) {
+        for (y, line) in self.lines.iter().enumerate() {
+            if offset <= line.len() {
+                return (offset, y);
+            }
+            offset -= line.len() + 1;
+        }
+        let last = self.lines.len() - 1;
+        (self.lines[last].len(), last)
+    }
+
     pub fn select_line(&mut self) {
         self.cursor_x = 0;
         self.selection_start = Some((0, self.cursor_y));
@@ -501,7 +616,7 @@ impl TextBuffer {
                         self.cursor_x += 1;
                     }
                 }
-                self.modified = true;
+                self.set_modified(true);
             }
         }
     }
@@ -513,7 +628,7 @@ impl TextBuffer {
         self.cursor_y = 0;
         self.selection_start = None;
         self.scroll_offset = 0;
-        self.modified = false;
+        self.set_modified(false);
         self.undo_history = UndoHistory::new();
     }
 
@@ -525,16 +640,191 @@ impl TextBuffer {
         self.cursor_x = 0;
         self.cursor_y = 0;
         self.selection_start = None;
-        self.modified = false;
+        self.set_modified(false);
         self.undo_history = UndoHistory::new();
     }
 
     // Tab handling
     pub fn insert_tab(&mut self) {
-        // Insert 4 spaces
-        for _ in 0..4 {
-            self.insert_char(' ');
+        if self.use_hard_tabs {
+            self.insert_char('\t');
+        } else {
+            for _ in 0..self.tab_width {
+                self.insert_char(' ');
+            }
+        }
+    }
+
+    /// Screen column of the cursor on its line, expanding hard tabs to the
+    /// next multiple of `tab_width`.
+    pub fn display_column(&self) -> usize {
+        let tab_width = self.tab_width.max(1);
+        self.current_line()
+            .chars()
+            .take(self.cursor_x)
+            .fold(0, |col, c| {
+                if c == '\t' {
+                    col + tab_width - col % tab_width
+                } else {
+                    col + 1
+                }
+            })
+    }
+
+    /// Strip trailing spaces and tabs from every line as a single undo step.
+    /// The cursor and selection anchor are clamped to the trimmed line ends.
+    /// Returns false (and records no undo step) when nothing needed trimming.
+    pub fn trim_trailing_whitespace(&mut self) -> bool {
+        let is_blank = |c: char| c == ' ' || c == '\t';
+        if !self.lines.iter().any(|l| l.ends_with(is_blank)) {
+            return false;
+        }
+
+        self.save_undo_forced();
+        for line in &mut self.lines {
+            let len = line.trim_end_matches(is_blank).len();
+            line.truncate(len);
+        }
+        self.cursor_x = min(self.cursor_x, self.lines[self.cursor_y].len());
+        if let Some((x, y)) = self.selection_start {
+            self.selection_start = Some((min(x, self.lines[y].len()), y));
+        }
+        self.set_modified(true);
+        true
+    }
+
+    /// Uppercase the word ending at byte `end` of the cursor line if
+    /// `is_keyword` accepts it, as its own undo step. Words inside string
+    /// literals, quoted identifiers and `--` comments, and parts of qualified
+    /// names like `t.name`, are left alone. Returns whether the line changed.
+    pub fn uppercase_keyword_before(&mut self, end: usize, is_keyword: fn(&str) -> bool) -> bool {
+        let line = self.current_line();
+        let Some(prefix) = line.get(..end) else {
+            return false;
+        };
+        let bytes = line.as_bytes();
+        let is_word = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+        let start = end - prefix.bytes().rev().take_while(|b| is_word(*b)).count();
+        let word = &line[start..end];
+        let qualified = (start > 0 && matches!(bytes[start - 1], b'.' | b'"' | b'$'))
+            || bytes.get(end) == Some(&b'.');
+        if word.is_empty()
+            || qualified
+            || word.bytes().all(|b| !b.is_ascii_lowercase())
+            || !is_keyword(word)
+            || inside_literal_or_comment(&line[..start])
+        {
+            return false;
+        }
+
+        let upper = word.to_ascii_uppercase();
+        self.save_undo_forced();
+        self.lines[self.cursor_y].replace_range(start..end, &upper);
+        self.set_modified(true);
+        true
+    }
+
+    // Line-wise editing (used by vim mode)
+
+    /// Remove the cursor's line and return its text as a single undo step.
+    /// The last remaining line is emptied rather than removed.
+    pub fn delete_line(&mut self) -> String {
+        self.save_undo_forced();
+        self.clear_selection();
+        let removed = if self.lines.len() > 1 {
+            let line = self.lines.remove(self.cursor_y);
+            self.cursor_y = min(self.cursor_y, self.lines.len() - 1);
+            line
+        } else {
+            std::mem::take(&mut self.lines[0])
+        };
+        self.cursor_x = 0;
+        self.set_modified(true);
+        removed
+    }
+
+    /// Insert `text` as whole lines below (or above) the cursor's line and
+    /// move the cursor to the first inserted line.
+    pub fn put_lines(&mut self, text: &str, below: bool) {
+        self.save_undo_forced();
+        self.clear_selection();
+        let at = if below {
+            self.cursor_y + 1
+        } else {
+            self.cursor_y
+        };
+        let new_lines: Vec<String> = text.split('\n').map(String::from).collect();
+        self.lines.splice(at..at, new_lines);
+        self.cursor_y = at;
+        self.cursor_x = 0;
+        self.set_modified(true);
+    }
+
+    /// Open an empty line below (or above) the cursor and move onto it.
+    pub fn open_line(&mut self, below: bool) {
+        self.put_lines("", below);
+    }
+
+    /// Position of the next occurrence of `pattern` after the cursor (or the
+    /// previous one before it), wrapping around the buffer.
+    pub fn find(&self, pattern: &str, forward: bool) -> Option<(usize, usize)> {
+        if pattern.is_empty() {
+            return None;
+        }
+        let (cx, cy) = (self.cursor_x, self.cursor_y);
+        let count = self.lines.len();
+        // Visit the cursor line twice: once for the part after (or before)
+        // the cursor and once more at the end for the wrapped-around part.
+        for step in 0..=count {
+            let y = if forward {
+                (cy + step) % count
+            } else {
+                (cy + count - step % count) % count
+            };
+            let line = &self.lines[y];
+            let found = if forward {
+                let from = if step == 0 { cx + 1 } else { 0 };
+                line.get(from..)
+                    .and_then(|rest| rest.find(pattern))
+                    .map(|i| i + from)
+                    .filter(|&x| step < count || x <= cx)
+            } else {
+                let to = if step == 0 { cx } else { line.len() };
+                line.get(..to)
+                    .and_then(|head| head.rfind(pattern))
+                    .filter(|&x| step < count || x >= cx)
+            };
+            if let Some(x) = found {
+                return Some((x, y));
+            }
         }
+        None
+    }
+
+    /// Positions `(x, y)` of every non-overlapping occurrence of `pattern`,
+    /// ignoring ASCII case, in buffer order.
+    pub fn find_all(&self, pattern: &str) -> Vec<(usize, usize)> {
+        if pattern.is_empty() {
+            return Vec::new();
+        }
+        let pattern = pattern.to_ascii_lowercase();
+        let mut matches = Vec::new();
+        for (y, line) in self.lines.iter().enumerate() {
+            let line = line.to_ascii_lowercase();
+            let mut from = 0;
+            while let Some(i) = line[from..].find(&pattern) {
+                matches.push((from + i, y));
+                from += i + pattern.len();
+            }
+        }
+        matches
+    }
+
+    /// Move the cursor to the start of 1-based `line`, clamped to the buffer.
+    pub fn go_to_line(&mut self, line: usize) {
+        self.clear_selection();
+        self.cursor_y = line.clamp(1, self.lines.len()) - 1;
+        self.cursor_x = 0;
     }
 
     // Scroll handling
@@ -551,6 +841,34 @@ impl TextBuffer {
     }
 }
 
+/// Whether the end of `prefix` lies inside a string literal, quoted
+/// identifier or comment that starts on the same line.
+fn inside_literal_or_comment(prefix: &str) -> bool {
+    let mut quote: Option<char> = None;
+    let mut in_block_comment = false;
+    let mut chars = prefix.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_block_comment {
+            if c == '*' && chars.peek() == Some(&'/') {
+                chars.next();
+                in_block_comment = false;
+            }
+        } else if let Some(q) = quote {
+            if c == q {
+                quote = None;
+            }
+        } else if c == '\'' || c == '"' {
+            quote = Some(c);
+        } else if c == '-' && chars.peek() == Some(&'-') {
+            return true;
+        } else if c == '/' && chars.peek() == Some(&'*') {
+            chars.next();
+            in_block_comment = true;
+        }
+    }
+    quote.is_some() || in_block_comment
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -661,6 +979,80 @@ mod tests {
         assert_eq!(buf.cursor_x, 4);
     }
 
+    #[test]
+    fn test_insert_tab_custom_width() {
+        let mut buf = TextBuffer::new();
+        buf.tab_width = 2;
+        buf.insert_tab();
+        assert_eq!(buf.text(), "  ");
+        assert_eq!(buf.cursor_x, 2);
+    }
+
+    #[test]
+    fn test_insert_hard_tab() {
+        let mut buf = TextBuffer::new();
+        buf.use_hard_tabs = true;
+        buf.insert_char('a');
+        buf.insert_tab();
+        assert_eq!(buf.text(), "a\t");
+        assert_eq!(buf.cursor_x, 2);
+        assert_eq!(buf.display_column(), 4);
+    }
+
+    #[test]
+    fn test_trim_trailing_whitespace() {
+        let mut buf = TextBuffer::from_text("SELECT 1  \nFROM t\t\n  \nWHERE x");
+        buf.cursor_y = 0;
+        buf.cursor_x = 10;
+        assert!(buf.trim_trailing_whitespace());
+        assert_eq!(buf.text(), "SELECT 1\nFROM t\n\nWHERE x");
+        assert_eq!(buf.cursor_x, 8);
+
+        // The whole trim undoes in one step
+        buf.undo();
+        assert_eq!(buf.text(), "SELECT 1  \nFROM t\t\n  \nWHERE x");
+        assert_eq!(buf.cursor_x, 10);
+    }
+
+    #[test]
+    fn test_trim_trailing_whitespace_noop() {
+        let mut buf = TextBuffer::from_text("  SELECT 1\nFROM t");
+        assert!(!buf.trim_trailing_whitespace());
+        assert!(!buf.undo());
+        assert_eq!(buf.text(), "  SELECT 1\nFROM t");
+    }
+
+    #[test]
+    fn test_uppercase_selection() {
+        let mut buf = TextBuffer::from_text("select id\nfrom users");
+        buf.selection_start = Some((0, 0));
+        buf.cursor_y = 1;
+        buf.cursor_x = 4;
+        assert!(buf.uppercase_selection());
+        assert_eq!(buf.text(), "SELECT ID\nFROM users");
+        assert_eq!(buf.get_selection(), Some(((0, 0), (4, 1))));
+
+        buf.undo();
+        assert_eq!(buf.text(), "select id\nfrom users");
+    }
+
+    #[test]
+    fn test_lowercase_word_at_cursor() {
+        let mut buf = TextBuffer::from_text("SELECT user_id FROM t");
+        buf.cursor_x = 10;
+        assert!(buf.lowercase_selection());
+        assert_eq!(buf.text(), "SELECT user_id FROM t");
+        buf.cursor_x = 3;
+        assert!(buf.lowercase_selection());
+        assert_eq!(buf.text(), "select user_id FROM t");
+        assert_eq!(buf.cursor_x, 3);
+
+        // Cursor on whitespace between words touches no word
+        let mut buf = TextBuffer::from_text("a  b");
+        buf.cursor_x = 2;
+        assert!(!buf.uppercase_selection());
+    }
+
     // --- Deletion ---
 
     #[test]
@@ -957,6 +1349,22 @@ mod tests {
         assert!(!buf.modified);
     }
 
+    #[test]
+    fn test_revision_follows_text_changes() {
+        let mut buf = TextBuffer::from_text("select 1");
+        let start = buf.revision();
+        buf.move_right();
+        assert_eq!(buf.revision(), start);
+        buf.insert_char('x');
+        let edited = buf.revision();
+        assert_ne!(edited, start);
+        buf.undo();
+        assert_ne!(buf.revision(), edited);
+        let undone = buf.revision();
+        buf.set_text("select 2");
+        assert_ne!(buf.revision(), undone);
+    }
+
     // --- Scroll ---
 
     #[test]
@@ -1153,4 +1561,179 @@ mod tests {
         buf.redo();
         assert_eq!(buf.text(), "first\nsecond");
     }
+
+    // --- Line-wise editing ---
+
+    #[test]
+    fn test_delete_line() {
+        let mut buf = TextBuffer::from_text("a\nb\nc");
+        buf.cursor_y = 2;
+        buf.cursor_x = 1;
+        assert_eq!(buf.delete_line(), "c");
+        assert_eq!(buf.text(), "a\nb");
+        assert_eq!((buf.cursor_x, buf.cursor_y), (0, 1));
+        buf.undo();
+        assert_eq!(buf.text(), "a\nb\nc");
+    }
+
+    #[test]
+    fn test_delete_only_line_leaves_empty_buffer() {
+        let mut buf = TextBuffer::from_text("only");
+        assert_eq!(buf.delete_line(), "only");
+        assert_eq!(buf.lines, vec![""]);
+    }
+
+    #[test]
+    fn test_put_lines() {
+        let mut buf = TextBuffer::from_text("a\nd");
+        buf.put_lines("b\nc", true);
+        assert_eq!(buf.text(), "a\nb\nc\nd");
+        assert_eq!(buf.cursor_y, 1);
+        buf.put_lines("z", false);
+        assert_eq!(buf.text(), "a\nz\nb\nc\nd");
+        assert_eq!(buf.cursor_y, 1);
+    }
+
+    #[test]
+    fn test_open_line() {
+        let mut buf = TextBuffer::from_text("select 1");
+        buf.open_line(true);
+        assert_eq!(buf.lines, vec!["select 1", ""]);
+        assert_eq!(buf.cursor_y, 1);
+    }
+
+    #[test]
+    fn test_find_forward_wraps() {
+        let mut buf = TextBuffer::from_text("id = 1\nname = id");
+        assert_eq!(buf.find("id", true), Some((7, 1)));
+        buf.cursor_y = 1;
+        buf.cursor_x = 7;
+        assert_eq!(buf.find("id", true), Some((0, 0)));
+        assert_eq!(buf.find("missing", true), None);
+    }
+
+    #[test]
+    fn test_find_backward() {
+        let mut buf = TextBuffer::from_text("id = 1\nname = id");
+        assert_eq!(buf.find("id", false), Some((7, 1)));
+        buf.cursor_y = 1;
+        buf.cursor_x = 7;
+        assert_eq!(buf.find("id", false), Some((0, 0)));
+    }
+
+    #[test]
+    fn test_find_single_match_returns_itself() {
+        let buf = TextBuffer::from_text("select id");
+        assert_eq!(buf.find("select", true), Some((0, 0)));
+    }
+
+    // --- Keyword uppercasing ---
+
+    fn is_kw(word: &str) -> bool {
+        matches!(
+            word.to_ascii_uppercase().as_str(),
+            "SELECT" | "FROM" | "WHERE" | "NAME"
+        )
+    }
+
+    fn type_and_uppercase(text: &str) -> TextBuffer {
+        let mut buf = TextBuffer::new();
+        for c in text.chars() {
+            buf.insert_char(c);
+            if !(c.is_alphanumeric() || c == '_') {
+                let end = buf.cursor_x - 1;
+                buf.uppercase_keyword_before(end, is_kw);
+            }
+        }
+        buf
+    }
+
+    #[test]
+    fn test_uppercase_keyword_on_boundary() {
+        let buf = type_and_uppercase("select id from users where ");
+        assert_eq!(buf.text(), "SELECT id FROM users WHERE ");
+    }
+
+    #[test]
+    fn test_uppercase_keyword_skips_strings_comments_and_qualified() {
+        let buf = type_and_uppercase("select 'from here' ");
+        assert_eq!(buf.text(), "SELECT 'from here' ");
+        let buf = type_and_uppercase("-- select ");
+        assert_eq!(buf.text(), "-- select ");
+        let buf = type_and_uppercase("/* from */ from ");
+        assert_eq!(buf.text(), "/* from */ FROM ");
+        let buf = type_and_uppercase("select u.name, \"select\" ");
+        assert_eq!(buf.text(), "SELECT u.name, \"select\" ");
+    }
+
+    #[test]
+    fn test_uppercase_keyword_is_undoable() {
+        let mut buf = type_and_uppercase("select ");
+        assert_eq!(buf.text(), "SELECT ");
+        buf.undo();
+        assert_eq!(buf.text(), "select ");
+    }
+
+    #[test]
+    fn test_select_offsets() {
+        let mut buf = TextBuffer::from_text("SELECT 1;\nSELECT 2\nFROM t;");
+        buf.select_offsets(10, 26);
+        assert_eq!(buf.selection_start, Some((0, 1)));
+        assert_eq!((buf.cursor_x, buf.cursor_y), (7, 2));
+        assert_eq!(
+            buf.get_selected_text().as_deref(),
+            Some("SELECT 2\nFROM t;")
+        );
+    }
+
+    #[test]
+    fn test_go_to_line_clamps() {
+        let mut buf = TextBuffer::from_text("a\nbb\nccc");
+        buf.cursor_x = 1;
+        buf.go_to_line(2);
+        assert_eq!((buf.cursor_x, buf.cursor_y), (0, 1));
+        buf.go_to_line(99);
+        assert_eq!(buf.cursor_y, 2);
+        buf.go_to_line(0);
+        assert_eq!(buf.cursor_y, 0);
+    }
+
+    #[test]
+    fn test_find_all_ignores_case() {
+        let buf = TextBuffer::from_text("select id\nFROM t WHERE id = id");
+        assert_eq!(buf.find_all("ID"), vec![(7, 0), (13, 1), (18, 1)]);
+        assert_eq!(buf.find_all("aa"), vec![]);
+        assert!(buf.find_all("").is_empty());
+
+        let buf = TextBuffer::from_text("aaaa");
+        assert_eq!(buf.find_all("aa"), vec![(0, 0), (2, 0)]);
+    }
+
+    #[test]
+    fn test_backspace_deletes_inserted_pair() {
+        let mut buf = TextBuffer::new();
+        buf.insert_text("count()");
+        buf.enter_inserted_pair();
+        assert_eq!(buf.cursor_x, 6);
+        buf.backspace();
+        assert_eq!(buf.text(), "count");
+        assert_eq!(buf.cursor_x, 5);
+        buf.undo();
+        assert_eq!(buf.text(), "count()");
+
+        // Once something was typed, the pair is the user's text
+        let mut buf = TextBuffer::new();
+        buf.insert_text("count()");
+        buf.enter_inserted_pair();
+        buf.insert_char('x');
+        buf.backspace();
+        buf.backspace();
+        assert_eq!(buf.text(), "count)");
+
+        // A pair the user typed is left alone
+        let mut buf = TextBuffer::from_text("count()");
+        buf.cursor_x = 6;
+        buf.backspace();
+        assert_eq!(buf.text(), "count)");
+    }
 }
@@ -1,5 +1,6 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,12 +12,59 @@ pub struct HistoryEntry {
     pub success: bool,
 }
 
+/// Runtimes of the successful runs of one query recorded in the history.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RuntimeStats {
+    pub runs: u64,
+    pub total_ms: u64,
+    pub last_ms: u64,
+}
+
+impl RuntimeStats {
+    pub fn average_ms(&self) -> u64 {
+        self.total_ms / self.runs.max(1)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct QueryHistory {
     entries: Vec<HistoryEntry>,
     #[serde(skip)]
     current_index: Option<usize>,
     max_entries: usize,
+    /// Runtime stats by `normalize_query` text, rebuilt from `entries`.
+    #[serde(skip)]
+    runtimes: HashMap<String, RuntimeStats>,
+}
+
+/// Query text as used to recognize a re-run: lowercased, whitespace
+/// collapsed and without a trailing `;`. Quoted literals and identifiers
+/// are kept as written.
+pub fn normalize_query(query: &str) -> String {
+    let mut out = String::new();
+    let mut quote = None;
+    for c in query.trim().trim_end_matches(';').trim_end().chars() {
+        match quote {
+            Some(q) => {
+                out.push(c);
+                if c == q {
+                    quote = None;
+                }
+            }
+            None if c.is_whitespace() => {
+                if !out.ends_with(' ') {
+                    out.push(' ');
+                }
+            }
+            None => {
+                if c == '\'' || c == '"' {
+                    quote = Some(c);
+                }
+                out.extend(c.to_lowercase());
+            }
+        }
+    }
+    out
 }
 
 #[allow(dead_code)]
@@ -26,13 +74,19 @@ impl QueryHistory {
             entries: Vec::new(),
             current_index: None,
             max_entries: 1000,
+            runtimes: HashMap::new(),
         }
     }
 
     pub fn add(&mut self, entry: HistoryEntry) {
-        // Don't add duplicate consecutive entries
-        if let Some(last) = self.entries.last() {
+        // Don't add duplicate consecutive entries, but keep the latest run's
+        // outcome and timing
+        if let Some(last) = self.entries.last_mut() {
             if last.query.trim() == entry.query.trim() {
+                last.timestamp = entry.timestamp;
+                last.execution_time_ms = entry.execution_time_ms;
+                last.success = entry.success;
+                self.rebuild_runtimes();
                 return;
             }
         }
@@ -45,6 +99,26 @@ impl QueryHistory {
         }
 
         self.current_index = None;
+        self.rebuild_runtimes();
+    }
+
+    /// How long earlier successful runs of `query` took, matched on
+    /// `normalize_query` text.
+    pub fn runtime_stats(&self, query: &str) -> Option<RuntimeStats> {
+        self.runtimes.get(&normalize_query(query)).copied()
+    }
+
+    fn rebuild_runtimes(&mut self) {
+        self.runtimes.clear();
+        for entry in self.entries.iter().filter(|e| e.success) {
+            let stats = self
+                .runtimes
+                .entry(normalize_query(&entry.query))
+                .or_default();
+            stats.runs += 1;
+            stats.total_ms += entry.execution_time_ms;
+            stats.last_ms = entry.execution_time_ms;
+        }
     }
 
     pub fn previous(&mut self) -> Option<&HistoryEntry> {
@@ -106,7 +180,8 @@ impl QueryHistory {
             return Ok(Self::new());
         }
         let content = std::fs::read_to_string(&path)?;
-        let history: QueryHistory = serde_json::from_str(&content)?;
+        let mut history: QueryHistory = serde_json::from_str(&content)?;
+        history.rebuild_runtimes();
         Ok(history)
     }
 
@@ -188,6 +263,44 @@ mod tests {
         assert_eq!(h.entries()[0].query, "q2");
     }
 
+    #[test]
+    fn test_runtime_stats() {
+        let mut h = QueryHistory::new();
+        let run = |query: &str, ms: u64, success: bool| HistoryEntry {
+            execution_time_ms: ms,
+            success,
+            ..make_entry(query)
+        };
+        h.add(run("SELECT * FROM t;", 100, true));
+        h.add(run("SELECT 2", 5, true));
+        h.add(run("select *\n  from t", 300, true));
+        h.add(run("SELECT * FROM t", 9000, false));
+        let stats = h.runtime_stats("SELECT  *  FROM t").unwrap();
+        assert_eq!(stats.runs, 2);
+        assert_eq!(stats.last_ms, 300);
+        assert_eq!(stats.average_ms(), 200);
+        assert!(h.runtime_stats("SELECT 3").is_none());
+
+        // A consecutive re-run replaces the timing of the previous entry
+        h.add(run("SELECT * FROM t", 400, true));
+        assert_eq!(h.entries().len(), 4);
+        let stats = h.runtime_stats("SELECT * FROM t").unwrap();
+        assert_eq!((stats.runs, stats.last_ms), (3, 400));
+    }
+
+    #[test]
+    fn test_normalize_query_keeps_quoted_text() {
+        assert_eq!(
+            normalize_query("SELECT  *\nFROM \"Users\" WHERE name = 'Bob  Smith';"),
+            "select * from \"Users\" where name = 'Bob  Smith'"
+        );
+        assert_eq!(normalize_query("SELECT 'it''s A'"), "select 'it''s A'");
+        assert_ne!(
+            normalize_query("SELECT * FROM t WHERE s = 'A'"),
+            normalize_query("SELECT * FROM t WHERE s = 'a'")
+        );
+    }
+
     // --- Navigation ---
 
     #[test]
@@ -1,5 +1,11 @@
 mod buffer;
 mod history;
+mod signature;
+mod statements;
+mod vim;
 
 pub use buffer::*;
 pub use history::*;
+pub use signature::*;
+pub use statements::*;
+pub use vim::*;
@@ -0,0 +1,408 @@
+/// Argument names of a SQL function, shown as a hint while typing a call
+/// and inserted as placeholders when a function completion is accepted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FunctionSignature {
+    pub name: &'static str,
+    /// `...` marks a variadic tail; keyword syntax such as `value AS type`
+    /// is kept as a single argument.
+    pub args: &'static [&'static str],
+}
+
+impl FunctionSignature {
+    /// Whether the arguments can be inserted as placeholders: at least one,
+    /// and all plain names rather than keyword syntax or `...`.
+    pub fn has_placeholders(&self) -> bool {
+        !self.args.is_empty()
+            && self
+                .args
+                .iter()
+                .all(|arg| arg.chars().all(|c| c.is_alphanumeric() || c == '_'))
+    }
+}
+
+/// Signatures of the functions offered by autocomplete.
+pub const FUNCTION_SIGNATURES: &[FunctionSignature] = &[
+    FunctionSignature {
+        name: "COUNT",
+        args: &["value"],
+    },
+    FunctionSignature {
+        name: "SUM",
+        args: &["value"],
+    },
+    FunctionSignature {
+        name: "AVG",
+        args: &["value"],
+    },
+    FunctionSignature {
+        name: "MIN",
+        args: &["value"],
+    },
+    FunctionSignature {
+        name: "MAX",
+        args: &["value"],
+    },
+    FunctionSignature {
+        name: "COALESCE",
+        args: &["value", "fallback"],
+    },
+    FunctionSignature {
+        name: "NULLIF",
+        args: &["value", "null_if"],
+    },
+    FunctionSignature {
+        name: "CAST",
+        args: &["value AS type"],
+    },
+    FunctionSignature {
+        name: "NOW",
+        args: &[],
+    },
+    FunctionSignature {
+        name: "CURRENT_DATE",
+        args: &[],
+    },
+    FunctionSignature {
+        name: "CURRENT_TIMESTAMP",
+        args: &[],
+    },
+    FunctionSignature {
+        name: "EXTRACT",
+        args: &["field FROM source"],
+    },
+    FunctionSignature {
+        name: "DATE_TRUNC",
+        args: &["field", "source"],
+    },
+    FunctionSignature {
+        name: "TO_CHAR",
+        args: &["value", "format"],
+    },
+    FunctionSignature {
+        name: "TO_DATE",
+        args: &["text", "format"],
+    },
+    FunctionSignature {
+        name: "TO_NUMBER",
+        args: &["text", "format"],
+    },
+    FunctionSignature {
+        name: "TO_TIMESTAMP",
+        args: &["text", "format"],
+    },
+    FunctionSignature {
+        name: "CONCAT",
+        args: &["value", "..."],
+    },
+    FunctionSignature {
+        name: "LENGTH",
+        args: &["string"],
+    },
+    FunctionSignature {
+        name: "LOWER",
+        args: &["string"],
+    },
+    FunctionSignature {
+        name: "UPPER",
+        args: &["string"],
+    },
+    FunctionSignature {
+        name: "TRIM",
+        args: &["string"],
+    },
+    FunctionSignature {
+        name: "SUBSTRING",
+        args: &["string", "start", "count"],
+    },
+    FunctionSignature {
+        name: "REPLACE",
+        args: &["string", "from", "to"],
+    },
+    FunctionSignature {
+        name: "POSITION",
+        args: &["substring IN string"],
+    },
+    FunctionSignature {
+        name: "LEFT",
+        args: &["string", "n"],
+    },
+    FunctionSignature {
+        name: "RIGHT",
+        args: &["string", "n"],
+    },
+    FunctionSignature {
+        name: "LPAD",
+        args: &["string", "length", "fill"],
+    },
+    FunctionSignature {
+        name: "RPAD",
+        args: &["string", "length", "fill"],
+    },
+    FunctionSignature {
+        name: "SPLIT_PART",
+        args: &["string", "delimiter", "n"],
+    },
+    FunctionSignature {
+        name: "STRING_AGG",
+        args: &["value", "delimiter"],
+    },
+    FunctionSignature {
+        name: "ARRAY_AGG",
+        args: &["value"],
+    },
+    FunctionSignature {
+        name: "JSON_AGG",
+        args: &["value"],
+    },
+    FunctionSignature {
+        name: "JSONB_AGG",
+        args: &["value"],
+    },
+    FunctionSignature {
+        name: "JSON_BUILD_OBJECT",
+        args: &["key", "value", "..."],
+    },
+    FunctionSignature {
+        name: "JSONB_BUILD_OBJECT",
+        args: &["key", "value", "..."],
+    },
+    FunctionSignature {
+        name: "ROW_NUMBER",
+        args: &[],
+    },
+    FunctionSignature {
+        name: "RANK",
+        args: &[],
+    },
+    FunctionSignature {
+        name: "DENSE_RANK",
+        args: &[],
+    },
+    FunctionSignature {
+        name: "LAG",
+        args: &["value", "offset", "default"],
+    },
+    FunctionSignature {
+        name: "LEAD",
+        args: &["value", "offset", "default"],
+    },
+    FunctionSignature {
+        name: "FIRST_VALUE",
+        args: &["value"],
+    },
+    FunctionSignature {
+        name: "LAST_VALUE",
+        args: &["value"],
+    },
+    FunctionSignature {
+        name: "NTILE",
+        args: &["buckets"],
+    },
+    FunctionSignature {
+        name: "GREATEST",
+        args: &["value", "..."],
+    },
+    FunctionSignature {
+        name: "LEAST",
+        args: &["value", "..."],
+    },
+    FunctionSignature {
+        name: "ABS",
+        args: &["value"],
+    },
+    FunctionSignature {
+        name: "CEIL",
+        args: &["value"],
+    },
+    FunctionSignature {
+        name: "FLOOR",
+        args: &["value"],
+    },
+    FunctionSignature {
+        name: "ROUND",
+        args: &["value", "digits"],
+    },
+    FunctionSignature {
+        name: "MOD",
+        args: &["dividend", "divisor"],
+    },
+    FunctionSignature {
+        name: "POWER",
+        args: &["base", "exponent"],
+    },
+    FunctionSignature {
+        name: "SQRT",
+        args: &["value"],
+    },
+    FunctionSignature {
+        name: "RANDOM",
+        args: &[],
+    },
+    FunctionSignature {
+        name: "GEN_RANDOM_UUID",
+        args: &[],
+    },
+    FunctionSignature {
+        name: "PG_SIZE_PRETTY",
+        args: &["bytes"],
+    },
+    FunctionSignature {
+        name: "PG_TOTAL_RELATION_SIZE",
+        args: &["relation"],
+    },
+    FunctionSignature {
+        name: "PG_RELATION_SIZE",
+        args: &["relation"],
+    },
+];
+
+/// The signature of `name`, matched case-insensitively.
+pub fn function_signature(name: &str) -> Option<&'static FunctionSignature> {
+    FUNCTION_SIGNATURES
+        .iter()
+        .find(|sig| sig.name.eq_ignore_ascii_case(name))
+}
+
+/// Byte ranges of the arguments of the call whose `(` is at `open` in
+/// `line`, each trimmed of surrounding spaces, plus the position of the
+/// closing `)`. Commas inside quotes or nested parentheses do not split.
+/// Returns `None` when the call is not closed on this line.
+pub fn argument_ranges(line: &str, open: usize) -> Option<(Vec<(usize, usize)>, usize)> {
+    if line.as_bytes().get(open) != Some(&b'(') {
+        return None;
+    }
+    let mut ranges = Vec::new();
+    let mut start = open + 1;
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    for (i, c) in line[open + 1..].char_indices() {
+        let i = open + 1 + i;
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') if depth > 0 => depth -= 1,
+            (None, ')') => {
+                ranges.push(trimmed_range(line, start, i));
+                return Some((ranges, i));
+            }
+            (None, ',') if depth == 0 => {
+                ranges.push(trimmed_range(line, start, i));
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The innermost known function call that `before` (the text up to the
+/// cursor) ends inside, and the index of the argument being typed.
+/// Parentheses in quotes and `--` comments are skipped, and a `;` starts
+/// over. Calls to functions
+/// without a signature (and bare parentheses) defer to the enclosing call.
+pub fn enclosing_call(before: &str) -> Option<(&'static FunctionSignature, usize)> {
+    // Position of each unclosed `(` and the commas seen at its level
+    let mut open: Vec<(usize, usize)> = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut comment = false;
+    let mut chars = before.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if comment {
+            comment = c != '\n';
+            continue;
+        }
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '-') if chars.peek().is_some_and(|&(_, next)| next == '-') => comment = true,
+            (None, '(') => open.push((i, 0)),
+            (None, ';') => open.clear(),
+            (None, ')') => {
+                open.pop();
+            }
+            (None, ',') => {
+                if let Some((_, commas)) = open.last_mut() {
+                    *commas += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    open.iter().rev().find_map(|&(paren, commas)| {
+        let name_part = before[..paren].trim_end();
+        let start = name_part
+            .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        function_signature(&name_part[start..]).map(|sig| (sig, commas))
+    })
+}
+
+fn trimmed_range(line: &str, start: usize, end: usize) -> (usize, usize) {
+    let text = &line[start..end];
+    let leading = text.len() - text.trim_start().len();
+    let trailing = text.len() - text.trim_end().len();
+    (start + leading, (end - trailing).max(start + leading))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_function_signature_lookup() {
+        let sig = function_signature("split_part").unwrap();
+        assert_eq!(sig.args, &["string", "delimiter", "n"]);
+        assert!(sig.has_placeholders());
+        assert!(!function_signature("NOW").unwrap().has_placeholders());
+        assert!(!function_signature("CAST").unwrap().has_placeholders());
+        assert!(!function_signature("CONCAT").unwrap().has_placeholders());
+        assert!(function_signature("my_function").is_none());
+    }
+
+    #[test]
+    fn test_enclosing_call() {
+        let (sig, arg) = enclosing_call("SELECT split_part(name, ',', ").unwrap();
+        assert_eq!((sig.name, arg), ("SPLIT_PART", 2));
+
+        // Innermost known call wins; unknown calls and bare parens defer
+        let (sig, arg) = enclosing_call("SELECT ROUND(SUM(x").unwrap();
+        assert_eq!((sig.name, arg), ("SUM", 0));
+        let (sig, arg) = enclosing_call("SELECT ROUND(my_fn(a, b), (1 + ").unwrap();
+        assert_eq!((sig.name, arg), ("ROUND", 1));
+
+        assert!(enclosing_call("SELECT ROUND(x, 2) FROM t").is_none());
+        assert!(enclosing_call("SELECT ROUND(x; SELECT ").is_none());
+        assert!(enclosing_call("WHERE id IN (1, ").is_none());
+        assert!(enclosing_call("SELECT 'ROUND(x, ").is_none());
+        assert!(enclosing_call("SELECT -- ROUND(x\nFROM t").is_none());
+    }
+
+    #[test]
+    fn test_argument_ranges() {
+        let line = "SELECT SPLIT_PART(name, ',', 2) FROM t";
+        let open = line.find('(').unwrap();
+        let (ranges, close) = argument_ranges(line, open).unwrap();
+        let args: Vec<&str> = ranges.iter().map(|&(s, e)| &line[s..e]).collect();
+        assert_eq!(args, vec!["name", "','", "2"]);
+        assert_eq!(&line[close..close + 1], ")");
+    }
+
+    #[test]
+    fn test_argument_ranges_nested_and_unclosed() {
+        let line = "COALESCE(LOWER(a, b), 'x)')";
+        let (ranges, _) = argument_ranges(line, 8).unwrap();
+        let args: Vec<&str> = ranges.iter().map(|&(s, e)| &line[s..e]).collect();
+        assert_eq!(args, vec!["LOWER(a, b)", "'x)'"]);
+
+        assert!(argument_ranges("ROUND(x, ", 5).is_none());
+        assert!(argument_ranges("ROUND x", 5).is_none());
+
+        // An empty argument list still has one (empty) slot
+        assert_eq!(argument_ranges("NOW()", 3).unwrap().0, vec![(4, 4)]);
+    }
+}
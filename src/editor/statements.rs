@@ -0,0 +1,344 @@
+/// Split `text` into statements on `;`, returning the `(start, end)` byte
+/// range of each, without its `;`. Semicolons inside quoted strings and
+/// identifiers, `E'...'` strings with backslash escapes, `$$` / `$tag$`
+/// dollar-quoted bodies, `--` line comments and (nested) block comments do
+/// not split. Ranges holding only whitespace are skipped.
+pub fn split_statements(text: &str) -> Vec<(usize, usize)> {
+    let mut boundaries = Vec::new();
+    let mut start = 0;
+    scan(text, |byte_pos| {
+        if !text[start..byte_pos].trim().is_empty() {
+            boundaries.push((start, byte_pos));
+        }
+        start = byte_pos + 1;
+    });
+
+    // Last statement (after the final `;`, or the whole text without one)
+    if start < text.len() && !text[start..].trim().is_empty() {
+        boundaries.push((start, text.len()));
+    }
+
+    boundaries
+}
+
+/// The tag of a dollar quote left open at the end of `text`, such as `$$`
+/// while inside a function body that continues on a later line.
+pub fn open_dollar_quote(text: &str) -> Option<&str> {
+    scan(text, |_| {})
+}
+
+/// The `$$` or `$tag$` opening a dollar quote at byte `pos` of `text`. A tag
+/// is an identifier that doesn't start with a digit, so `$1` parameters and
+/// names like `a$b$` don't open one.
+pub fn dollar_quote_tag(text: &str, pos: usize) -> Option<&str> {
+    let rest = text.get(pos..)?.strip_prefix('$')?;
+    let follows_word = text[..pos]
+        .chars()
+        .next_back()
+        .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '$');
+    if follows_word || rest.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    let tag_len = rest
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(rest.len());
+    rest[tag_len..]
+        .starts_with('$')
+        .then(|| &text[pos..pos + tag_len + 2])
+}
+
+/// Walk `text`, calling `on_semicolon` with the byte offset of each `;`
+/// that ends a statement. Returns the tag of a dollar quote still open at
+/// the end.
+fn scan(text: &str, mut on_semicolon: impl FnMut(usize)) -> Option<&str> {
+    let mut in_single_quote = false;
+    let mut backslash_escapes = false;
+    let mut in_double_quote = false;
+    let mut in_line_comment = false;
+    let mut block_comment_depth = 0usize;
+    let mut dollar_tag: Option<&str> = None;
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let len = chars.len();
+    let next_is = |i: usize, c: char| i + 1 < len && chars[i + 1].1 == c;
+    let mut i = 0;
+
+    while i < len {
+        let (byte_pos, c) = chars[i];
+
+        if let Some(tag) = dollar_tag {
+            if text[byte_pos..].starts_with(tag) {
+                dollar_tag = None;
+                i += tag.chars().count();
+                continue;
+            }
+        } else if in_line_comment {
+            if c == '\n' {
+                in_line_comment = false;
+            }
+        } else if block_comment_depth > 0 {
+            if c == '*' && next_is(i, '/') {
+                block_comment_depth -= 1;
+                i += 1;
+            } else if c == '/' && next_is(i, '*') {
+                block_comment_depth += 1;
+                i += 1;
+            }
+        } else if in_single_quote {
+            if c == '\\' && backslash_escapes {
+                i += 1;
+            } else if c == '\'' {
+                // A doubled quote ('') is an escaped quote
+                if next_is(i, '\'') {
+                    i += 1;
+                } else {
+                    in_single_quote = false;
+                }
+            }
+        } else if in_double_quote {
+            if c == '"' {
+                in_double_quote = false;
+            }
+        } else {
+            match c {
+                '\'' => {
+                    in_single_quote = true;
+                    backslash_escapes = is_escape_string_prefix(&chars, i);
+                }
+                '"' => in_double_quote = true,
+                '-' if next_is(i, '-') => in_line_comment = true,
+                '/' if next_is(i, '*') => {
+                    block_comment_depth = 1;
+                    i += 1;
+                }
+                '$' => {
+                    if let Some(tag) = dollar_quote_tag(text, byte_pos) {
+                        dollar_tag = Some(tag);
+                        i += tag.chars().count();
+                        continue;
+                    }
+                }
+                ';' => on_semicolon(byte_pos),
+                _ => {}
+            }
+        }
+
+        i += 1;
+    }
+
+    dollar_tag
+}
+
+/// The statements of `text` as trimmed slices without their `;`, dropping
+/// any that are empty or only `--` comments.
+pub fn statement_texts(text: &str) -> Vec<&str> {
+    split_statements(text)
+        .into_iter()
+        .map(|(start, end)| text[start..end].trim().trim_end_matches(';').trim())
+        .filter(|statement| {
+            statement.lines().any(|line| {
+                let line = line.trim();
+                !line.is_empty() && !line.starts_with("--")
+            })
+        })
+        .collect()
+}
+
+/// Whether `statement` starts or ends a transaction itself (`BEGIN`,
+/// `COMMIT`, ...). `ROLLBACK TO` a savepoint does not count.
+pub fn controls_transaction(statement: &str) -> bool {
+    let mut words = leading_words(statement);
+    match words.next().as_deref() {
+        Some("BEGIN" | "START" | "COMMIT" | "END" | "ABORT") => true,
+        Some("ROLLBACK") => !matches!(words.next().as_deref(), Some("TO")),
+        _ => false,
+    }
+}
+
+/// Whether `statement` opens a transaction (`BEGIN`, `START TRANSACTION`).
+pub fn opens_transaction(statement: &str) -> bool {
+    matches!(
+        leading_words(statement).next().as_deref(),
+        Some("BEGIN" | "START")
+    )
+}
+
+/// The upper-cased words of `statement`, skipping `--` comment lines.
+fn leading_words(statement: &str) -> impl Iterator<Item = String> + '_ {
+    statement
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with("--"))
+        .flat_map(str::split_whitespace)
+        .map(|word| word.trim_end_matches(';').to_uppercase())
+}
+
+/// Whether the quote at `quote` opens an `E'...'` escape string: it follows
+/// an `E` that is not the end of a longer word.
+fn is_escape_string_prefix(chars: &[(usize, char)], quote: usize) -> bool {
+    let Some(prefix) = quote.checked_sub(1) else {
+        return false;
+    };
+    let before_prefix = prefix
+        .checked_sub(1)
+        .map(|i| chars[i].1)
+        .filter(|c| c.is_alphanumeric() || *c == '_');
+    matches!(chars[prefix].1, 'e' | 'E') && before_prefix.is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pieces(text: &str) -> Vec<&str> {
+        split_statements(text)
+            .into_iter()
+            .map(|(start, end)| text[start..end].trim())
+            .collect()
+    }
+
+    #[test]
+    fn test_splits_on_semicolons() {
+        assert_eq!(pieces("SELECT 1; SELECT 2;"), vec!["SELECT 1", "SELECT 2"]);
+        assert_eq!(pieces("SELECT 1;\nSELECT 2"), vec!["SELECT 1", "SELECT 2"]);
+        assert_eq!(pieces("SELECT 1"), vec!["SELECT 1"]);
+    }
+
+    #[test]
+    fn test_ranges_exclude_semicolons() {
+        let text = "SELECT 1;  SELECT 2";
+        assert_eq!(split_statements(text), vec![(0, 8), (9, 19)]);
+    }
+
+    #[test]
+    fn test_blank_input_and_empty_statements() {
+        assert!(split_statements("").is_empty());
+        assert!(split_statements("  \n ").is_empty());
+        assert!(split_statements(" ; ;\n").is_empty());
+        assert_eq!(pieces(";;SELECT 1;;"), vec!["SELECT 1"]);
+    }
+
+    #[test]
+    fn test_semicolons_in_strings_and_identifiers() {
+        assert_eq!(
+            pieces("SELECT 'a;b'; SELECT \"odd;name\" FROM t"),
+            vec!["SELECT 'a;b'", "SELECT \"odd;name\" FROM t"]
+        );
+        // A doubled quote stays inside the string
+        assert_eq!(
+            pieces("SELECT 'it''s; fine'; SELECT 2"),
+            vec!["SELECT 'it''s; fine'", "SELECT 2"]
+        );
+    }
+
+    #[test]
+    fn test_escape_strings() {
+        assert_eq!(
+            pieces(r"SELECT E'it\'s; fine'; SELECT 2"),
+            vec![r"SELECT E'it\'s; fine'", "SELECT 2"]
+        );
+        // Outside E'...' a backslash is an ordinary character
+        assert_eq!(
+            pieces(r"SELECT 'C:\'; SELECT 2"),
+            vec![r"SELECT 'C:\'", "SELECT 2"]
+        );
+        assert_eq!(
+            pieces(r"SELECT name'x\'; SELECT 2"),
+            vec![r"SELECT name'x\'", "SELECT 2"]
+        );
+    }
+
+    #[test]
+    fn test_comments() {
+        assert_eq!(
+            pieces("SELECT 1 -- no split; here\n; SELECT 2"),
+            vec!["SELECT 1 -- no split; here", "SELECT 2"]
+        );
+        assert_eq!(
+            pieces("SELECT /* a; b */ 1; SELECT 2"),
+            vec!["SELECT /* a; b */ 1", "SELECT 2"]
+        );
+    }
+
+    #[test]
+    fn test_nested_block_comments() {
+        assert_eq!(
+            pieces("SELECT /* outer /* inner; */ still comment; */ 1; SELECT 2"),
+            vec![
+                "SELECT /* outer /* inner; */ still comment; */ 1",
+                "SELECT 2"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dollar_quoted_bodies() {
+        let function =
+            "CREATE FUNCTION f() RETURNS int AS $$\nBEGIN\n  RETURN 1;\nEND;\n$$ LANGUAGE plpgsql";
+        assert_eq!(
+            pieces(&format!("{function}; SELECT 2")),
+            vec![function, "SELECT 2"]
+        );
+        assert_eq!(
+            pieces("SELECT $fn$ a; $$ b; $fn$; SELECT 2"),
+            vec!["SELECT $fn$ a; $$ b; $fn$", "SELECT 2"]
+        );
+        // Quotes and comments inside the body don't matter
+        assert_eq!(
+            pieces("SELECT $$ it's -- ; /* $$; SELECT 2"),
+            vec!["SELECT $$ it's -- ; /* $$", "SELECT 2"]
+        );
+    }
+
+    #[test]
+    fn test_dollar_signs_that_are_not_quotes() {
+        assert_eq!(
+            pieces("SELECT $1; SELECT a$b$; SELECT 3"),
+            vec!["SELECT $1", "SELECT a$b$", "SELECT 3"]
+        );
+        assert_eq!(dollar_quote_tag("$$", 0), Some("$$"));
+        assert_eq!(dollar_quote_tag("x $body$ y", 2), Some("$body$"));
+        assert_eq!(dollar_quote_tag("$1$", 0), None);
+        assert_eq!(dollar_quote_tag("$a b$", 0), None);
+        assert_eq!(dollar_quote_tag("a$b$", 1), None);
+    }
+
+    #[test]
+    fn test_open_dollar_quote() {
+        assert_eq!(open_dollar_quote("AS $body$\nBEGIN"), Some("$body$"));
+        assert_eq!(open_dollar_quote("AS $$ x $$;"), None);
+        assert_eq!(open_dollar_quote("SELECT '$$'"), None);
+        assert_eq!(open_dollar_quote("-- $$\nSELECT 1"), None);
+    }
+
+    #[test]
+    fn test_multibyte_text() {
+        let text = "SELECT 'é;ü'; SELECT '日本'";
+        assert_eq!(pieces(text), vec!["SELECT 'é;ü'", "SELECT '日本'"]);
+    }
+
+    #[test]
+    fn test_controls_transaction() {
+        assert!(controls_transaction("BEGIN"));
+        assert!(controls_transaction(
+            "-- apply\nstart transaction isolation level serializable"
+        ));
+        assert!(controls_transaction("commit;"));
+        assert!(controls_transaction("ROLLBACK"));
+        assert!(!controls_transaction("ROLLBACK TO SAVEPOINT a"));
+        assert!(!controls_transaction("SAVEPOINT a"));
+        assert!(!controls_transaction("UPDATE t SET begin = 1"));
+
+        assert!(opens_transaction("-- apply\nbegin;"));
+        assert!(opens_transaction("START TRANSACTION READ ONLY"));
+        assert!(!opens_transaction("COMMIT"));
+    }
+
+    #[test]
+    fn test_statement_texts() {
+        assert_eq!(
+            statement_texts("SELECT 1;\n  SELECT ';';\n\n-- done\n"),
+            vec!["SELECT 1", "SELECT ';'"]
+        );
+        assert!(statement_texts("  ;\n").is_empty());
+    }
+}
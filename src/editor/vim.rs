@@ -0,0 +1,467 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use super::TextBuffer;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VimMode {
+    Normal,
+    Insert,
+    Visual,
+}
+
+/// What the editor should do after a key went through vim handling.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VimOutcome {
+    /// The key was consumed.
+    Handled,
+    /// Not a vim key (insert-mode typing, F-keys, Ctrl shortcuts); process it
+    /// with the regular editor bindings.
+    PassThrough,
+    /// A `:` command line was submitted, without the leading colon.
+    Command(String),
+    /// Feedback for the status bar, e.g. a failed search.
+    Message(String),
+}
+
+/// Line being typed after `/` or `:`.
+#[derive(Debug, Clone, PartialEq)]
+enum Prompt {
+    Search(String),
+    Command(String),
+}
+
+/// Text held by `y`/`d` for `p`/`P`. Line-wise registers paste whole lines.
+#[derive(Debug, Clone, PartialEq)]
+struct Register {
+    text: String,
+    linewise: bool,
+}
+
+/// Modal (vim-style) editing layered on `TextBuffer`'s movement and
+/// selection primitives.
+#[derive(Debug, Clone)]
+pub struct VimState {
+    pub mode: VimMode,
+    /// First key of a two-key command (`dd`, `yy`, `gg`).
+    pending: Option<char>,
+    prompt: Option<Prompt>,
+    last_search: Option<String>,
+    register: Option<Register>,
+    /// Where `v` was pressed; the selection runs from here to the cursor.
+    visual_anchor: (usize, usize),
+}
+
+impl Default for VimState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VimState {
+    pub fn new() -> Self {
+        Self {
+            mode: VimMode::Normal,
+            pending: None,
+            prompt: None,
+            last_search: None,
+            register: None,
+            visual_anchor: (0, 0),
+        }
+    }
+
+    pub fn mode_label(&self) -> &'static str {
+        match self.mode {
+            VimMode::Normal => "NORMAL",
+            VimMode::Insert => "INSERT",
+            VimMode::Visual => "VISUAL",
+        }
+    }
+
+    /// The `/search` or `:command` line being typed, if any.
+    pub fn prompt_line(&self) -> Option<String> {
+        match &self.prompt {
+            Some(Prompt::Search(s)) => Some(format!("/{}", s)),
+            Some(Prompt::Command(s)) => Some(format!(":{}", s)),
+            None => None,
+        }
+    }
+
+    pub fn handle_key(&mut self, buf: &mut TextBuffer, key: KeyEvent) -> VimOutcome {
+        if self.prompt.is_some() {
+            return self.handle_prompt_key(buf, key);
+        }
+        match self.mode {
+            VimMode::Insert => {
+                if key.code == KeyCode::Esc {
+                    self.mode = VimMode::Normal;
+                    if buf.cursor_x > 0 {
+                        buf.cursor_x -= 1;
+                    }
+                    VimOutcome::Handled
+                } else {
+                    VimOutcome::PassThrough
+                }
+            }
+            VimMode::Normal | VimMode::Visual => self.handle_command_key(buf, key),
+        }
+    }
+
+    fn handle_prompt_key(&mut self, buf: &mut TextBuffer, key: KeyEvent) -> VimOutcome {
+        let Some(prompt) = self.prompt.as_mut() else {
+            return VimOutcome::Handled;
+        };
+        let input = match prompt {
+            Prompt::Search(s) | Prompt::Command(s) => s,
+        };
+        match key.code {
+            KeyCode::Esc => self.prompt = None,
+            KeyCode::Backspace if input.is_empty() => self.prompt = None,
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Enter => match self.prompt.take() {
+                Some(Prompt::Search(pattern)) => {
+                    if !pattern.is_empty() {
+                        self.last_search = Some(pattern);
+                    }
+                    return self.search(buf, true);
+                }
+                Some(Prompt::Command(command)) => {
+                    let command = command.trim().to_string();
+                    if !command.is_empty() {
+                        return VimOutcome::Command(command);
+                    }
+                }
+                None => {}
+            },
+            _ => {}
+        }
+        VimOutcome::Handled
+    }
+
+    fn handle_command_key(&mut self, buf: &mut TextBuffer, key: KeyEvent) -> VimOutcome {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        if ctrl {
+            return match key.code {
+                KeyCode::Char('r') if self.mode == VimMode::Normal => {
+                    buf.redo();
+                    VimOutcome::Handled
+                }
+                _ => VimOutcome::PassThrough,
+            };
+        }
+
+        let c = match key.code {
+            KeyCode::Char(c) => c,
+            KeyCode::Left | KeyCode::Backspace => 'h',
+            KeyCode::Down | KeyCode::Enter => 'j',
+            KeyCode::Up => 'k',
+            KeyCode::Right => 'l',
+            KeyCode::Home => '0',
+            KeyCode::End => '$',
+            KeyCode::Delete => 'x',
+            KeyCode::Esc => {
+                self.pending = None;
+                self.leave_visual(buf);
+                return VimOutcome::Handled;
+            }
+            // Tab would insert indentation; it has no Normal mode meaning here
+            KeyCode::Tab => return VimOutcome::Handled,
+            _ => return VimOutcome::PassThrough,
+        };
+
+        if let Some(first) = self.pending.take() {
+            match (first, c) {
+                ('g', 'g') => self.motion(buf, |b| b.move_to_start()),
+                ('d', 'd') => {
+                    let text = buf.delete_line();
+                    self.register = Some(Register {
+                        text,
+                        linewise: true,
+                    });
+                }
+                ('y', 'y') => {
+                    self.register = Some(Register {
+                        text: buf.current_line().to_string(),
+                        linewise: true,
+                    });
+                }
+                _ => {}
+            }
+            return VimOutcome::Handled;
+        }
+
+        if self.motion_key(buf, c) {
+            return VimOutcome::Handled;
+        }
+
+        match (self.mode, c) {
+            (VimMode::Visual, 'y') => {
+                self.register = self.take_visual(buf, false);
+            }
+            (VimMode::Visual, 'd' | 'x') => {
+                self.register = self.take_visual(buf, true);
+            }
+            (VimMode::Visual, 'v') => self.leave_visual(buf),
+            (VimMode::Visual, _) => {}
+
+            (_, 'i') => self.mode = VimMode::Insert,
+            (_, 'a') => {
+                if buf.cursor_x < buf.current_line().len() {
+                    buf.cursor_x += 1;
+                }
+                self.mode = VimMode::Insert;
+            }
+            (_, 'I') => {
+                buf.move_to_line_start();
+                self.mode = VimMode::Insert;
+            }
+            (_, 'A') => {
+                buf.move_to_line_end();
+                self.mode = VimMode::Insert;
+            }
+            (_, 'o') => {
+                buf.open_line(true);
+                self.mode = VimMode::Insert;
+            }
+            (_, 'O') => {
+                buf.open_line(false);
+                self.mode = VimMode::Insert;
+            }
+            (_, 'x') if buf.cursor_x < buf.current_line().len() => buf.delete(),
+            (_, 'd' | 'y' | 'g') => self.pending = Some(c),
+            (_, 'p') => self.put(buf, true),
+            (_, 'P') => self.put(buf, false),
+            (_, 'u') => {
+                buf.undo();
+            }
+            (_, 'v') => {
+                self.visual_anchor = (buf.cursor_x, buf.cursor_y);
+                buf.start_selection();
+                self.mode = VimMode::Visual;
+            }
+            (_, '/') => self.prompt = Some(Prompt::Search(String::new())),
+            (_, ':') => self.prompt = Some(Prompt::Command(String::new())),
+            (_, 'n') => return self.search(buf, true),
+            (_, 'N') => return self.search(buf, false),
+            // Unbound keys in Normal mode never insert text
+            _ => {}
+        }
+        VimOutcome::Handled
+    }
+
+    /// Apply a cursor motion for `c`. Returns false if `c` is not a motion.
+    fn motion_key(&mut self, buf: &mut TextBuffer, c: char) -> bool {
+        match c {
+            'h' => {
+                if buf.cursor_x > 0 {
+                    self.motion(buf, |b| b.move_left());
+                }
+            }
+            'l' => {
+                if buf.cursor_x < buf.current_line().len() {
+                    self.motion(buf, |b| b.move_right());
+                }
+            }
+            'j' => self.motion(buf, |b| b.move_down()),
+            'k' => self.motion(buf, |b| b.move_up()),
+            'w' => self.motion(buf, |b| b.move_word_right()),
+            'b' => self.motion(buf, |b| b.move_word_left()),
+            '0' => self.motion(buf, |b| b.move_to_line_start()),
+            '$' => self.motion(buf, |b| b.move_to_line_end()),
+            'G' => self.motion(buf, |b| {
+                b.move_to_end();
+                b.move_to_line_start();
+            }),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Run a buffer movement, keeping the visual selection anchored (buffer
+    /// movements clear the selection).
+    fn motion(&self, buf: &mut TextBuffer, movement: impl FnOnce(&mut TextBuffer)) {
+        movement(buf);
+        if self.mode == VimMode::Visual {
+            buf.selection_start = Some(self.visual_anchor);
+        }
+    }
+
+    fn leave_visual(&mut self, buf: &mut TextBuffer) {
+        if self.mode == VimMode::Visual {
+            buf.clear_selection();
+            self.mode = VimMode::Normal;
+        }
+    }
+
+    /// Yank (and with `delete`, remove) the visual selection. Vim selections
+    /// include the character under the cursor, so the end is extended by one.
+    fn take_visual(&mut self, buf: &mut TextBuffer, delete: bool) -> Option<Register> {
+        let (start, end) = buf.get_selection()?;
+        let end_x = (end.0 + 1).min(buf.lines[end.1].len());
+        buf.selection_start = Some(start);
+        buf.cursor_x = end_x;
+        buf.cursor_y = end.1;
+        let text = buf.get_selected_text().unwrap_or_default();
+        if delete {
+            buf.delete_selection();
+        } else {
+            buf.clear_selection();
+            buf.cursor_x = start.0;
+            buf.cursor_y = start.1;
+        }
+        self.mode = VimMode::Normal;
+        Some(Register {
+            text,
+            linewise: false,
+        })
+    }
+
+    fn put(&self, buf: &mut TextBuffer, after: bool) {
+        let Some(register) = &self.register else {
+            return;
+        };
+        if register.linewise {
+            buf.put_lines(&register.text, after);
+        } else {
+            if after && buf.cursor_x < buf.current_line().len() {
+                buf.cursor_x += 1;
+            }
+            buf.insert_text(&register.text);
+        }
+    }
+
+    fn search(&self, buf: &mut TextBuffer, forward: bool) -> VimOutcome {
+        let Some(pattern) = &self.last_search else {
+            return VimOutcome::Message("No previous search pattern".to_string());
+        };
+        match buf.find(pattern, forward) {
+            Some((x, y)) => {
+                buf.clear_selection();
+                buf.cursor_x = x;
+                buf.cursor_y = y;
+                VimOutcome::Handled
+            }
+            None => VimOutcome::Message(format!("Pattern not found: {}", pattern)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(vim: &mut VimState, buf: &mut TextBuffer, keys: &str) -> VimOutcome {
+        let mut outcome = VimOutcome::Handled;
+        for c in keys.chars() {
+            let code = match c {
+                '\n' => KeyCode::Enter,
+                '\x1b' => KeyCode::Esc,
+                c => KeyCode::Char(c),
+            };
+            outcome = vim.handle_key(buf, KeyEvent::new(code, KeyModifiers::NONE));
+        }
+        outcome
+    }
+
+    #[test]
+    fn test_starts_in_normal_and_does_not_insert() {
+        let mut vim = VimState::new();
+        let mut buf = TextBuffer::from_text("select 1");
+        press(&mut vim, &mut buf, "zq");
+        assert_eq!(buf.text(), "select 1");
+        assert_eq!(vim.mode, VimMode::Normal);
+    }
+
+    #[test]
+    fn test_hjkl_movement() {
+        let mut vim = VimState::new();
+        let mut buf = TextBuffer::from_text("abc\ndef");
+        press(&mut vim, &mut buf, "llj");
+        assert_eq!((buf.cursor_x, buf.cursor_y), (2, 1));
+        press(&mut vim, &mut buf, "hk");
+        assert_eq!((buf.cursor_x, buf.cursor_y), (1, 0));
+        // h at column 0 does not wrap to the previous line
+        press(&mut vim, &mut buf, "jhhh");
+        assert_eq!((buf.cursor_x, buf.cursor_y), (0, 1));
+    }
+
+    #[test]
+    fn test_insert_mode_passes_keys_through() {
+        let mut vim = VimState::new();
+        let mut buf = TextBuffer::from_text("");
+        assert_eq!(press(&mut vim, &mut buf, "i"), VimOutcome::Handled);
+        assert_eq!(vim.mode, VimMode::Insert);
+        assert_eq!(press(&mut vim, &mut buf, "x"), VimOutcome::PassThrough);
+        press(&mut vim, &mut buf, "\x1b");
+        assert_eq!(vim.mode, VimMode::Normal);
+    }
+
+    #[test]
+    fn test_dd_and_p() {
+        let mut vim = VimState::new();
+        let mut buf = TextBuffer::from_text("one\ntwo\nthree");
+        press(&mut vim, &mut buf, "ddp");
+        assert_eq!(buf.text(), "two\none\nthree");
+        press(&mut vim, &mut buf, "ggyyP");
+        assert_eq!(buf.text(), "two\ntwo\none\nthree");
+    }
+
+    #[test]
+    fn test_visual_yank_includes_cursor_char() {
+        let mut vim = VimState::new();
+        let mut buf = TextBuffer::from_text("select id");
+        press(&mut vim, &mut buf, "vllllly$p");
+        assert_eq!(buf.text(), "select idselect");
+        assert_eq!(vim.mode, VimMode::Normal);
+    }
+
+    #[test]
+    fn test_visual_delete() {
+        let mut vim = VimState::new();
+        let mut buf = TextBuffer::from_text("abcdef");
+        press(&mut vim, &mut buf, "lvld");
+        assert_eq!(buf.text(), "adef");
+        press(&mut vim, &mut buf, "u");
+        assert_eq!(buf.text(), "abcdef");
+    }
+
+    #[test]
+    fn test_search_and_repeat() {
+        let mut vim = VimState::new();
+        let mut buf = TextBuffer::from_text("id, name\nwhere id = 1");
+        press(&mut vim, &mut buf, "/id\n");
+        assert_eq!((buf.cursor_x, buf.cursor_y), (6, 1));
+        press(&mut vim, &mut buf, "n");
+        assert_eq!((buf.cursor_x, buf.cursor_y), (0, 0));
+        assert_eq!(
+            press(&mut vim, &mut buf, "/nope\n"),
+            VimOutcome::Message("Pattern not found: nope".to_string())
+        );
+    }
+
+    #[test]
+    fn test_command_line() {
+        let mut vim = VimState::new();
+        let mut buf = TextBuffer::new();
+        press(&mut vim, &mut buf, ":ru");
+        assert_eq!(vim.prompt_line().as_deref(), Some(":ru"));
+        assert_eq!(
+            press(&mut vim, &mut buf, "n\n"),
+            VimOutcome::Command("run".to_string())
+        );
+        assert_eq!(vim.prompt_line(), None);
+    }
+
+    #[test]
+    fn test_open_line_and_append() {
+        let mut vim = VimState::new();
+        let mut buf = TextBuffer::from_text("select");
+        press(&mut vim, &mut buf, "o");
+        assert_eq!(vim.mode, VimMode::Insert);
+        assert_eq!(buf.lines, vec!["select", ""]);
+        press(&mut vim, &mut buf, "\x1bkA");
+        assert_eq!(buf.cursor_x, 6);
+    }
+}
@@ -1,3 +1,5 @@
+use crate::db::format_row_count;
+
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct PlanNode {
@@ -12,6 +14,19 @@ pub struct PlanNode {
     pub depth: usize,
 }
 
+impl PlanNode {
+    /// Lines this node and its subtree occupy in the visual plan: one per
+    /// node plus one per detail.
+    pub fn line_count(&self) -> usize {
+        1 + self.details.len()
+            + self
+                .children
+                .iter()
+                .map(PlanNode::line_count)
+                .sum::<usize>()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct QueryPlan {
     pub root: PlanNode,
@@ -20,6 +35,19 @@ pub struct QueryPlan {
     pub execution_time: Option<f64>,
 }
 
+impl QueryPlan {
+    /// One-line summary of the planner's top-level estimate, e.g.
+    /// "Seq Scan on users: est. cost 35.50, ~2,550 rows".
+    pub fn estimate_summary(&self) -> Option<String> {
+        let (_, total_cost) = self.root.estimated_cost?;
+        let mut summary = format!("{}: est. cost {:.2}", self.root.node_type, total_cost);
+        if let Some(rows) = self.root.estimated_rows {
+            summary.push_str(&format!(", ~{} rows", format_row_count(rows as usize)));
+        }
+        Some(summary)
+    }
+}
+
 pub fn is_explain_query(query: &str) -> bool {
     let trimmed = query.trim().to_uppercase();
     trimmed.starts_with("EXPLAIN")
@@ -298,6 +326,18 @@ mod tests {
         assert_eq!(plan.root.estimated_rows, Some(2550));
     }
 
+    #[test]
+    fn test_estimate_summary() {
+        let output = "\
+Hash Join  (cost=10.00..12045.75 rows=125000 width=72)
+  ->  Seq Scan on orders  (cost=0.00..1000.00 rows=50000 width=36)";
+        let plan = parse_explain_output(output).unwrap();
+        assert_eq!(
+            plan.estimate_summary().as_deref(),
+            Some("Hash Join: est. cost 12045.75, ~125,000 rows")
+        );
+    }
+
     #[test]
     fn test_parse_explain_analyze() {
         let output = "\
@@ -329,6 +369,17 @@ Sort  (cost=100.00..100.25 rows=100 width=40)
             .any(|d| d.contains("Filter")));
     }
 
+    #[test]
+    fn test_plan_line_count() {
+        let output = "\
+Sort  (cost=100.00..100.25 rows=100 width=40)
+  Sort Key: name
+  ->  Seq Scan on users  (cost=0.00..35.50 rows=100 width=40)
+        Filter: (age > 18)";
+        let plan = parse_explain_output(output).unwrap();
+        assert_eq!(plan.root.line_count(), 4);
+    }
+
     #[test]
     fn test_node_color_class() {
         let fast_node = PlanNode {
@@ -1,60 +1,151 @@
-use crate::db::{CellValue, QueryResult};
+use std::io::Write;
+use std::path::Path;
 
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+
+use crate::db::{quote_ident, CellValue, QueryResult};
+
+/// CSV dialect, configured under `[export.csv]` in `config.toml`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct CsvOptions {
+    pub delimiter: char,
+    pub quote: char,
+    /// Quote every field, not just those containing special characters.
+    pub always_quote: bool,
+    /// End lines with `\r\n` instead of `\n`.
+    pub crlf: bool,
+    /// Written, never quoted, for NULL. Empty strings and text equal to it
+    /// are always quoted so loaders can tell them apart.
+    pub null: String,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            quote: '"',
+            always_quote: false,
+            crlf: false,
+            null: String::new(),
+        }
+    }
+}
+
+#[allow(dead_code)]
 pub fn to_csv(result: &QueryResult) -> String {
+    to_csv_with(result, &CsvOptions::default())
+}
+
+pub fn to_csv_with(result: &QueryResult, options: &CsvOptions) -> String {
     let mut output = String::new();
+    let delimiter = options.delimiter.to_string();
+    let line_ending = if options.crlf { "\r\n" } else { "\n" };
 
     // Header
-    let headers: Vec<String> = result.columns.iter().map(|c| csv_escape(&c.name)).collect();
-    output.push_str(&headers.join(","));
-    output.push('\n');
+    let headers: Vec<String> = result
+        .columns
+        .iter()
+        .map(|c| csv_escape_with(&c.name, options))
+        .collect();
+    output.push_str(&headers.join(&delimiter));
+    output.push_str(line_ending);
 
     // Rows
     for row in &result.rows {
-        let cells: Vec<String> = row
-            .iter()
-            .map(|cell| csv_escape(&cell_to_csv(cell)))
-            .collect();
-        output.push_str(&cells.join(","));
-        output.push('\n');
+        let cells: Vec<String> = row.iter().map(|cell| csv_field(cell, options)).collect();
+        output.push_str(&cells.join(&delimiter));
+        output.push_str(line_ending);
     }
 
     output
 }
 
 pub fn to_json(result: &QueryResult) -> String {
-    let mut rows_json: Vec<serde_json::Value> = Vec::new();
+    let rows_json: Vec<serde_json::Value> = result
+        .rows
+        .iter()
+        .map(|row| row_to_json(result, row))
+        .collect();
+
+    serde_json::to_string_pretty(&rows_json).unwrap_or_else(|_| "[]".to_string())
+}
 
+/// JSON Lines: one compact JSON object per row, no enclosing array.
+pub fn to_ndjson(result: &QueryResult) -> String {
+    let mut output = String::new();
     for row in &result.rows {
-        let mut obj = serde_json::Map::new();
-        for (i, cell) in row.iter().enumerate() {
-            let col_name = result
-                .columns
-                .get(i)
-                .map(|c| c.name.clone())
-                .unwrap_or_else(|| format!("column_{}", i));
-            obj.insert(col_name, cell_to_json(cell));
-        }
-        rows_json.push(serde_json::Value::Object(obj));
+        output.push_str(&row_to_json(result, row).to_string());
+        output.push('\n');
     }
+    output
+}
 
-    serde_json::to_string_pretty(&rows_json).unwrap_or_else(|_| "[]".to_string())
+fn row_to_json(result: &QueryResult, row: &[CellValue]) -> serde_json::Value {
+    let mut obj = serde_json::Map::new();
+    for (i, cell) in row.iter().enumerate() {
+        let col_name = result
+            .columns
+            .get(i)
+            .map(|c| c.name.clone())
+            .unwrap_or_else(|| format!("column_{}", i));
+        obj.insert(col_name, cell_to_json(cell));
+    }
+    serde_json::Value::Object(obj)
 }
 
-pub fn to_sql_insert(result: &QueryResult, table_name: &str) -> String {
+/// One `INSERT` per row into `[schema.]table`, with identifiers quoted where
+/// needed. Values whose literal alone would not reload faithfully (dates,
+/// JSON, bytea, arrays, ...) are cast to their column type. With
+/// `type_comments`, a comment block listing each column's type leads.
+pub fn to_sql_insert(
+    result: &QueryResult,
+    schema: Option<&str>,
+    table: &str,
+    type_comments: bool,
+) -> String {
     if result.rows.is_empty() || result.columns.is_empty() {
         return String::new();
     }
 
     let mut output = String::new();
-    let col_names: Vec<&str> = result.columns.iter().map(|c| c.name.as_str()).collect();
+    if type_comments {
+        output.push_str("-- Columns:\n");
+        for col in &result.columns {
+            // Spell out line breaks so a name can't end the comment early
+            let name = quote_ident(&col.name)
+                .replace('\r', "\\r")
+                .replace('\n', "\\n");
+            output.push_str(&format!("--   {} {}\n", name, col.type_name));
+        }
+        output.push('\n');
+    }
 
+    let table_name = match schema {
+        Some(schema) => format!("{}.{}", quote_ident(schema), quote_ident(table)),
+        None => quote_ident(table),
+    };
+    let col_names: Vec<String> = result
+        .columns
+        .iter()
+        .map(|c| quote_ident(&c.name))
+        .collect();
     for row in &result.rows {
         output.push_str(&format!(
             "INSERT INTO {} ({}) VALUES\n",
             table_name,
             col_names.join(", ")
         ));
-        let values: Vec<String> = row.iter().map(cell_to_sql).collect();
+        let values: Vec<String> = row
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| {
+                let type_name = result.columns.get(i).map_or("", |c| c.type_name.as_str());
+                cell_to_sql(cell, type_name)
+            })
+            .collect();
         output.push_str(&format!("  ({});\n", values.join(", ")));
     }
 
@@ -73,7 +164,14 @@ pub fn to_tsv(result: &QueryResult) -> String {
     for row in &result.rows {
         let cells: Vec<String> = row
             .iter()
-            .map(|cell| cell_to_csv(cell).replace('\t', " "))
+            .map(|cell| match cell {
+                // An empty field is NULL; `""` marks an empty string
+                CellValue::Null => String::new(),
+                _ => match cell.display().replace('\t', " ") {
+                    text if text.is_empty() => "\"\"".to_string(),
+                    text => text,
+                },
+            })
             .collect();
         output.push_str(&cells.join("\t"));
         output.push('\n');
@@ -82,6 +180,37 @@ pub fn to_tsv(result: &QueryResult) -> String {
     output
 }
 
+/// An HTML `<table>` with a header row, for pasting into rich-text editors.
+pub fn to_html(result: &QueryResult) -> String {
+    let mut output = String::from("<table>\n<thead>\n<tr>");
+    for col in &result.columns {
+        output.push_str(&format!("<th>{}</th>", html_escape(&col.name)));
+    }
+    output.push_str("</tr>\n</thead>\n<tbody>\n");
+
+    for row in &result.rows {
+        output.push_str("<tr>");
+        for cell in row {
+            output.push_str(&format!("<td>{}</td>", html_escape(&cell_to_csv(cell))));
+        }
+        output.push_str("</tr>\n");
+    }
+
+    output.push_str("</tbody>\n</table>\n");
+    output
+}
+
+/// Write an export to `path`, gzip-compressed when `compress` is set.
+pub fn write_export(path: &Path, content: &str, compress: bool) -> std::io::Result<()> {
+    if !compress {
+        return std::fs::write(path, content);
+    }
+    let mut encoder = GzEncoder::new(std::fs::File::create(path)?, Compression::default());
+    encoder.write_all(content.as_bytes())?;
+    encoder.finish()?;
+    Ok(())
+}
+
 fn cell_to_csv(cell: &CellValue) -> String {
     match cell {
         CellValue::Null => String::new(),
@@ -89,6 +218,20 @@ fn cell_to_csv(cell: &CellValue) -> String {
     }
 }
 
+/// One CSV field: the NULL token as is, or the value's text escaped per
+/// `options`.
+fn csv_field(cell: &CellValue, options: &CsvOptions) -> String {
+    if let CellValue::Null = cell {
+        return options.null.clone();
+    }
+    let text = cell.display();
+    if text.is_empty() || text == options.null {
+        csv_quote(&text, options.quote)
+    } else {
+        csv_escape_with(&text, options)
+    }
+}
+
 fn cell_to_json(cell: &CellValue) -> serde_json::Value {
     match cell {
         CellValue::Null => serde_json::Value::Null,
@@ -98,6 +241,12 @@ fn cell_to_json(cell: &CellValue) -> serde_json::Value {
         CellValue::Int64(i) => serde_json::json!(*i),
         CellValue::Float32(f) => serde_json::json!(*f),
         CellValue::Float64(f) => serde_json::json!(*f),
+        // Exact digits as a JSON number; NaN and ±Infinity have no JSON
+        // number form and stay strings
+        CellValue::Numeric(n) => n
+            .parse::<serde_json::Number>()
+            .map(serde_json::Value::Number)
+            .unwrap_or_else(|_| serde_json::Value::String(n.clone())),
         CellValue::Json(j) => j.clone(),
         CellValue::Array(arr) => {
             let items: Vec<serde_json::Value> = arr.iter().map(cell_to_json).collect();
@@ -107,7 +256,16 @@ fn cell_to_json(cell: &CellValue) -> serde_json::Value {
     }
 }
 
-fn cell_to_sql(cell: &CellValue) -> String {
+/// A SQL literal for `cell`, cast to `type_name` (a PostgreSQL type name
+/// such as `timestamptz` or `_int4`) where the bare literal is ambiguous.
+fn cell_to_sql(cell: &CellValue, type_name: &str) -> String {
+    let cast = |literal: String| {
+        if type_name.is_empty() {
+            literal
+        } else {
+            format!("{}::{}", literal, sql_type(type_name))
+        }
+    };
     match cell {
         CellValue::Null => "NULL".to_string(),
         CellValue::Bool(b) => {
@@ -120,22 +278,77 @@ fn cell_to_sql(cell: &CellValue) -> String {
         CellValue::Int16(i) => i.to_string(),
         CellValue::Int32(i) => i.to_string(),
         CellValue::Int64(i) => i.to_string(),
-        CellValue::Float32(f) => f.to_string(),
-        CellValue::Float64(f) => f.to_string(),
-        CellValue::Text(s) => format!("'{}'", s.replace('\'', "''")),
-        CellValue::Json(j) => format!("'{}'", j.to_string().replace('\'', "''")),
-        other => format!("'{}'", other.display().replace('\'', "''")),
+        CellValue::Float32(f) if f.is_finite() => f.to_string(),
+        CellValue::Float64(f) if f.is_finite() => f.to_string(),
+        CellValue::Float32(f) => cast(sql_float_special(f64::from(*f))),
+        CellValue::Float64(f) => cast(sql_float_special(*f)),
+        // NaN and the infinities fall through to a quoted, cast literal
+        CellValue::Numeric(n) if n.starts_with(|c: char| c == '-' || c.is_ascii_digit()) => {
+            n.clone()
+        }
+        CellValue::Text(s) => sql_string(s),
+        CellValue::Bytes(b) => {
+            let hex: String = b.iter().map(|byte| format!("{:02x}", byte)).collect();
+            cast(format!("'\\x{}'", hex))
+        }
+        CellValue::TimestampTz(dt) => cast(sql_string(&dt.to_rfc3339())),
+        CellValue::Json(j) => cast(sql_string(&j.to_string())),
+        CellValue::Array(items) => {
+            let items: Vec<String> = items.iter().map(|item| cell_to_sql(item, "")).collect();
+            cast(format!("ARRAY[{}]", items.join(", ")))
+        }
+        other => cast(sql_string(&other.display())),
+    }
+}
+
+fn sql_string(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+fn sql_float_special(f: f64) -> String {
+    if f.is_nan() {
+        "'NaN'".to_string()
+    } else if f > 0.0 {
+        "'Infinity'".to_string()
+    } else {
+        "'-Infinity'".to_string()
+    }
+}
+
+/// Type name for a cast; array types are reported as `_elem`.
+fn sql_type(type_name: &str) -> String {
+    match type_name.strip_prefix('_') {
+        Some(elem) => format!("{}[]", quote_ident(elem)),
+        None => quote_ident(type_name),
     }
 }
 
-fn csv_escape(s: &str) -> String {
-    if s.contains(',') || s.contains('"') || s.contains('\n') || s.contains('\r') {
-        format!("\"{}\"", s.replace('"', "\"\""))
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn csv_escape_with(s: &str, options: &CsvOptions) -> String {
+    let quote = options.quote;
+    if options.always_quote
+        || s.contains(options.delimiter)
+        || s.contains(quote)
+        || s.contains('\n')
+        || s.contains('\r')
+    {
+        csv_quote(s, quote)
     } else {
         s.to_string()
     }
 }
 
+fn csv_quote(s: &str, quote: char) -> String {
+    let doubled = format!("{}{}", quote, quote);
+    format!("{}{}{}", quote, s.replace(quote, &doubled), quote)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -191,9 +404,66 @@ mod tests {
 
     #[test]
     fn test_csv_escaping() {
-        assert_eq!(csv_escape("hello"), "hello");
-        assert_eq!(csv_escape("hello,world"), "\"hello,world\"");
-        assert_eq!(csv_escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+        let options = CsvOptions::default();
+        assert_eq!(csv_escape_with("hello", &options), "hello");
+        assert_eq!(csv_escape_with("hello,world", &options), "\"hello,world\"");
+        assert_eq!(
+            csv_escape_with("say \"hi\"", &options),
+            "\"say \"\"hi\"\"\""
+        );
+    }
+
+    #[test]
+    fn test_csv_dialect() {
+        let mut result = make_result();
+        result.rows[0][1] = CellValue::Text("Smith; 'Al'".to_string());
+        let options = CsvOptions {
+            delimiter: ';',
+            quote: '\'',
+            always_quote: false,
+            crlf: true,
+            ..CsvOptions::default()
+        };
+        let csv = to_csv_with(&result, &options);
+        assert!(csv.starts_with("id;name;active\r\n"));
+        assert!(csv.contains("1;'Smith; ''Al''';true\r\n"));
+
+        let options = CsvOptions {
+            always_quote: true,
+            ..CsvOptions::default()
+        };
+        let csv = to_csv_with(&make_result(), &options);
+        // NULL stays unquoted even when every value is quoted
+        assert!(csv.contains("\"2\",\"Bob\",\n"));
+    }
+
+    #[test]
+    fn test_null_and_empty_string_are_distinct() {
+        let mut result = make_result();
+        result.rows[0][1] = CellValue::Text(String::new());
+        let csv = to_csv(&result);
+        assert!(csv.contains("1,\"\",true\n"));
+        assert!(csv.contains("2,Bob,\n"));
+
+        let options = CsvOptions {
+            null: "\\N".to_string(),
+            ..CsvOptions::default()
+        };
+        result.rows[1][1] = CellValue::Text("\\N".to_string());
+        let csv = to_csv_with(&result, &options);
+        assert!(csv.contains("1,\"\",true\n"));
+        assert!(csv.contains("2,\"\\N\",\\N\n"));
+
+        let tsv = to_tsv(&result);
+        assert!(tsv.contains("1\t\"\"\ttrue\n"));
+        assert!(tsv.ends_with("2\t\\N\t\n"));
+
+        let parsed: Vec<serde_json::Value> = serde_json::from_str(&to_json(&result)).unwrap();
+        assert_eq!(parsed[0]["name"], "");
+        assert!(parsed[1]["active"].is_null());
+        let ndjson = to_ndjson(&result);
+        assert!(ndjson.contains(r#""name":"""#));
+        assert!(ndjson.contains(r#""active":null"#));
     }
 
     #[test]
@@ -208,11 +478,22 @@ mod tests {
         assert!(parsed[1]["active"].is_null());
     }
 
+    #[test]
+    fn test_ndjson_export() {
+        let ndjson = to_ndjson(&make_result());
+        let lines: Vec<&str> = ndjson.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], r#"{"active":true,"id":1,"name":"Alice"}"#);
+        let second: serde_json::Value = serde_json::from_str(lines[1]).unwrap();
+        assert!(second["active"].is_null());
+        assert!(to_ndjson(&QueryResult::empty()).is_empty());
+    }
+
     #[test]
     fn test_sql_insert_export() {
         let result = make_result();
-        let sql = to_sql_insert(&result, "users");
-        assert!(sql.contains("INSERT INTO users (id, name, active) VALUES"));
+        let sql = to_sql_insert(&result, None, "users", false);
+        assert!(sql.starts_with("INSERT INTO users (id, name, active) VALUES"));
         assert!(sql.contains("(1, 'Alice', TRUE)"));
         assert!(sql.contains("(2, 'Bob', NULL)"));
     }
@@ -225,17 +506,125 @@ mod tests {
         assert!(tsv.contains("1\tAlice\ttrue\n"));
     }
 
+    #[test]
+    fn test_html_export() {
+        let mut result = make_result();
+        result.rows[1][1] = CellValue::Text("<Bob & co>".to_string());
+        let html = to_html(&result);
+        assert!(
+            html.starts_with("<table>\n<thead>\n<tr><th>id</th><th>name</th><th>active</th></tr>")
+        );
+        assert!(html.contains("<tr><td>1</td><td>Alice</td><td>true</td></tr>"));
+        assert!(html.contains("<td>&lt;Bob &amp; co&gt;</td><td></td>"));
+        assert!(html.ends_with("</tbody>\n</table>\n"));
+    }
+
+    #[test]
+    fn test_write_export_gzip() {
+        use flate2::read::GzDecoder;
+        use std::io::Read;
+
+        let path = std::env::temp_dir().join(format!("pgrsql_test_{}.csv.gz", std::process::id()));
+        let csv = to_csv(&make_result());
+        write_export(&path, &csv, true).unwrap();
+
+        let mut decoded = String::new();
+        GzDecoder::new(std::fs::File::open(&path).unwrap())
+            .read_to_string(&mut decoded)
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(decoded, csv);
+    }
+
     #[test]
     fn test_empty_result_sql_insert() {
         let result = QueryResult::empty();
-        let sql = to_sql_insert(&result, "users");
+        let sql = to_sql_insert(&result, None, "users", true);
         assert!(sql.is_empty());
     }
 
+    #[test]
+    fn test_sql_insert_type_comments() {
+        let sql = to_sql_insert(&make_result(), None, "users", true);
+        assert!(sql
+            .starts_with("-- Columns:\n--   id int4\n--   name text\n--   active bool\n\nINSERT"));
+    }
+
+    #[test]
+    fn test_sql_insert_type_comments_escape_names() {
+        let mut result = make_result();
+        result.columns[1].name = "name\nDROP TABLE users; --".to_string();
+        let sql = to_sql_insert(&result, None, "users", true);
+        assert!(sql.contains("--   \"name\\nDROP TABLE users; --\" text\n"));
+        let comments = sql.split("\n\n").next().unwrap();
+        assert!(comments.lines().all(|line| line.starts_with("--")));
+    }
+
+    #[test]
+    fn test_sql_insert_quotes_identifiers_and_values() {
+        let mut result = make_result();
+        result.columns[0].name = "order".to_string();
+        result.columns[1].name = "Full Name".to_string();
+        result.rows[0][1] = CellValue::Text("it's 'quoted'".to_string());
+        result.rows[1][1] = CellValue::Text("NULL".to_string());
+
+        let sql = to_sql_insert(&result, Some("Sales"), "user", false);
+        assert!(sql
+            .contains("INSERT INTO \"Sales\".\"user\" (\"order\", \"Full Name\", active) VALUES"));
+        assert!(sql.contains("(1, 'it''s ''quoted''', TRUE);"));
+        // A real NULL is bare; the string "NULL" stays a string
+        assert!(sql.contains("(2, 'NULL', NULL);"));
+    }
+
+    #[test]
+    fn test_sql_typed_literals() {
+        use chrono::{NaiveDate, TimeZone, Utc};
+
+        let date = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        assert_eq!(
+            cell_to_sql(&CellValue::Date(date), "date"),
+            "'2024-03-01'::date"
+        );
+        let ts = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        assert_eq!(
+            cell_to_sql(&CellValue::TimestampTz(ts), "timestamptz"),
+            "'2024-03-01T12:00:00+00:00'::timestamptz"
+        );
+        assert_eq!(
+            cell_to_sql(&CellValue::Json(serde_json::json!({"a": "it's"})), "jsonb"),
+            "'{\"a\":\"it''s\"}'::jsonb"
+        );
+        assert_eq!(
+            cell_to_sql(&CellValue::Bytes(vec![0xde, 0xad]), "bytea"),
+            "'\\xdead'::bytea"
+        );
+        assert_eq!(
+            cell_to_sql(
+                &CellValue::Array(vec![CellValue::Int32(1), CellValue::Null]),
+                "_int4"
+            ),
+            "ARRAY[1, NULL]::int4[]"
+        );
+        assert_eq!(
+            cell_to_sql(&CellValue::Float64(f64::NAN), "float8"),
+            "'NaN'::float8"
+        );
+        assert_eq!(cell_to_sql(&CellValue::Float64(1.5), "float8"), "1.5");
+        assert_eq!(
+            cell_to_sql(&CellValue::Numeric("-1.0000000001".into()), "numeric"),
+            "-1.0000000001"
+        );
+        assert_eq!(
+            cell_to_sql(&CellValue::Numeric("NaN".into()), "numeric"),
+            "'NaN'::numeric"
+        );
+        assert_eq!(cell_to_sql(&CellValue::Null, "date"), "NULL");
+    }
+
     #[test]
     fn test_sql_single_quote_escaping() {
         assert_eq!(
-            cell_to_sql(&CellValue::Text("O'Brien".to_string())),
+            cell_to_sql(&CellValue::Text("O'Brien".to_string()), "text"),
             "'O''Brien'"
         );
     }
@@ -253,5 +642,16 @@ mod tests {
             cell_to_json(&CellValue::Bool(true)),
             serde_json::json!(true)
         );
+        let numeric = cell_to_json(&CellValue::Numeric("12345678901234567890.01".into()));
+        assert!(numeric.is_number());
+        assert_eq!(numeric.to_string(), "12345678901234567890.01");
+        assert_eq!(
+            cell_to_json(&CellValue::Numeric("12.50".into())).to_string(),
+            "12.50"
+        );
+        assert_eq!(
+            cell_to_json(&CellValue::Numeric("NaN".into())),
+            serde_json::json!("NaN")
+        );
     }
 }
@@ -0,0 +1,188 @@
+//! Non-interactive mode: run SQL against a saved connection and write the
+//! result in one of the export formats, without starting the TUI.
+
+use std::fmt;
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::AtomicUsize;
+
+use anyhow::anyhow;
+use clap::ValueEnum;
+
+use crate::audit::{self, AuditRecord};
+use crate::config::{ExportSettings, Settings};
+use crate::db::{
+    create_client, execute_query, ConnectionConfig, ErrorCategory, QueryResult, StructuredError,
+};
+use crate::editor::statement_texts;
+use crate::export;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    Csv,
+    Json,
+    Jsonl,
+    Sql,
+    Tsv,
+}
+
+impl OutputFormat {
+    pub fn render(self, result: &QueryResult, settings: &ExportSettings) -> String {
+        match self {
+            OutputFormat::Csv => export::to_csv_with(result, &settings.csv),
+            OutputFormat::Json => export::to_json(result),
+            OutputFormat::Jsonl => export::to_ndjson(result),
+            OutputFormat::Sql => {
+                export::to_sql_insert(result, None, "results", settings.sql_type_comments)
+            }
+            OutputFormat::Tsv => export::to_tsv(result),
+        }
+    }
+}
+
+/// Why a headless run failed. Each kind maps to its own exit code so scripts
+/// can tell a bad connection from a bad query.
+#[derive(Debug)]
+pub enum Failure {
+    /// Could not reach or authenticate with the server.
+    Connect(anyhow::Error),
+    /// A statement failed on the server.
+    Query(Box<StructuredError>),
+    /// Anything else, e.g. no SQL given or the output file can't be written.
+    Other(anyhow::Error),
+}
+
+impl Failure {
+    /// 1 for general failures (2 is taken by argument errors), 3 for
+    /// connection problems, then 4-7 for syntax, semantic, execution and
+    /// transaction errors.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Failure::Other(_) => 1,
+            Failure::Connect(_) => 3,
+            Failure::Query(err) => match err.category {
+                ErrorCategory::Connection => 3,
+                ErrorCategory::Syntax => 4,
+                ErrorCategory::Semantic => 5,
+                ErrorCategory::Execution => 6,
+                ErrorCategory::Transaction => 7,
+                ErrorCategory::Unknown => 1,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::Connect(e) => write!(f, "Connection failed: {:#}", e),
+            Failure::Query(err) => write!(f, "{}", err.display_full()),
+            Failure::Other(e) => write!(f, "Error: {:#}", e),
+        }
+    }
+}
+
+/// Connect and run each statement in `sql`, writing the results one after
+/// another to `output` (gzip-compressed when it ends in `.gz`) or streaming
+/// them to stdout. Stops at the first failing statement. Each statement is
+/// recorded in the audit log when one is configured.
+pub async fn run(
+    config: &ConnectionConfig,
+    sql: &str,
+    format: OutputFormat,
+    output: Option<&Path>,
+    settings: &Settings,
+) -> Result<(), Failure> {
+    let audit_path = settings.audit.log_path(config);
+    let statements = statement_texts(sql);
+    if statements.is_empty() {
+        return Err(Failure::Other(anyhow!("No SQL to execute")));
+    }
+
+    // Server notices go to stderr so they never mix with the results
+    let (notice_tx, mut notice_rx) = tokio::sync::mpsc::unbounded_channel();
+    let client = create_client(config, Some(notice_tx))
+        .await
+        .map_err(Failure::Connect)?;
+    let mut content = String::new();
+    for statement in statements {
+        let result = execute_query(&client, statement, &AtomicUsize::new(0)).await;
+        while let Ok(notice) = notice_rx.try_recv() {
+            eprintln!("{}", notice);
+        }
+        let result = result.map_err(Failure::Other)?;
+        if let Some(path) = &audit_path {
+            audit::append(path, &AuditRecord::new(config, statement, &result)).map_err(|e| {
+                Failure::Other(
+                    anyhow!(e).context(format!("Could not write audit log {}", path.display())),
+                )
+            })?;
+        }
+        if let Some(err) = result.error {
+            return Err(Failure::Query(Box::new(err)));
+        }
+
+        let rendered = match result.affected_rows {
+            Some(affected) if result.columns.is_empty() => {
+                format!("{} rows affected\n", affected)
+            }
+            _ => format.render(&result, &settings.export),
+        };
+        match output {
+            Some(_) => content.push_str(&rendered),
+            None => {
+                let mut stdout = std::io::stdout();
+                stdout
+                    .write_all(rendered.as_bytes())
+                    .and_then(|()| stdout.flush())
+                    .map_err(|e| Failure::Other(e.into()))?;
+            }
+        }
+    }
+
+    if let Some(path) = output {
+        let compress = path.extension().is_some_and(|ext| ext == "gz");
+        export::write_export(path, &content, compress).map_err(|e| {
+            Failure::Other(anyhow!(e).context(format!("Could not write {}", path.display())))
+        })?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::QueryResult;
+
+    #[test]
+    fn test_failure_exit_codes() {
+        let query_error = |category| {
+            let mut err = StructuredError::from_string("boom".into());
+            err.category = category;
+            Failure::Query(Box::new(err))
+        };
+        assert_eq!(query_error(ErrorCategory::Syntax).exit_code(), 4);
+        assert_eq!(query_error(ErrorCategory::Semantic).exit_code(), 5);
+        assert_eq!(query_error(ErrorCategory::Connection).exit_code(), 3);
+        assert_eq!(Failure::Connect(anyhow!("refused")).exit_code(), 3);
+        assert_eq!(Failure::Other(anyhow!("no sql")).exit_code(), 1);
+        assert_eq!(
+            Failure::Connect(anyhow!("refused")).to_string(),
+            "Connection failed: refused"
+        );
+    }
+
+    #[test]
+    fn test_render_formats() {
+        let result = QueryResult::from_text_rows(&["id"], vec![vec!["1".into()]]);
+        let settings = ExportSettings::default();
+        assert_eq!(OutputFormat::Csv.render(&result, &settings), "id\n1\n");
+        assert_eq!(
+            OutputFormat::Jsonl.render(&result, &settings),
+            "{\"id\":\"1\"}\n"
+        );
+        assert!(OutputFormat::Sql
+            .render(&result, &settings)
+            .contains("INSERT INTO results (id) VALUES"));
+    }
+}
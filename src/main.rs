@@ -1,11 +1,17 @@
 pub mod ast;
+mod audit;
+mod config;
 mod db;
 mod editor;
 mod explain;
 mod export;
+mod headless;
+mod meta;
 mod ui;
 
-use crate::db::ConnectionManager;
+use crate::config::Settings;
+use crate::db::{pgpass_password, ConnectionManager};
+use crate::headless::{Failure, OutputFormat};
 use crate::ui::App;
 use anyhow::Result;
 use clap::Parser;
@@ -17,7 +23,8 @@ use crossterm::{
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
-use std::io;
+use std::io::{self, IsTerminal, Read};
+use std::path::PathBuf;
 
 /// A beautiful TUI SQL editor for PostgreSQL
 #[derive(Parser)]
@@ -26,6 +33,20 @@ struct Cli {
     /// Auto-connect to a saved connection by name
     #[arg(long = "connect")]
     connect: Option<String>,
+
+    /// Run this SQL and print the result instead of starting the TUI
+    /// (requires --connect). SQL piped to stdin is run the same way.
+    #[arg(short = 'e', long = "execute", requires = "connect")]
+    execute: Option<String>,
+
+    /// Output format for --execute
+    #[arg(long = "format", value_enum, default_value = "csv")]
+    format: OutputFormat,
+
+    /// Write --execute output to this file instead of stdout (gzipped if it
+    /// ends in .gz)
+    #[arg(short = 'o', long = "output")]
+    output: Option<PathBuf>,
 }
 
 #[tokio::main]
@@ -54,11 +75,12 @@ async fn main() -> Result<()> {
             }
         };
 
-        // Resolve password: PGPASSWORD env var, then interactive prompt
+        // Resolve password: PGPASSWORD env var, then ~/.pgpass (read when
+        // connecting), then interactive prompt
         if config.password.is_empty() {
             if let Ok(pw) = std::env::var("PGPASSWORD") {
                 config.password = pw;
-            } else {
+            } else if pgpass_password(&config).is_none() {
                 let prompt = format!("Password for {}: ", config.display_string());
                 config.password = rpassword::read_password_from_tty(Some(&prompt))?;
             }
@@ -69,6 +91,38 @@ async fn main() -> Result<()> {
         None
     };
 
+    // Headless mode: run --execute or piped SQL and exit without touching
+    // the terminal
+    let sql = match &cli.execute {
+        Some(sql) => Some(sql.clone()),
+        None if auto_connect_config.is_some() && !io::stdin().is_terminal() => {
+            let mut sql = String::new();
+            io::stdin().read_to_string(&mut sql)?;
+            Some(sql)
+        }
+        None => None,
+    };
+    if let (Some(sql), Some(config)) = (sql, &auto_connect_config) {
+        // A broken config.toml must not silently drop settings like [audit]
+        let result = match Settings::load() {
+            Ok(settings) => {
+                headless::run(config, &sql, cli.format, cli.output.as_deref(), &settings).await
+            }
+            Err(e) => Err(Failure::Other(e.context(format!(
+                "Could not load {}",
+                Settings::config_path().display()
+            )))),
+        };
+        let code = match result {
+            Ok(()) => 0,
+            Err(failure) => {
+                eprintln!("{}", failure);
+                failure.exit_code()
+            }
+        };
+        std::process::exit(code);
+    }
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -86,6 +140,7 @@ async fn main() -> Result<()> {
 
     // Run the app
     let res = run_app(&mut terminal, &mut app).await;
+    let _ = app.save_prefs();
 
     // Restore terminal
     disable_raw_mode()?;
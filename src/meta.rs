@@ -0,0 +1,473 @@
+//! psql-style backslash meta-commands (`\d`, `\dt`, `\l`, `\dn`, `\c`), handled
+//! client-side instead of being sent to the server, plus pgrsql's `\grep`.
+
+/// Tables with more rows than this (by planner estimate) are skipped by
+/// `\grep` unless it is run as `\grep!`.
+pub const GREP_LARGE_TABLE_ROWS: i64 = 1_000_000;
+
+/// Matching values `\grep` returns per table.
+pub const GREP_MATCHES_PER_TABLE: usize = 20;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetaCommand {
+    /// `\d name`: open the table inspector for `[schema.]name`.
+    Describe {
+        schema: Option<String>,
+        table: String,
+    },
+    /// `\dt [pattern]` or a bare `\d`.
+    ListTables { pattern: Option<String> },
+    /// `\l`
+    ListDatabases,
+    /// `\dn`
+    ListSchemas,
+    /// `\c dbname`: switch the active database.
+    Connect { database: String },
+    /// `\grep text [pattern]`: search the text columns of the tables
+    /// matching `pattern` (as for `\dt`) for values containing `needle`.
+    /// `\grep!` also scans tables over `GREP_LARGE_TABLE_ROWS`.
+    Grep {
+        needle: String,
+        pattern: Option<String>,
+        include_large: bool,
+    },
+}
+
+pub fn is_meta_command(input: &str) -> bool {
+    input.trim_start().starts_with('\\')
+}
+
+/// Parse a meta-command line. Returns an error message for unknown commands
+/// or malformed arguments.
+pub fn parse_meta_command(input: &str) -> Result<MetaCommand, String> {
+    let input = input.trim().trim_end_matches(';');
+    if let Some(command) = parse_grep(input) {
+        return command;
+    }
+    let words = split_words(input)?;
+    let command = words.first().map(String::as_str).unwrap_or_default();
+    if words.len() > 2 {
+        return Err(format!("Too many arguments to {}", command));
+    }
+    let arg = words.get(1).map(String::as_str);
+
+    match command {
+        "\\d" | "\\d+" => match arg {
+            Some(name) => {
+                let (schema, table) = split_qualified(name);
+                Ok(MetaCommand::Describe { schema, table })
+            }
+            None => Ok(MetaCommand::ListTables { pattern: None }),
+        },
+        "\\dt" | "\\dt+" => Ok(MetaCommand::ListTables {
+            pattern: arg.map(str::to_string),
+        }),
+        "\\l" | "\\l+" | "\\list" => Ok(MetaCommand::ListDatabases),
+        "\\dn" | "\\dn+" => Ok(MetaCommand::ListSchemas),
+        "\\c" | "\\connect" => match arg {
+            Some(name) => Ok(MetaCommand::Connect {
+                database: unquote(name),
+            }),
+            None => Err(format!("{} needs a database name", command)),
+        },
+        _ => Err(format!(
+            "Unsupported meta-command {} (try \\d, \\dt, \\l, \\dn, \\c or \\grep)",
+            command
+        )),
+    }
+}
+
+/// `\grep text [pattern]`, where `text` may be single-quoted to include
+/// spaces. `None` when `input` is another command.
+fn parse_grep(input: &str) -> Option<Result<MetaCommand, String>> {
+    let (command, rest) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+    let include_large = match command {
+        "\\grep" => false,
+        "\\grep!" => true,
+        _ => return None,
+    };
+    let rest = rest.trim_start();
+    let (needle, rest) = match rest.strip_prefix('\'') {
+        Some(quoted) => {
+            // '' inside the quotes is a literal quote
+            let mut needle = String::new();
+            let mut chars = quoted.char_indices().peekable();
+            let mut end = None;
+            while let Some((i, c)) = chars.next() {
+                if c == '\'' {
+                    if chars.peek().is_some_and(|&(_, next)| next == '\'') {
+                        chars.next();
+                    } else {
+                        end = Some(i + 1);
+                        break;
+                    }
+                }
+                needle.push(c);
+            }
+            match end {
+                Some(end) => (needle, &quoted[end..]),
+                None => return Some(Err("Unterminated quoted string".to_string())),
+            }
+        }
+        None => {
+            let (needle, rest) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            (needle.to_string(), rest)
+        }
+    };
+    if needle.is_empty() {
+        return Some(Err(format!("{} needs text to search for", command)));
+    }
+    let mut rest = rest.split_whitespace();
+    let pattern = rest.next().map(str::to_string);
+    if rest.next().is_some() {
+        return Some(Err(format!("Too many arguments to {}", command)));
+    }
+    Some(Ok(MetaCommand::Grep {
+        needle,
+        pattern,
+        include_large,
+    }))
+}
+
+/// Split a command line on whitespace outside double quotes, as psql does,
+/// so `\c "My DB"` has one argument. Quotes are kept for `unquote`.
+fn split_words(input: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quoted = false;
+    for c in input.chars() {
+        if c == '"' {
+            // A doubled quote inside quotes closes and reopens them
+            quoted = !quoted;
+        } else if c.is_whitespace() && !quoted {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        word.push(c);
+    }
+    if quoted {
+        return Err("Unterminated quoted identifier".to_string());
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+fn split_qualified(name: &str) -> (Option<String>, String) {
+    // The first dot outside double quotes separates schema and name
+    let mut quoted = false;
+    let dot = name.char_indices().find_map(|(i, c)| {
+        if c == '"' {
+            quoted = !quoted;
+        }
+        (c == '.' && !quoted).then_some(i)
+    });
+    match dot {
+        Some(i) => (Some(unquote(&name[..i])), unquote(&name[i + 1..])),
+        None => (None, unquote(name)),
+    }
+}
+
+fn unquote(ident: &str) -> String {
+    ident
+        .strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .map(|s| s.replace("\"\"", "\""))
+        .unwrap_or_else(|| ident.to_string())
+}
+
+/// `WHERE` condition on `table_schema`/`table_name` selecting the tables a
+/// `\dt`-style pattern names: all user tables without one, tables on the
+/// search_path for a bare name.
+fn table_filter(pattern: Option<&str>) -> String {
+    match pattern.map(split_qualified) {
+        Some((Some(schema), table)) => format!(
+            "table_schema LIKE {} AND table_name LIKE {}",
+            like_literal(&schema),
+            like_literal(&table)
+        ),
+        Some((None, table)) => format!(
+            "table_schema = ANY (current_schemas(false)) AND table_name LIKE {}",
+            like_literal(&table)
+        ),
+        None => "table_schema NOT IN ('pg_catalog', 'information_schema')".to_string(),
+    }
+}
+
+/// Catalog query listing the base tables `\grep` would search: schema,
+/// name, text-like columns (`text[]`) and the planner's row estimate.
+pub fn grep_tables_query(pattern: Option<&str>) -> String {
+    format!(
+        "SELECT table_schema::text, table_name::text, \
+         array_agg(column_name::text ORDER BY ordinal_position), \
+         (SELECT reltuples::bigint FROM pg_class \
+          WHERE oid = format('%I.%I', table_schema, table_name)::regclass) \
+         FROM information_schema.columns \
+         JOIN information_schema.tables USING (table_schema, table_name) \
+         WHERE table_type = 'BASE TABLE' \
+         AND udt_name IN ('text', 'varchar', 'bpchar', 'citext', 'name', 'json', 'jsonb', 'uuid') \
+         AND {} GROUP BY 1, 2 ORDER BY 1, 2",
+        table_filter(pattern)
+    )
+}
+
+/// A table `\grep` searches and its text-like columns.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GrepTable {
+    pub schema: String,
+    pub name: String,
+    pub columns: Vec<String>,
+}
+
+/// One query finding up to `GREP_MATCHES_PER_TABLE` values containing
+/// `needle` (case-insensitively) in each of `tables`. Each match is a row of
+/// table, column, value and the whole row as JSON, grouped by table.
+pub fn grep_query(needle: &str, tables: &[GrepTable]) -> Option<String> {
+    if tables.is_empty() {
+        return None;
+    }
+    let literal = |text: &str| format!("'{}'", text.replace('\'', "''"));
+    let contains: String = needle
+        .chars()
+        .map(|c| match c {
+            '%' | '_' | '\\' => format!("\\{}", c),
+            c => c.to_string(),
+        })
+        .collect();
+    let contains = literal(&format!("%{}%", contains));
+
+    let parts: Vec<String> = tables
+        .iter()
+        .map(|table| {
+            let qualified = format!(
+                "{}.{}",
+                crate::db::quote_ident(&table.schema),
+                crate::db::quote_ident(&table.name)
+            );
+            let values: Vec<String> = table
+                .columns
+                .iter()
+                .map(|column| {
+                    format!(
+                        "({}, t.{}::text)",
+                        literal(column),
+                        crate::db::quote_ident(column)
+                    )
+                })
+                .collect();
+            format!(
+                "(SELECT {} AS \"Table\", m.col AS \"Column\", m.val AS \"Value\", \
+                 row_to_json(t)::text AS \"Row\" FROM {} t \
+                 CROSS JOIN LATERAL (VALUES {}) AS m(col, val) \
+                 WHERE m.val ILIKE {} LIMIT {})",
+                literal(&format!("{}.{}", table.schema, table.name)),
+                qualified,
+                values.join(", "),
+                contains,
+                GREP_MATCHES_PER_TABLE
+            )
+        })
+        .collect();
+    Some(parts.join("\nUNION ALL\n"))
+}
+
+/// Turn a psql pattern (`*` and `?` wildcards) into an escaped LIKE literal.
+fn like_literal(pattern: &str) -> String {
+    let like: String = pattern
+        .chars()
+        .map(|c| match c {
+            '*' => "%".to_string(),
+            '?' => "_".to_string(),
+            '%' | '_' => format!("\\{}", c),
+            '\'' => "''".to_string(),
+            c => c.to_string(),
+        })
+        .collect();
+    format!("'{}'", like)
+}
+
+impl MetaCommand {
+    /// The catalog query that answers this command, or `None` for commands
+    /// handled in the UI (`\d name`, `\c`).
+    pub fn catalog_query(&self) -> Option<String> {
+        match self {
+            MetaCommand::Describe { .. }
+            | MetaCommand::Connect { .. }
+            | MetaCommand::Grep { .. } => None,
+            MetaCommand::ListTables { pattern } => Some(format!(
+                "SELECT table_schema AS \"Schema\", table_name AS \"Name\", \
+                 table_type AS \"Type\" FROM information_schema.tables \
+                 WHERE {} ORDER BY 1, 2",
+                table_filter(pattern.as_deref())
+            )),
+            MetaCommand::ListDatabases => Some(
+                "SELECT datname AS \"Name\", pg_get_userbyid(datdba) AS \"Owner\", \
+                 pg_encoding_to_char(encoding) AS \"Encoding\" FROM pg_database \
+                 WHERE NOT datistemplate ORDER BY 1"
+                    .to_string(),
+            ),
+            MetaCommand::ListSchemas => Some(
+                "SELECT nspname AS \"Name\", pg_get_userbyid(nspowner) AS \"Owner\" \
+                 FROM pg_namespace WHERE nspname !~ '^pg_' \
+                 AND nspname <> 'information_schema' ORDER BY 1"
+                    .to_string(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_meta_command() {
+        assert!(is_meta_command("\\d users"));
+        assert!(is_meta_command("  \\l"));
+        assert!(!is_meta_command("SELECT '\\d'"));
+    }
+
+    #[test]
+    fn test_parse_describe() {
+        assert_eq!(
+            parse_meta_command("\\d users"),
+            Ok(MetaCommand::Describe {
+                schema: None,
+                table: "users".into()
+            })
+        );
+        assert_eq!(
+            parse_meta_command("\\d+ audit.\"Events\";"),
+            Ok(MetaCommand::Describe {
+                schema: Some("audit".into()),
+                table: "Events".into()
+            })
+        );
+        assert_eq!(
+            parse_meta_command("\\d"),
+            Ok(MetaCommand::ListTables { pattern: None })
+        );
+        assert_eq!(
+            parse_meta_command("\\d \"Sales 2024\".\"Q1.totals\""),
+            Ok(MetaCommand::Describe {
+                schema: Some("Sales 2024".into()),
+                table: "Q1.totals".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_listings() {
+        assert_eq!(parse_meta_command("\\l"), Ok(MetaCommand::ListDatabases));
+        assert_eq!(parse_meta_command("\\dn"), Ok(MetaCommand::ListSchemas));
+        assert_eq!(
+            parse_meta_command("\\dt user*"),
+            Ok(MetaCommand::ListTables {
+                pattern: Some("user*".into())
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_connect() {
+        assert_eq!(
+            parse_meta_command("\\c analytics"),
+            Ok(MetaCommand::Connect {
+                database: "analytics".into()
+            })
+        );
+        assert_eq!(
+            parse_meta_command("\\connect \"My DB\""),
+            Ok(MetaCommand::Connect {
+                database: "My DB".into()
+            })
+        );
+        assert_eq!(
+            parse_meta_command("\\connect \"Reports\""),
+            Ok(MetaCommand::Connect {
+                database: "Reports".into()
+            })
+        );
+        assert!(parse_meta_command("\\c").is_err());
+        assert!(parse_meta_command("\\c \"My DB").is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_meta_command("\\x").is_err());
+        assert!(parse_meta_command("\\d a b").is_err());
+    }
+
+    #[test]
+    fn test_catalog_query_pattern() {
+        let sql = MetaCommand::ListTables {
+            pattern: Some("public.user_*".into()),
+        }
+        .catalog_query()
+        .unwrap();
+        assert!(sql.contains("table_schema LIKE 'public'"));
+        assert!(sql.contains("table_name LIKE 'user\\_%'"));
+        assert!(MetaCommand::Describe {
+            schema: None,
+            table: "t".into()
+        }
+        .catalog_query()
+        .is_none());
+    }
+
+    #[test]
+    fn test_parse_grep() {
+        assert_eq!(
+            parse_meta_command("\\grep alice@example.com"),
+            Ok(MetaCommand::Grep {
+                needle: "alice@example.com".into(),
+                pattern: None,
+                include_large: false
+            })
+        );
+        assert_eq!(
+            parse_meta_command("\\grep! 'O''Brien st' audit.*;"),
+            Ok(MetaCommand::Grep {
+                needle: "O'Brien st".into(),
+                pattern: Some("audit.*".into()),
+                include_large: true
+            })
+        );
+        assert!(parse_meta_command("\\grep").is_err());
+        assert!(parse_meta_command("\\grep 'open").is_err());
+        assert!(parse_meta_command("\\grep a b c").is_err());
+    }
+
+    #[test]
+    fn test_grep_query() {
+        let tables = vec![
+            GrepTable {
+                schema: "public".into(),
+                name: "users".into(),
+                columns: vec!["email".into(), "Notes".into()],
+            },
+            GrepTable {
+                schema: "audit".into(),
+                name: "events".into(),
+                columns: vec!["payload".into()],
+            },
+        ];
+        let sql = grep_query("50%_o'k", &tables).unwrap();
+        assert!(sql.contains("FROM public.users t"));
+        assert!(sql.contains("('email', t.email::text), ('Notes', t.\"Notes\"::text)"));
+        assert!(sql.contains("ILIKE '%50\\%\\_o''k%'"));
+        assert!(sql.contains("'audit.events' AS \"Table\""));
+        assert_eq!(sql.matches("UNION ALL").count(), 1);
+        assert!(grep_query("x", &[]).is_none());
+    }
+
+    #[test]
+    fn test_grep_tables_query_uses_pattern() {
+        let sql = grep_tables_query(Some("public.user*"));
+        assert!(sql.contains("table_schema LIKE 'public' AND table_name LIKE 'user%'"));
+        assert!(sql.contains("BASE TABLE"));
+    }
+}
@@ -1,20 +1,56 @@
 use anyhow::Result;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::task::JoinHandle;
-use tokio_postgres::Client;
+use tokio_postgres::{Client, SimpleQueryMessage};
 
+use crate::ast::{
+    analyze_query, destructive_statement, is_read_only_query, parse_single, parse_sql,
+    select_window, single_source_table, with_select_window, without_select_order,
+    without_select_window, DestructiveStatement, Query, WarningSeverity,
+};
+use crate::audit::AuditRecord;
+use crate::config::{Settings, TableEnterAction, UiPrefs};
 use crate::db::{
-    create_client, execute_query, get_columns, get_databases, get_indexes, get_schemas,
-    get_table_ddl, get_tables, ColumnDetails, ConnectionConfig, ConnectionManager, DatabaseInfo,
-    IndexInfo, QueryResult, SchemaInfo, SslMode, TableInfo,
+    create_client, diff_results, execute_query, format_bytes, format_row_count, get_columns,
+    get_databases, get_indexes, get_schema_ddl, get_schemas, get_table_comment, get_table_ddl,
+    get_tables, quote_ident, test_connection, CellChange, CellValue, ColumnDetails,
+    ConnectionConfig, ConnectionManager, DatabaseInfo, DisplayOptions, Environment, ErrorCategory,
+    IndexInfo, Notice, QueryResult, RowDiff, SchemaInfo, SslMode, StructuredError, TableInfo,
+};
+use crate::editor::{
+    argument_ranges, controls_transaction, enclosing_call, function_signature, opens_transaction,
+    split_statements, statement_texts, FunctionSignature, HistoryEntry, QueryHistory, TextBuffer,
+    VimOutcome, VimState,
+};
+use crate::explain::{format_duration_ms, is_explain_query, parse_explain_output, QueryPlan};
+use crate::meta::{
+    grep_query, grep_tables_query, is_meta_command, parse_meta_command, GrepTable, MetaCommand,
+    GREP_LARGE_TABLE_ROWS,
+};
+use crate::ui::{
+    compile_format_rules, filter_actions, is_sql_keyword, CellRule, ColumnView, KeyContext, Keymap,
+    PaletteAction, Theme, SQL_KEYWORDS, SQL_TYPES,
 };
-use crate::editor::{HistoryEntry, QueryHistory, TextBuffer};
-use crate::explain::{is_explain_query, parse_explain_output, QueryPlan};
-use crate::ui::{Theme, SQL_KEYWORDS, SQL_TYPES};
 
 pub const SPINNER_FRAMES: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
 
+/// LIMIT used when paging a SELECT that has none.
+const DEFAULT_PAGE_SIZE: i64 = 100;
+
+/// A LIMITed result is flagged as a slice when the planner expects this many
+/// times more rows without the LIMIT.
+const ESTIMATE_NOTE_FACTOR: u64 = 10;
+
+/// How long the editor lines of a just-executed statement stay highlighted.
+const EXECUTE_FLASH: Duration = Duration::from_millis(600);
+
+/// How often the status bar latency is refreshed with a `SELECT 1`.
+const PING_INTERVAL: Duration = Duration::from_secs(5);
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Focus {
     Sidebar,
@@ -24,6 +60,218 @@ pub enum Focus {
     Help,
     TableInspector,
     ExportPicker,
+    AstTree,
+    DestructiveConfirm,
+    ResultCompare,
+    CellDetail,
+    CommandPalette,
+    GoToLine,
+    Find,
+}
+
+/// Fuzzy-filtered list of actions opened with Ctrl+P.
+#[derive(Debug, Clone)]
+pub struct CommandPaletteState {
+    pub query: String,
+    pub matches: Vec<PaletteAction>,
+    pub selected: usize,
+    /// Pane to return to; actions run as if triggered from it.
+    pub return_focus: Focus,
+}
+
+/// Incremental search in the editor (Ctrl+F).
+#[derive(Debug, Clone)]
+pub struct FindState {
+    pub query: String,
+    /// Positions `(x, y)` of every match of `query`.
+    pub matches: Vec<(usize, usize)>,
+    /// Index into `matches` of the match under the cursor.
+    pub current: usize,
+    /// Cursor position when the search opened; matches are looked up from here.
+    origin: (usize, usize),
+}
+
+/// Parsed AST of the query at the cursor, rendered as a tree.
+#[derive(Debug, Clone)]
+pub struct AstTreeState {
+    pub lines: Vec<String>,
+    pub scroll: usize,
+}
+
+/// Full value of the selected result cell (Enter in Results).
+#[derive(Debug, Clone)]
+pub struct CellDetailState {
+    pub column: String,
+    pub type_name: String,
+    pub text: String,
+    /// Pretty-printed value when the cell holds JSON, even as `text`.
+    pub json: Option<String>,
+    pub show_json: bool,
+    pub scroll: usize,
+}
+
+impl CellDetailState {
+    /// The text currently shown: pretty JSON or the raw value.
+    pub fn shown(&self) -> &str {
+        match &self.json {
+            Some(json) if self.show_json => json,
+            _ => &self.text,
+        }
+    }
+}
+
+/// Selection and scroll of a result, restored when cycling back to it.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ResultPosition {
+    pub row: usize,
+    pub col: usize,
+    pub scroll_y: usize,
+    pub plan_scroll: usize,
+}
+
+/// A statement's result and what the results view keeps alongside it.
+#[derive(Debug, Clone)]
+pub struct ResultEntry {
+    pub result: QueryResult,
+    /// The SQL that produced it.
+    pub query: String,
+    pub plan: Option<QueryPlan>,
+    /// The `(schema, table)` its rows were selected from, when the query
+    /// read a single table. Used as the target of SQL INSERT exports.
+    pub source_table: Option<(Option<String>, String)>,
+    /// Server notices raised while the query ran.
+    pub notices: Vec<Notice>,
+    /// The planner's row estimate for its SELECT without LIMIT/OFFSET,
+    /// looked up when the fetched rows filled the LIMIT.
+    pub row_estimate: Option<u64>,
+    /// Approximate bytes fetched (`QueryResult::approx_bytes`).
+    pub size: usize,
+    /// The columns hidden from view, and their order.
+    pub column_view: ColumnView,
+    /// Where the cursor was when another result was shown.
+    pub position: ResultPosition,
+    /// How each cell differs from the previous run of the same query, when
+    /// both results have the same shape.
+    pub changes: Option<Vec<Vec<CellChange>>>,
+}
+
+impl ResultEntry {
+    fn new(query: String, result: QueryResult) -> Self {
+        Self {
+            size: result.approx_bytes(),
+            result,
+            query,
+            plan: None,
+            source_table: None,
+            notices: Vec::new(),
+            row_estimate: None,
+            column_view: ColumnView::default(),
+            position: ResultPosition::default(),
+            changes: None,
+        }
+    }
+}
+
+/// Differences between the current result and a pinned one.
+#[derive(Debug, Clone)]
+pub struct ResultCompareState {
+    /// Index into `App::pinned`.
+    pub pinned_index: usize,
+    /// Column rows are matched on for the row diff.
+    pub key_col: usize,
+    pub lines: Vec<String>,
+    pub scroll: usize,
+}
+
+/// A query executing on a background task.
+pub struct PendingQuery {
+    pub query: String,
+    /// Retry number of this run; 0 for the first attempt.
+    pub attempt: u32,
+    /// Rows streamed in so far, shared with the task.
+    pub rows_received: Arc<AtomicUsize>,
+    /// When this attempt was started.
+    pub started: Instant,
+    pub handle: JoinHandle<Result<QueryResult>>,
+}
+
+/// A replacement session being opened on a background task after the old
+/// one was lost.
+pub struct PendingReconnect {
+    pub handle: JoinHandle<Result<Client>>,
+    /// A failed read-only query to run again once reconnected: its text,
+    /// retry number and the failure to report if reconnecting fails.
+    pub retry: Option<(String, u32, QueryResult)>,
+}
+
+/// A row estimate running for a LIMITed result.
+pub struct PendingEstimate {
+    /// Index of the result in `results`.
+    pub index: usize,
+    /// The EXPLAIN sent, kept for the audit log.
+    pub explain: String,
+    pub started: Instant,
+    pub handle: JoinHandle<Result<Option<u64>>>,
+}
+
+/// What the editor title says about the statement at the cursor, worked out
+/// once per edit or move to another statement rather than every frame.
+#[derive(Debug, Default)]
+pub struct StatementHints {
+    /// Buffer revision and statement line range the hints were made for.
+    key: Option<(u64, Option<(usize, usize)>)>,
+    /// The statement parses but only as `Query::Raw`.
+    pub is_raw: bool,
+    /// How long it took before, e.g. "last run 2.30s, avg 1.95s over 4 runs".
+    pub runtime: Option<String>,
+}
+
+/// A row of the sidebar's Tables tree.
+pub enum TreeRow<'a> {
+    /// A schema and whether it is expanded.
+    Schema(&'a SchemaInfo, bool),
+    Table(&'a TableInfo),
+}
+
+/// Sidebar contents loaded on a background task by `refresh_schema`.
+pub struct SchemaLoad {
+    pub databases: Result<Vec<DatabaseInfo>>,
+    pub schemas: Result<Vec<SchemaInfo>>,
+    pub tables: Vec<TableInfo>,
+    /// Schemas whose tables were fetched.
+    pub loaded: Vec<String>,
+}
+
+/// An "execute all" run over every statement in the editor.
+pub struct BatchRun {
+    /// Statements not started yet, in order.
+    pub remaining: VecDeque<String>,
+    pub total: usize,
+    pub failed: usize,
+    /// Run inside one transaction that is committed only if every
+    /// statement succeeds, and rolled back at the first failure.
+    pub transactional: bool,
+}
+
+impl BatchRun {
+    /// 1-based number of the statement currently running.
+    pub fn current(&self) -> usize {
+        self.total - self.remaining.len()
+    }
+}
+
+/// A DROP/TRUNCATE held back until the user types the name of its target.
+#[derive(Debug, Clone)]
+pub struct DestructiveConfirmState {
+    pub query: String,
+    pub statement: DestructiveStatement,
+    pub input: String,
+}
+
+impl DestructiveConfirmState {
+    pub fn is_confirmed(&self) -> bool {
+        self.input.trim() == self.statement.target
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -32,6 +280,8 @@ pub struct TableInspectorState {
     pub schema_name: String,
     pub columns: Vec<ColumnDetails>,
     pub indexes: Vec<IndexInfo>,
+    /// `COMMENT ON TABLE` text, shown above the columns.
+    pub comment: Option<String>,
     pub ddl: String,
     pub show_ddl: bool,
     pub scroll: usize,
@@ -41,17 +291,21 @@ pub struct TableInspectorState {
 pub enum ExportFormat {
     Csv,
     Json,
+    Ndjson,
     SqlInsert,
     Tsv,
     ClipboardCsv,
+    ClipboardHtml,
 }
 
 pub const EXPORT_FORMATS: &[ExportFormat] = &[
     ExportFormat::Csv,
     ExportFormat::Json,
+    ExportFormat::Ndjson,
     ExportFormat::SqlInsert,
     ExportFormat::Tsv,
     ExportFormat::ClipboardCsv,
+    ExportFormat::ClipboardHtml,
 ];
 
 impl ExportFormat {
@@ -59,9 +313,11 @@ impl ExportFormat {
         match self {
             ExportFormat::Csv => "CSV (.csv)",
             ExportFormat::Json => "JSON (.json)",
+            ExportFormat::Ndjson => "JSON Lines (.jsonl)",
             ExportFormat::SqlInsert => "SQL INSERT (.sql)",
             ExportFormat::Tsv => "TSV (.tsv)",
             ExportFormat::ClipboardCsv => "Copy to clipboard (CSV)",
+            ExportFormat::ClipboardHtml => "Copy to clipboard (HTML table)",
         }
     }
 
@@ -69,9 +325,11 @@ impl ExportFormat {
         match self {
             ExportFormat::Csv => "csv",
             ExportFormat::Json => "json",
+            ExportFormat::Ndjson => "jsonl",
             ExportFormat::SqlInsert => "sql",
             ExportFormat::Tsv => "tsv",
             ExportFormat::ClipboardCsv => "csv",
+            ExportFormat::ClipboardHtml => "html",
         }
     }
 }
@@ -95,6 +353,11 @@ pub enum TreeNode {
 #[allow(dead_code)]
 pub struct App {
     pub theme: Theme,
+    pub settings: Settings,
+    pub keymap: Keymap,
+    /// Modal editing state; `None` when vim mode is off.
+    pub vim: Option<VimState>,
+    pub prefs: UiPrefs,
     pub focus: Focus,
     pub should_quit: bool,
 
@@ -112,22 +375,34 @@ pub struct App {
     pub sidebar_selected: usize,
     pub sidebar_scroll: usize,
     pub expanded_schemas: Vec<String>,
+    /// Schemas whose tables are in `tables`; others load on expansion.
+    pub loaded_schemas: Vec<String>,
     pub expanded_tables: Vec<String>,
 
     // Editor
     pub editor: TextBuffer,
     pub query_history: QueryHistory,
+    /// First and last editor line of the statement just sent to run, and
+    /// when; drawn with a background until `EXECUTE_FLASH` has passed.
+    pub executed_flash: Option<(usize, usize, Instant)>,
+    pub statement_hints: StatementHints,
 
     // Layout
     pub editor_height_percent: u16,
+    /// Rows of text the editor pane showed in the last draw.
+    pub editor_visible_rows: usize,
+    /// Rows the sidebar list showed in the last draw.
+    pub sidebar_visible_rows: usize,
 
     // Results
-    pub results: Vec<QueryResult>,
+    pub results: Vec<ResultEntry>,
     pub current_result: usize,
     pub result_scroll_x: usize,
     pub result_scroll_y: usize,
     pub result_selected_row: usize,
     pub result_selected_col: usize,
+    /// When the last query finished, shown in the status bar.
+    pub last_query_at: Option<chrono::DateTime<chrono::Local>>,
 
     // Toasts
     pub toasts: Vec<Toast>,
@@ -142,20 +417,73 @@ pub struct App {
 
     // Autocomplete
     pub autocomplete: AutocompleteState,
+    pub snippet: Option<SignatureSnippet>,
 
-    // EXPLAIN plan
-    pub explain_plans: Vec<Option<QueryPlan>>,
+    /// Conditional formatting rules from `config.toml`.
+    pub format_rules: Vec<CellRule>,
+    /// Index in `results` of the latest successful run of each query text.
+    last_result_by_query: HashMap<String, usize>,
     pub show_visual_plan: bool,
+    /// Scroll offset of the visual plan. The raw view keeps its own position
+    /// in `result_scroll_y`, so toggling with Ctrl+E preserves both.
     pub plan_scroll: usize,
 
+    // Offline mode: parse and analyze queries locally instead of executing
+    pub offline_mode: bool,
+
     // Table Inspector
     pub table_inspector: Option<TableInspectorState>,
 
+    // AST tree view
+    pub ast_tree: Option<AstTreeState>,
+
+    // Wrap the selected result row's cells instead of truncating them
+    pub wrap_selected_row: bool,
+    /// Show timestamp cells as "3 hours ago" instead of absolute values.
+    pub relative_time: bool,
+    /// Draw the current result as a bar chart of `chart_columns` instead of
+    /// a table, when it has a numeric column.
+    pub show_result_chart: bool,
+    /// Pop up autocomplete while typing, not only on Ctrl+Space.
+    pub autocomplete_while_typing: bool,
+
+    // Results pinned for later comparison
+    pub pinned: Vec<QueryResult>,
+    pub result_compare: Option<ResultCompareState>,
+    pub cell_detail: Option<CellDetailState>,
+    pub command_palette: Option<CommandPaletteState>,
+    /// Line number typed into the Ctrl+G prompt while it is open.
+    pub go_to_line: Option<String>,
+    pub find: Option<FindState>,
+
+    // Typed-name confirmation for DROP/TRUNCATE
+    pub destructive_confirm: Option<DestructiveConfirmState>,
+
     // Export
     pub export_selected: usize,
+    /// Gzip file exports (toggled with `z` in the export picker).
+    pub export_compress: bool,
 
     // Async connection task
     pub pending_connection: Option<(ConnectionConfig, JoinHandle<Result<Client>>)>,
+    /// Latency ping running in the background, polled by `tick`.
+    pub pending_ping: Option<JoinHandle<Result<Duration>>>,
+    /// Reconnect of a lost session, polled by `tick`.
+    pub pending_reconnect: Option<PendingReconnect>,
+    /// Sidebar reload running in the background.
+    pub pending_schema: Option<JoinHandle<SchemaLoad>>,
+    /// Exact row count of a sidebar table (`n`): its qualified name, start
+    /// time and task.
+    pub pending_count: Option<(String, Instant, JoinHandle<Result<i64>>)>,
+    /// Row estimate for a LIMITed result.
+    pub pending_estimate: Option<PendingEstimate>,
+    /// Connection test started from the dialog; never becomes the session.
+    pub pending_test: Option<JoinHandle<Result<String>>>,
+
+    // Async query task
+    pub pending_query: Option<PendingQuery>,
+    /// Execute-all run in progress; each finished query starts the next.
+    pub batch: Option<BatchRun>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -211,6 +539,8 @@ pub struct ConnectionDialogState {
     pub field_cursors: [usize; 6],
     pub saved_connections: Vec<ConnectionConfig>,
     pub selected_saved: Option<usize>,
+    /// Saved connection loaded into the fields, which Ctrl+S overwrites.
+    pub editing: Option<usize>,
     /// Inline status message shown inside the dialog
     pub status_message: Option<(String, StatusType)>,
 }
@@ -233,6 +563,7 @@ impl Default for ConnectionDialogState {
             field_cursors,
             saved_connections: Vec::new(),
             selected_saved: None,
+            editing: None,
             status_message: None,
         }
     }
@@ -252,6 +583,8 @@ pub enum SuggestionKind {
     Table,
     Column,
     Function,
+    /// A value seen in the current result, completing a string literal.
+    Value,
 }
 
 impl SuggestionKind {
@@ -262,10 +595,22 @@ impl SuggestionKind {
             SuggestionKind::Table => "TB",
             SuggestionKind::Column => "CL",
             SuggestionKind::Function => "FN",
+            SuggestionKind::Value => "VL",
         }
     }
 }
 
+/// Placeholder arguments of a function call inserted by autocomplete; Tab
+/// moves from one to the next.
+#[derive(Debug, Clone, Copy)]
+pub struct SignatureSnippet {
+    /// Line and byte position of the call's `(`.
+    pub y: usize,
+    pub open: usize,
+    /// Index of the selected argument.
+    pub current: usize,
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct AutocompleteState {
     pub active: bool,
@@ -274,6 +619,48 @@ pub struct AutocompleteState {
     pub prefix: String,
 }
 
+/// When `before` (the line up to the cursor) ends inside a string literal
+/// compared with `=`, LIKE or ILIKE, returns the column on the other side,
+/// whether the comparison is ILIKE, and the literal typed so far.
+fn value_completion_context(before: &str) -> Option<(&str, bool, &str)> {
+    // An odd number of quotes leaves the last one open
+    let open = before
+        .chars()
+        .filter(|&c| c == '\'')
+        .fold(false, |open, _| !open);
+    if !open {
+        return None;
+    }
+    let quote = before.rfind('\'')?;
+    let literal = &before[quote + 1..];
+    let lhs = before[..quote].trim_end();
+    let upper = lhs.to_ascii_uppercase();
+
+    let (lhs, ilike) = if let Some(rest) = lhs.strip_suffix('=') {
+        if rest.ends_with(['<', '>', '!']) {
+            return None;
+        }
+        (rest, false)
+    } else if upper.ends_with(" ILIKE") {
+        (&lhs[..lhs.len() - 6], true)
+    } else if upper.ends_with(" LIKE") {
+        (&lhs[..lhs.len() - 5], false)
+    } else {
+        return None;
+    };
+    let lhs = lhs.trim_end();
+    let lhs = match lhs.to_ascii_uppercase().ends_with(" NOT") {
+        true => lhs[..lhs.len() - 4].trim_end(),
+        false => lhs,
+    };
+
+    let column = lhs
+        .rsplit(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+        .next()?;
+    let column = column.rsplit('.').next()?;
+    (!column.is_empty()).then_some((column, ilike, literal))
+}
+
 pub const SQL_FUNCTIONS: &[&str] = &[
     "COUNT",
     "SUM",
@@ -338,6 +725,26 @@ pub const SQL_FUNCTIONS: &[&str] = &[
 impl App {
     pub fn new() -> Self {
         let query_history = QueryHistory::load().unwrap_or_default();
+        let (settings, settings_error) = match Settings::load() {
+            Ok(settings) => (settings, None),
+            Err(e) => (
+                Settings::default(),
+                Some(format!(
+                    "Could not load {}, using defaults: {:#}",
+                    Settings::config_path().display(),
+                    e
+                )),
+            ),
+        };
+        let prefs = UiPrefs::load().unwrap_or_default();
+        let (keymap, mut startup_errors) = Keymap::with_overrides(&settings.keybindings);
+        startup_errors.extend(settings_error);
+        let (format_rules, rule_errors) = compile_format_rules(&settings.format_rules);
+        startup_errors.extend(rule_errors);
+        let vim = settings.editor.vim_mode.then(VimState::new);
+        let mut editor = TextBuffer::new();
+        editor.tab_width = settings.editor.tab_width;
+        editor.use_hard_tabs = settings.editor.use_hard_tabs;
         let saved_connections = ConnectionManager::load_saved_connections().unwrap_or_default();
 
         // Try to auto-populate last used connection
@@ -349,7 +756,7 @@ impl App {
                     .enumerate()
                     .find(|(_, c)| &c.name == name)
                 {
-                    (conn.clone(), 5_usize, Some(idx)) // Focus on password field
+                    (conn.resume(), 5_usize, Some(idx)) // Focus on password field
                 } else {
                     (ConnectionConfig::default(), 0_usize, None)
                 }
@@ -367,7 +774,10 @@ impl App {
         ];
 
         Self {
-            theme: Theme::dark(),
+            theme: Theme::by_name(&prefs.theme).unwrap_or_default(),
+            settings,
+            keymap,
+            vim,
             focus: Focus::ConnectionDialog,
             should_quit: false,
 
@@ -379,11 +789,12 @@ impl App {
                 field_cursors,
                 saved_connections,
                 selected_saved: initial_selected_saved,
+                editing: initial_selected_saved,
                 status_message: None,
             },
 
             sidebar_tab: SidebarTab::Tables,
-            sidebar_width: 35,
+            sidebar_width: prefs.sidebar_width.clamp(15, 80),
             databases: Vec::new(),
             schemas: Vec::new(),
             tables: Vec::new(),
@@ -391,12 +802,17 @@ impl App {
             sidebar_selected: 0,
             sidebar_scroll: 0,
             expanded_schemas: vec!["public".to_string()],
+            loaded_schemas: Vec::new(),
             expanded_tables: Vec::new(),
 
-            editor: TextBuffer::new(),
+            editor,
             query_history,
+            executed_flash: None,
+            statement_hints: StatementHints::default(),
 
-            editor_height_percent: 40,
+            editor_height_percent: prefs.editor_height_percent.clamp(15, 85),
+            editor_visible_rows: 1,
+            sidebar_visible_rows: 1,
 
             results: Vec::new(),
             current_result: 0,
@@ -404,22 +820,103 @@ impl App {
             result_scroll_y: 0,
             result_selected_row: 0,
             result_selected_col: 0,
+            last_query_at: None,
 
-            toasts: Vec::new(),
+            toasts: startup_errors
+                .into_iter()
+                .map(|e| Toast::new(e, StatusType::Warning))
+                .collect(),
             is_loading: false,
             loading_message: String::new(),
             spinner_frame: 0,
             show_help: false,
             autocomplete: AutocompleteState::default(),
+            snippet: None,
 
-            explain_plans: Vec::new(),
+            format_rules,
+            last_result_by_query: HashMap::new(),
             show_visual_plan: true,
             plan_scroll: 0,
 
+            offline_mode: false,
+
             table_inspector: None,
+            ast_tree: None,
+            wrap_selected_row: prefs.wrap_selected_row,
+            relative_time: prefs.relative_time,
+            show_result_chart: false,
+            autocomplete_while_typing: prefs.autocomplete,
+            pinned: Vec::new(),
+            result_compare: None,
+            cell_detail: None,
+            command_palette: None,
+            go_to_line: None,
+            find: None,
+            destructive_confirm: None,
             export_selected: 0,
+            export_compress: false,
             pending_connection: None,
+            pending_reconnect: None,
+            pending_ping: None,
+            pending_test: None,
+            pending_schema: None,
+            pending_count: None,
+            pending_estimate: None,
+            pending_query: None,
+            batch: None,
+            prefs,
+        }
+    }
+
+    /// Save the dialog's fields as a connection: over the saved entry they
+    /// were loaded from (or one with the same name), else as a new entry.
+    /// The password is never written.
+    fn save_dialog_connection(&mut self) {
+        let dialog = &mut self.connection_dialog;
+        let name = dialog.config.name.trim().to_string();
+        if name.is_empty() {
+            dialog.status_message = Some((
+                "Name the connection to save it".to_string(),
+                StatusType::Warning,
+            ));
+            return;
+        }
+        let index = dialog
+            .editing
+            .or_else(|| dialog.saved_connections.iter().position(|c| c.name == name));
+        let clash = dialog
+            .saved_connections
+            .iter()
+            .enumerate()
+            .any(|(i, c)| c.name == name && Some(i) != index);
+        if clash {
+            dialog.status_message = Some((
+                format!("Another saved connection is named \"{}\"", name),
+                StatusType::Warning,
+            ));
+            return;
+        }
+
+        dialog.config.name = name.clone();
+        match index.filter(|&i| i < dialog.saved_connections.len()) {
+            Some(i) => {
+                dialog.saved_connections[i] =
+                    dialog.saved_connections[i].with_edits(&dialog.config);
+            }
+            None => {
+                dialog.saved_connections.push(dialog.config.clone());
+                dialog.editing = Some(dialog.saved_connections.len() - 1);
+            }
         }
+        dialog.status_message = Some(
+            match ConnectionManager::save_connections(&dialog.saved_connections) {
+                Ok(()) => (format!("Saved \"{}\"", name), StatusType::Success),
+                Err(e) => (
+                    format!("Could not save connections: {}", e),
+                    StatusType::Error,
+                ),
+            },
+        );
     }
 
     pub async fn try_auto_connect(&mut self, mut config: ConnectionConfig) {
@@ -457,7 +954,7 @@ impl App {
                     );
                 }
                 let _ = ConnectionManager::save_last_connection(&config.name);
-                let _ = self.refresh_schema().await;
+                self.refresh_schema();
                 self.set_status(
                     format!("Connected to {}", config.display_string()),
                     StatusType::Success,
@@ -476,10 +973,46 @@ impl App {
         }
     }
 
+    /// Record the current database and schema on the saved connection so the
+    /// next connect resumes there.
+    fn remember_location(&mut self) {
+        let name = &self.connection.config.name;
+        let Some(saved) = self
+            .connection_dialog
+            .saved_connections
+            .iter_mut()
+            .find(|c| &c.name == name)
+        else {
+            return;
+        };
+        saved.last_database = Some(self.connection.current_database.clone());
+        saved.last_schema = Some(self.connection.current_schema.clone());
+        let _ = ConnectionManager::save_connections(&self.connection_dialog.saved_connections);
+    }
+
+    /// Write the current layout and view toggles to `prefs.toml`.
+    pub fn save_prefs(&mut self) -> Result<()> {
+        self.prefs.sidebar_width = self.sidebar_width;
+        self.prefs.editor_height_percent = self.editor_height_percent;
+        self.prefs.wrap_selected_row = self.wrap_selected_row;
+        self.prefs.relative_time = self.relative_time;
+        self.prefs.autocomplete = self.autocomplete_while_typing;
+        self.prefs.save()
+    }
+
     pub async fn handle_input(&mut self, key: KeyEvent) -> Result<()> {
         // Global shortcuts
         match (key.code, key.modifiers) {
-            (KeyCode::Char('?'), _) if self.focus != Focus::Editor => {
+            (KeyCode::Char('?'), _)
+                if !matches!(
+                    self.focus,
+                    Focus::Editor
+                        | Focus::DestructiveConfirm
+                        | Focus::CommandPalette
+                        | Focus::GoToLine
+                        | Focus::Find
+                ) =>
+            {
                 self.show_help = !self.show_help;
                 if self.show_help {
                     self.focus = Focus::Help;
@@ -493,10 +1026,29 @@ impl App {
                 self.focus = Focus::Editor;
                 return Ok(());
             }
+            (KeyCode::Char('p'), KeyModifiers::CONTROL)
+                if matches!(self.focus, Focus::Editor | Focus::Results | Focus::Sidebar) =>
+            {
+                self.command_palette = Some(CommandPaletteState {
+                    query: String::new(),
+                    matches: filter_actions(""),
+                    selected: 0,
+                    return_focus: self.focus,
+                });
+                self.autocomplete.active = false;
+                self.focus = Focus::CommandPalette;
+                return Ok(());
+            }
             _ => {}
         }
 
-        match self.focus {
+        if matches!(self.focus, Focus::Editor | Focus::Results | Focus::Sidebar) {
+            if let Some(action) = self.keymap.action_for(KeyContext::Global, &key) {
+                return self.run_palette_action(action).await;
+            }
+        }
+
+        let result = match self.focus {
             Focus::ConnectionDialog => self.handle_connection_dialog_input(key).await,
             Focus::Sidebar => self.handle_sidebar_input(key).await,
             Focus::Editor => self.handle_editor_input(key).await,
@@ -504,12 +1056,135 @@ impl App {
             Focus::Help => self.handle_help_input(key).await,
             Focus::TableInspector => self.handle_table_inspector_input(key).await,
             Focus::ExportPicker => self.handle_export_input(key).await,
+            Focus::AstTree => self.handle_ast_tree_input(key).await,
+            Focus::DestructiveConfirm => self.handle_destructive_confirm_input(key).await,
+            Focus::ResultCompare => self.handle_result_compare_input(key).await,
+            Focus::CellDetail => self.handle_cell_detail_input(key),
+            Focus::CommandPalette => self.handle_command_palette_input(key).await,
+            Focus::GoToLine => self.handle_go_to_line_input(key),
+            Focus::Find => self.handle_find_input(key),
+        };
+        self.editor
+            .ensure_cursor_visible(self.editor_visible_rows.max(1));
+        self.ensure_sidebar_selection_visible();
+        result
+    }
+
+    /// Scroll the sidebar list so the selected row is in view.
+    fn ensure_sidebar_selection_visible(&mut self) {
+        let height = self.sidebar_visible_rows.max(1);
+        if self.sidebar_selected < self.sidebar_scroll {
+            self.sidebar_scroll = self.sidebar_selected;
+        } else if self.sidebar_selected >= self.sidebar_scroll + height {
+            self.sidebar_scroll = self.sidebar_selected + 1 - height;
+        }
+    }
+
+    // Conditions stay inside the key arms on purpose: as match guards they
+    // would let the key fall through to later arms.
+    #[allow(clippy::collapsible_match)]
+    fn handle_find_input(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(find) = self.find.as_mut() else {
+            self.focus = Focus::Editor;
+            return Ok(());
+        };
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        match key.code {
+            KeyCode::Esc => {
+                // Leave the current match selected
+                self.find = None;
+                self.focus = Focus::Editor;
+                return Ok(());
+            }
+            KeyCode::Enter | KeyCode::Down | KeyCode::F(3) if !shift => {
+                if !find.matches.is_empty() {
+                    find.current = (find.current + 1) % find.matches.len();
+                }
+            }
+            KeyCode::Enter | KeyCode::Up | KeyCode::F(3) => {
+                if !find.matches.is_empty() {
+                    find.current = (find.current + find.matches.len() - 1) % find.matches.len();
+                }
+            }
+            KeyCode::Backspace => {
+                find.query.pop();
+                self.refresh_find_matches();
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                find.query.push(c);
+                self.refresh_find_matches();
+            }
+            _ => {}
+        }
+        self.select_current_find_match();
+        Ok(())
+    }
+
+    /// Re-run the search after the query changed, starting from the first
+    /// match at or after where the search was opened.
+    fn refresh_find_matches(&mut self) {
+        let Some(find) = self.find.as_mut() else {
+            return;
+        };
+        find.matches = self.editor.find_all(&find.query);
+        let (ox, oy) = find.origin;
+        find.current = find
+            .matches
+            .iter()
+            .position(|&(x, y)| (y, x) >= (oy, ox))
+            .unwrap_or(0);
+    }
+
+    /// Select the current match, or return to the origin when nothing matches.
+    fn select_current_find_match(&mut self) {
+        let Some(find) = &self.find else {
+            return;
+        };
+        match find.matches.get(find.current) {
+            Some(&(x, y)) => {
+                self.editor.selection_start = Some((x, y));
+                self.editor.cursor_x = x + find.query.len();
+                self.editor.cursor_y = y;
+            }
+            None => {
+                self.editor.clear_selection();
+                (self.editor.cursor_x, self.editor.cursor_y) = find.origin;
+            }
+        }
+    }
+
+    fn handle_go_to_line_input(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(input) = self.go_to_line.as_mut() else {
+            self.focus = Focus::Editor;
+            return Ok(());
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.go_to_line = None;
+                self.focus = Focus::Editor;
+            }
+            KeyCode::Enter => {
+                if let Ok(line) = input.parse::<usize>() {
+                    self.editor.go_to_line(line);
+                }
+                self.go_to_line = None;
+                self.focus = Focus::Editor;
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) if c.is_ascii_digit() => input.push(c),
+            _ => {}
         }
+        Ok(())
     }
 
+    #[allow(clippy::collapsible_match)]
     async fn handle_connection_dialog_input(&mut self, key: KeyEvent) -> Result<()> {
         // Ignore input while connection is in progress (except Esc to cancel)
-        if self.pending_connection.is_some() && key.code != KeyCode::Esc {
+        if (self.pending_connection.is_some() || self.pending_test.is_some())
+            && key.code != KeyCode::Esc
+        {
             return Ok(());
         }
 
@@ -525,6 +1200,12 @@ impl App {
                         Some(("Connection cancelled".to_string(), StatusType::Warning));
                     return Ok(());
                 }
+                if let Some(handle) = self.pending_test.take() {
+                    handle.abort();
+                    self.connection_dialog.status_message =
+                        Some(("Connection test cancelled".to_string(), StatusType::Warning));
+                    return Ok(());
+                }
                 if self.connection.is_connected() {
                     dialog.active = false;
                     self.focus = Focus::Editor;
@@ -533,12 +1214,27 @@ impl App {
                     self.should_quit = true;
                 }
             }
+            KeyCode::Char('o') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                dialog.active = false;
+                self.offline_mode = true;
+                self.focus = Focus::Editor;
+                self.set_status(
+                    "Offline mode: queries are parsed and analyzed locally".to_string(),
+                    StatusType::Info,
+                );
+            }
+            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.save_dialog_connection();
+            }
+            KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.start_connection_test();
+            }
             KeyCode::Tab => {
-                dialog.field_index = (dialog.field_index + 1) % 7;
+                dialog.field_index = (dialog.field_index + 1) % 8;
             }
             KeyCode::BackTab => {
                 dialog.field_index = if dialog.field_index == 0 {
-                    6
+                    7
                 } else {
                     dialog.field_index - 1
                 };
@@ -571,6 +1267,9 @@ impl App {
                         SslMode::VerifyCa => SslMode::Require,
                         SslMode::VerifyFull => SslMode::VerifyCa,
                     };
+                } else if dialog.field_index == 7 {
+                    dialog.config.environment =
+                        Environment::cycle(dialog.config.environment, false);
                 } else if dialog.field_cursors[dialog.field_index] > 0 {
                     dialog.field_cursors[dialog.field_index] -= 1;
                 }
@@ -585,6 +1284,8 @@ impl App {
                         SslMode::VerifyCa => SslMode::VerifyFull,
                         SslMode::VerifyFull => SslMode::Disable,
                     };
+                } else if dialog.field_index == 7 {
+                    dialog.config.environment = Environment::cycle(dialog.config.environment, true);
                 } else {
                     let len = dialog_field_len(&dialog.config, dialog.field_index);
                     if dialog.field_cursors[dialog.field_index] < len {
@@ -606,7 +1307,7 @@ impl App {
             KeyCode::Enter => {
                 if let Some(idx) = dialog.selected_saved {
                     if idx < dialog.saved_connections.len() {
-                        dialog.config = dialog.saved_connections[idx].clone();
+                        dialog.config = dialog.saved_connections[idx].resume();
                         dialog.field_cursors = [
                             dialog.config.name.len(),
                             dialog.config.host.len(),
@@ -617,13 +1318,14 @@ impl App {
                         ];
                         dialog.field_index = 5; // Auto-focus password field
                         dialog.selected_saved = None;
+                        dialog.editing = Some(idx);
                     }
                 } else {
                     self.start_connect();
                 }
             }
             KeyCode::Char(c) => {
-                if dialog.field_index == 6 {
+                if dialog.field_index >= 6 {
                     return Ok(());
                 }
                 dialog.selected_saved = None;
@@ -666,7 +1368,7 @@ impl App {
                 }
             }
             KeyCode::Backspace => {
-                if dialog.field_index == 6 {
+                if dialog.field_index >= 6 {
                     return Ok(());
                 }
                 dialog.selected_saved = None;
@@ -716,6 +1418,11 @@ impl App {
                     // Delete saved connection
                     if idx < dialog.saved_connections.len() {
                         dialog.saved_connections.remove(idx);
+                        dialog.editing = match dialog.editing {
+                            Some(e) if e == idx => None,
+                            Some(e) if e > idx => Some(e - 1),
+                            other => other,
+                        };
                         let _ = ConnectionManager::save_connections(&dialog.saved_connections);
                         if dialog.saved_connections.is_empty() {
                             dialog.selected_saved = None;
@@ -726,7 +1433,7 @@ impl App {
                     }
                 } else {
                     // Delete character in text field
-                    if dialog.field_index == 6 {
+                    if dialog.field_index >= 6 {
                         return Ok(());
                     }
                     dialog.selected_saved = None;
@@ -773,6 +1480,7 @@ impl App {
         Ok(())
     }
 
+    #[allow(clippy::collapsible_match)]
     async fn handle_sidebar_input(&mut self, key: KeyEvent) -> Result<()> {
         match key.code {
             KeyCode::Tab | KeyCode::Right => {
@@ -786,13 +1494,21 @@ impl App {
             KeyCode::Down => {
                 let max = match self.sidebar_tab {
                     SidebarTab::Databases => self.databases.len(),
-                    SidebarTab::Tables => self.tables.len() + self.schemas.len(),
+                    SidebarTab::Tables => self.table_tree_rows().count(),
                     SidebarTab::History => self.query_history.entries().len(),
                 };
                 if self.sidebar_selected < max.saturating_sub(1) {
                     self.sidebar_selected += 1;
                 }
             }
+            // Shift+Enter is not reported by every terminal, so Alt+Enter works too
+            KeyCode::Enter
+                if key
+                    .modifiers
+                    .intersects(KeyModifiers::SHIFT | KeyModifiers::ALT) =>
+            {
+                self.insert_column_list().await;
+            }
             KeyCode::Enter => {
                 self.handle_sidebar_select().await?;
             }
@@ -815,14 +1531,40 @@ impl App {
             KeyCode::Char('i') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 self.open_table_inspector().await;
             }
+            KeyCode::Char('x') if self.sidebar_tab == SidebarTab::History => {
+                self.rerun_history_entry().await?;
+            }
+            KeyCode::Char('n') if self.sidebar_tab == SidebarTab::Tables => {
+                self.count_selected_table_rows();
+            }
+            KeyCode::Char('p') if self.sidebar_tab == SidebarTab::Tables => {
+                self.peek_selected_table().await?;
+            }
             _ => {}
         }
         Ok(())
     }
 
+    #[allow(clippy::collapsible_match)]
     async fn handle_editor_input(&mut self, key: KeyEvent) -> Result<()> {
         let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
         let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+
+        if let Some(vim) = self.vim.as_mut() {
+            match vim.handle_key(&mut self.editor, key) {
+                VimOutcome::Handled => {
+                    self.autocomplete.active = false;
+                    return Ok(());
+                }
+                VimOutcome::PassThrough => {}
+                VimOutcome::Command(command) => return self.run_vim_command(&command).await,
+                VimOutcome::Message(message) => {
+                    self.set_status(message, StatusType::Warning);
+                    return Ok(());
+                }
+            }
+        }
 
         // Handle autocomplete navigation when active
         if self.autocomplete.active {
@@ -858,12 +1600,30 @@ impl App {
             }
         }
 
+        // Tab steps through the placeholders of an inserted function call
+        if let Some(snippet) = self.snippet.as_mut() {
+            match key.code {
+                KeyCode::Tab if !shift && !ctrl && snippet.y == self.editor.cursor_y => {
+                    snippet.current += 1;
+                    self.select_snippet_argument();
+                    return Ok(());
+                }
+                KeyCode::Esc => self.snippet = None,
+                _ => {}
+            }
+        }
+
         // Ctrl+Space triggers autocomplete
         if ctrl && key.code == KeyCode::Char(' ') {
-            self.update_autocomplete();
+            self.update_autocomplete(true);
             return Ok(());
         }
 
+        if let Some(action) = self.keymap.action_for(KeyContext::Editor, &key) {
+            self.autocomplete.active = false;
+            return self.run_palette_action(action).await;
+        }
+
         match key.code {
             KeyCode::Tab if !ctrl => {
                 if shift {
@@ -875,17 +1635,8 @@ impl App {
             KeyCode::BackTab => {
                 self.focus = Focus::Sidebar;
             }
-            KeyCode::Enter if ctrl => {
-                self.autocomplete.active = false;
-                self.execute_query().await?;
-                self.focus = Focus::Results;
-            }
-            KeyCode::F(5) => {
-                self.autocomplete.active = false;
-                self.execute_query().await?;
-                self.focus = Focus::Results;
-            }
             KeyCode::Enter => {
+                self.uppercase_keyword_before(self.editor.cursor_x);
                 self.editor.insert_newline();
                 self.autocomplete.active = false;
             }
@@ -910,9 +1661,11 @@ impl App {
             KeyCode::Char('y') if ctrl => {
                 self.editor.redo();
             }
-            KeyCode::Char('l') if ctrl => {
-                self.editor.clear();
-                self.autocomplete.active = false;
+            KeyCode::Char('u') if alt => {
+                self.editor.uppercase_selection();
+            }
+            KeyCode::Char('l') if alt => {
+                self.editor.lowercase_selection();
             }
             // Pane resizing: Ctrl+Shift+Up/Down
             KeyCode::Up if ctrl && shift => {
@@ -940,11 +1693,14 @@ impl App {
             }
             KeyCode::Char(c) => {
                 self.editor.insert_char(c);
-                self.update_autocomplete();
+                if !(c.is_alphanumeric() || c == '_') {
+                    self.uppercase_keyword_before(self.editor.cursor_x - 1);
+                }
+                self.update_autocomplete(false);
             }
             KeyCode::Backspace => {
                 self.editor.backspace();
-                self.update_autocomplete();
+                self.update_autocomplete(false);
             }
             KeyCode::Delete => {
                 self.editor.delete();
@@ -995,28 +1751,48 @@ impl App {
         Ok(())
     }
 
+    #[allow(clippy::collapsible_match)]
     async fn handle_results_input(&mut self, key: KeyEvent) -> Result<()> {
+        if let Some(plan_lines) = self.visible_plan_line_count() {
+            let max_scroll = plan_lines.saturating_sub(1);
+            match key.code {
+                KeyCode::Up if !key.modifiers.contains(KeyModifiers::SHIFT) => {
+                    self.plan_scroll = self.plan_scroll.saturating_sub(1);
+                    return Ok(());
+                }
+                KeyCode::Down => {
+                    self.plan_scroll = (self.plan_scroll + 1).min(max_scroll);
+                    return Ok(());
+                }
+                KeyCode::PageUp => {
+                    self.plan_scroll = self.plan_scroll.saturating_sub(20);
+                    return Ok(());
+                }
+                KeyCode::PageDown => {
+                    self.plan_scroll = (self.plan_scroll + 20).min(max_scroll);
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(action) = self.keymap.action_for(KeyContext::Results, &key) {
+            return self.run_palette_action(action).await;
+        }
+
         match key.code {
             // Tab/Shift+Tab for column navigation (Snowflake-style)
             KeyCode::Tab if key.modifiers.contains(KeyModifiers::SHIFT) => {
                 // Shift+Tab: move to previous column
-                if self.result_selected_col > 0 {
-                    self.result_selected_col -= 1;
-                }
+                self.step_result_column(false);
             }
             KeyCode::BackTab => {
                 // BackTab: move to previous column
-                if self.result_selected_col > 0 {
-                    self.result_selected_col -= 1;
-                }
+                self.step_result_column(false);
             }
             KeyCode::Tab => {
                 // Tab: move to next column
-                if let Some(result) = self.results.get(self.current_result) {
-                    if self.result_selected_col < result.columns.len().saturating_sub(1) {
-                        self.result_selected_col += 1;
-                    }
-                }
+                self.step_result_column(true);
             }
             KeyCode::Esc => {
                 // Esc to leave results and go back to editor
@@ -1026,16 +1802,10 @@ impl App {
                 self.focus = Focus::Editor;
             }
             KeyCode::Left => {
-                if self.result_selected_col > 0 {
-                    self.result_selected_col -= 1;
-                }
+                self.step_result_column(false);
             }
             KeyCode::Right => {
-                if let Some(result) = self.results.get(self.current_result) {
-                    if self.result_selected_col < result.columns.len().saturating_sub(1) {
-                        self.result_selected_col += 1;
-                    }
-                }
+                self.step_result_column(true);
             }
             KeyCode::Up => {
                 if self.result_selected_row > 0 {
@@ -1044,7 +1814,7 @@ impl App {
                 }
             }
             KeyCode::Down => {
-                if let Some(result) = self.results.get(self.current_result) {
+                if let Some(result) = self.current_query_result() {
                     if self.result_selected_row < result.rows.len().saturating_sub(1) {
                         self.result_selected_row += 1;
                         self.auto_scroll_results();
@@ -1052,11 +1822,13 @@ impl App {
                 }
             }
             KeyCode::Home => {
-                self.result_selected_col = 0;
+                if let Some(&first) = self.visible_result_columns().first() {
+                    self.result_selected_col = first;
+                }
             }
             KeyCode::End => {
-                if let Some(result) = self.results.get(self.current_result) {
-                    self.result_selected_col = result.columns.len().saturating_sub(1);
+                if let Some(&last) = self.visible_result_columns().last() {
+                    self.result_selected_col = last;
                 }
             }
             KeyCode::PageUp => {
@@ -1064,7 +1836,7 @@ impl App {
                 self.auto_scroll_results();
             }
             KeyCode::PageDown => {
-                if let Some(result) = self.results.get(self.current_result) {
+                if let Some(result) = self.current_query_result() {
                     self.result_selected_row =
                         (self.result_selected_row + 20).min(result.rows.len().saturating_sub(1));
                     self.auto_scroll_results();
@@ -1073,38 +1845,23 @@ impl App {
             KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 self.copy_selected_cell();
             }
-            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                if !self.results.is_empty() {
-                    self.export_selected = 0;
-                    self.focus = Focus::ExportPicker;
-                }
+            KeyCode::Enter => {
+                self.open_cell_detail();
             }
             KeyCode::Char('[') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 if self.current_result > 0 {
-                    self.current_result -= 1;
-                    self.result_selected_row = 0;
-                    self.result_selected_col = 0;
-                    self.result_scroll_y = 0;
+                    self.show_result(self.current_result - 1);
                 }
             }
             KeyCode::Char(']') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 if self.current_result < self.results.len().saturating_sub(1) {
-                    self.current_result += 1;
-                    self.result_selected_row = 0;
-                    self.result_selected_col = 0;
-                    self.result_scroll_y = 0;
+                    self.show_result(self.current_result + 1);
                 }
             }
             KeyCode::Char('e') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 // Toggle between visual plan and raw text for EXPLAIN results
-                if self
-                    .explain_plans
-                    .get(self.current_result)
-                    .and_then(|p| p.as_ref())
-                    .is_some()
-                {
+                if self.current_entry().is_some_and(|e| e.plan.is_some()) {
                     self.show_visual_plan = !self.show_visual_plan;
-                    self.plan_scroll = 0;
                 }
             }
             _ => {}
@@ -1112,14 +1869,239 @@ impl App {
         Ok(())
     }
 
-    /// Keep the selected result row visible by adjusting scroll position.
-    fn auto_scroll_results(&mut self) {
-        if self.result_selected_row < self.result_scroll_y {
-            self.result_scroll_y = self.result_selected_row;
+    /// Re-run the current result's SELECT with its LIMIT or OFFSET changed:
+    /// `limit_factor` doubles (2) or halves (-2) the page size, `pages`
+    /// moves the OFFSET by whole pages. A SELECT without LIMIT gets one.
+    async fn adjust_result_window(&mut self, limit_factor: i64, pages: i64) -> Result<()> {
+        let Some(sql) = self.current_entry().map(|e| &e.query) else {
+            return Ok(());
+        };
+        let Some((limit, offset)) = select_window(sql) else {
+            self.set_status(
+                "Only a SELECT with a literal LIMIT/OFFSET can be paged".to_string(),
+                StatusType::Warning,
+            );
+            return Ok(());
+        };
+        let mut limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).max(1);
+        limit = match limit_factor {
+            f if f > 0 => limit.saturating_mul(f),
+            f if f < 0 => (limit / -f).max(1),
+            _ => limit,
+        };
+        let new_offset = offset.saturating_add(pages.saturating_mul(limit)).max(0);
+        if pages < 0 && offset == 0 {
+            self.set_status("Already at the first page".to_string(), StatusType::Info);
+            return Ok(());
         }
-        // Use a conservative visible-height estimate; rendering will clamp if needed
-        let estimated_visible = 20_usize;
-        if self.result_selected_row >= self.result_scroll_y + estimated_visible {
+        let Some(paged) = with_select_window(sql, limit, new_offset) else {
+            return Ok(());
+        };
+        self.set_status(
+            format!("LIMIT {} OFFSET {}", limit, new_offset),
+            StatusType::Info,
+        );
+        self.run_query_unguarded(paged).await
+    }
+
+    /// When result `index` of `sql` filled its LIMIT, ask the planner in the
+    /// background how many rows the SELECT has without it.
+    fn start_row_estimate(&mut self, sql: &str, index: usize) {
+        let Some(result) = self.results.get(index).map(|e| &e.result) else {
+            return;
+        };
+        let filled = match select_window(sql) {
+            Some((Some(limit), _)) => result.error.is_none() && result.row_count as i64 >= limit,
+            _ => false,
+        };
+        let (true, Some(unlimited), Some(client)) = (
+            filled,
+            without_select_window(sql),
+            self.connection.catalog_client().cloned(),
+        ) else {
+            return;
+        };
+        if let Some(pending) = self.pending_estimate.take() {
+            pending.handle.abort();
+        }
+        let explain = format!("EXPLAIN {}", unlimited);
+        let sql = explain.clone();
+        let handle = tokio::spawn(async move {
+            let messages = client.simple_query(&sql).await?;
+            let text = messages
+                .iter()
+                .filter_map(|msg| match msg {
+                    SimpleQueryMessage::Row(row) => row.get(0).map(str::to_string),
+                    _ => None,
+                })
+                .collect::<Vec<String>>()
+                .join("\n");
+            Ok(parse_explain_output(&text).and_then(|plan| plan.root.estimated_rows))
+        });
+        self.pending_estimate = Some(PendingEstimate {
+            index,
+            explain,
+            started: Instant::now(),
+            handle,
+        });
+    }
+
+    /// The result being shown, with what the results view keeps for it.
+    pub fn current_entry(&self) -> Option<&ResultEntry> {
+        self.results.get(self.current_result)
+    }
+
+    /// The query result being shown.
+    pub fn current_query_result(&self) -> Option<&QueryResult> {
+        self.current_entry().map(|e| &e.result)
+    }
+
+    /// Planner estimate of the current result's rows without its LIMIT, when
+    /// it is far above what was fetched.
+    pub fn truncated_row_estimate(&self) -> Option<u64> {
+        let entry = self.current_entry()?;
+        let estimate = entry.row_estimate?;
+        (estimate > (entry.result.row_count as u64).max(1) * ESTIMATE_NOTE_FACTOR)
+            .then_some(estimate)
+    }
+
+    /// Switch to result `index`, remembering the cursor of the one being
+    /// left and restoring where `index` was last viewed.
+    fn show_result(&mut self, index: usize) {
+        let leaving = self
+            .results
+            .get_mut(self.current_result)
+            .filter(|_| self.current_result != index);
+        if let Some(entry) = leaving {
+            entry.position = ResultPosition {
+                row: self.result_selected_row,
+                col: self.result_selected_col,
+                scroll_y: self.result_scroll_y,
+                plan_scroll: self.plan_scroll,
+            };
+        }
+        self.current_result = index;
+        let position = self
+            .results
+            .get(index)
+            .map(|e| e.position)
+            .unwrap_or_default();
+        self.result_selected_row = position.row;
+        self.result_selected_col = position.col;
+        self.result_scroll_y = position.scroll_y;
+        self.plan_scroll = position.plan_scroll;
+    }
+
+    /// Data indices of the current result's columns, in display order,
+    /// without the hidden ones.
+    pub fn visible_result_columns(&self) -> Vec<usize> {
+        let count = self
+            .current_query_result()
+            .map_or(0, |result| result.columns.len());
+        match self.current_entry() {
+            Some(entry) => entry.column_view.visible(count),
+            None => (0..count).collect(),
+        }
+    }
+
+    fn step_result_column(&mut self, forward: bool) {
+        let count = self
+            .current_query_result()
+            .map_or(0, |result| result.columns.len());
+        if let Some(view) = self.current_entry().map(|e| &e.column_view) {
+            if let Some(col) = view.step(self.result_selected_col, count, forward) {
+                self.result_selected_col = col;
+            }
+        }
+    }
+
+    /// Hide the selected result column and move to its visible neighbour.
+    fn hide_selected_column(&mut self) {
+        let Some(result) = self.current_query_result() else {
+            return;
+        };
+        let count = result.columns.len();
+        let Some(name) = result
+            .columns
+            .get(self.result_selected_col)
+            .map(|col| col.name.clone())
+        else {
+            return;
+        };
+        let Some(view) = self
+            .results
+            .get_mut(self.current_result)
+            .map(|e| &mut e.column_view)
+        else {
+            return;
+        };
+        if !view.hide(self.result_selected_col, count) {
+            self.set_status(
+                "Cannot hide the last visible column".to_string(),
+                StatusType::Warning,
+            );
+            return;
+        }
+        let col = self.result_selected_col;
+        let next = view
+            .step(col, count, true)
+            .or_else(|| view.step(col, count, false));
+        if let Some(next) = next {
+            self.result_selected_col = next;
+        }
+        self.set_status(
+            format!("Hid column {} (H shows all)", name),
+            StatusType::Info,
+        );
+    }
+
+    /// Move the selected result column one place left or right, or to the
+    /// front when `to_front` is set. The selection follows the column.
+    fn move_selected_column(&mut self, forward: bool, to_front: bool) {
+        let count = self
+            .current_query_result()
+            .map_or(0, |result| result.columns.len());
+        if let Some(view) = self
+            .results
+            .get_mut(self.current_result)
+            .map(|e| &mut e.column_view)
+        {
+            if to_front {
+                view.pin(self.result_selected_col, count);
+            } else {
+                view.shift(self.result_selected_col, count, forward);
+            }
+        }
+    }
+
+    fn show_all_columns(&mut self) {
+        if let Some(view) = self
+            .results
+            .get_mut(self.current_result)
+            .map(|e| &mut e.column_view)
+        {
+            view.show_all();
+        }
+    }
+
+    /// Line count of the visual plan when it is the view being shown.
+    fn visible_plan_line_count(&self) -> Option<usize> {
+        if !self.show_visual_plan {
+            return None;
+        }
+        self.current_entry()
+            .and_then(|e| e.plan.as_ref())
+            .map(|plan| plan.root.line_count())
+    }
+
+    /// Keep the selected result row visible by adjusting scroll position.
+    fn auto_scroll_results(&mut self) {
+        if self.result_selected_row < self.result_scroll_y {
+            self.result_scroll_y = self.result_selected_row;
+        }
+        // Use a conservative visible-height estimate; rendering will clamp if needed
+        let estimated_visible = 20_usize;
+        if self.result_selected_row >= self.result_scroll_y + estimated_visible {
             self.result_scroll_y = self
                 .result_selected_row
                 .saturating_sub(estimated_visible - 1);
@@ -1151,17 +2133,13 @@ impl App {
             }
             KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 if let Some(ref inspector) = self.table_inspector {
-                    if inspector.show_ddl {
-                        if let Ok(mut clipboard) = arboard::Clipboard::new() {
-                            let _ = clipboard.set_text(&inspector.ddl);
-                            self.set_status(
-                                "DDL copied to clipboard".to_string(),
-                                StatusType::Success,
-                            );
-                        }
+                    if let Ok(mut clipboard) = arboard::Clipboard::new() {
+                        let _ = clipboard.set_text(&inspector.ddl);
+                        self.set_status("DDL copied to clipboard".to_string(), StatusType::Success);
                     }
                 }
             }
+            KeyCode::Char('s') | KeyCode::Char('S') => self.copy_schema_ddl().await,
             KeyCode::Up => {
                 if let Some(ref mut inspector) = self.table_inspector {
                     inspector.scroll = inspector.scroll.saturating_sub(1);
@@ -1187,32 +2165,62 @@ impl App {
         Ok(())
     }
 
-    async fn handle_export_input(&mut self, key: KeyEvent) -> Result<()> {
+    /// Copy the `CREATE TABLE` of every table in the inspected table's schema.
+    async fn copy_schema_ddl(&mut self) {
+        let Some(schema) = self.table_inspector.as_ref().map(|i| i.schema_name.clone()) else {
+            return;
+        };
+        let Some(client) = self.connection.catalog_client() else {
+            return;
+        };
+        match get_schema_ddl(client, &schema).await {
+            Ok(ddls) => match arboard::Clipboard::new() {
+                Ok(mut clipboard) => {
+                    let _ = clipboard.set_text(ddls.join("\n"));
+                    self.set_status(
+                        format!(
+                            "DDL of {} tables in {} copied to clipboard",
+                            ddls.len(),
+                            schema
+                        ),
+                        StatusType::Success,
+                    );
+                }
+                Err(_) => {
+                    self.set_status("Failed to access clipboard".to_string(), StatusType::Error)
+                }
+            },
+            Err(e) => self.set_status(
+                format!("Failed to generate DDL for {}: {}", schema, e),
+                StatusType::Error,
+            ),
+        }
+    }
+
+    async fn handle_ast_tree_input(&mut self, key: KeyEvent) -> Result<()> {
         match key.code {
-            KeyCode::Esc => {
-                self.focus = Focus::Results;
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.ast_tree = None;
+                self.focus = Focus::Editor;
             }
             KeyCode::Up => {
-                if self.export_selected > 0 {
-                    self.export_selected -= 1;
+                if let Some(ref mut tree) = self.ast_tree {
+                    tree.scroll = tree.scroll.saturating_sub(1);
                 }
             }
             KeyCode::Down => {
-                if self.export_selected < EXPORT_FORMATS.len() - 1 {
-                    self.export_selected += 1;
+                if let Some(ref mut tree) = self.ast_tree {
+                    tree.scroll = (tree.scroll + 1).min(tree.lines.len().saturating_sub(1));
                 }
             }
-            KeyCode::Enter => {
-                let format = EXPORT_FORMATS[self.export_selected];
-                self.perform_export(format);
-                self.focus = Focus::Results;
+            KeyCode::PageUp => {
+                if let Some(ref mut tree) = self.ast_tree {
+                    tree.scroll = tree.scroll.saturating_sub(10);
+                }
             }
-            KeyCode::Char(c @ '1'..='5') => {
-                let idx = (c as usize) - ('1' as usize);
-                if idx < EXPORT_FORMATS.len() {
-                    let format = EXPORT_FORMATS[idx];
-                    self.perform_export(format);
-                    self.focus = Focus::Results;
+            KeyCode::PageDown => {
+                if let Some(ref mut tree) = self.ast_tree {
+                    tree.scroll = (tree.scroll + 10).min(tree.lines.len().saturating_sub(1));
                 }
             }
             _ => {}
@@ -1220,202 +2228,992 @@ impl App {
         Ok(())
     }
 
-    async fn open_table_inspector(&mut self) {
-        if self.sidebar_tab != SidebarTab::Tables || self.connection.client.is_none() {
-            return;
-        }
-
-        // Find the selected table from the sidebar
-        let mut index = 0;
-        let mut target_table: Option<(String, String)> = None;
-
-        for schema in &self.schemas {
-            if index == self.sidebar_selected {
-                // Schema is selected, not a table
-                return;
+    async fn handle_result_compare_input(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(compare) = self.result_compare.as_mut() else {
+            self.focus = Focus::Results;
+            return Ok(());
+        };
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.result_compare = None;
+                self.focus = Focus::Results;
             }
-            index += 1;
-
-            if self.expanded_schemas.contains(&schema.name) {
-                for table in &self.tables {
-                    if table.schema == schema.name {
-                        if index == self.sidebar_selected {
-                            target_table = Some((schema.name.clone(), table.name.clone()));
-                            break;
-                        }
-                        index += 1;
-                    }
-                }
-                if target_table.is_some() {
-                    break;
-                }
+            KeyCode::Up => {
+                compare.scroll = compare.scroll.saturating_sub(1);
+            }
+            KeyCode::Down => {
+                compare.scroll = (compare.scroll + 1).min(compare.lines.len().saturating_sub(1));
+            }
+            KeyCode::Left => {
+                let index = compare.pinned_index.saturating_sub(1);
+                let key_col = compare.key_col;
+                self.open_result_compare(index, key_col);
+            }
+            KeyCode::Right => {
+                let index = (compare.pinned_index + 1).min(self.pinned.len().saturating_sub(1));
+                let key_col = compare.key_col;
+                self.open_result_compare(index, key_col);
             }
+            _ => {}
         }
+        Ok(())
+    }
 
-        let (schema_name, table_name) = match target_table {
-            Some(t) => t,
-            None => return,
+    fn open_cell_detail(&mut self) {
+        let Some(result) = self.current_query_result() else {
+            return;
         };
-
-        let client = self.connection.client.as_ref().unwrap();
-
-        let columns = get_columns(client, &schema_name, &table_name)
-            .await
-            .unwrap_or_default();
-        let indexes = get_indexes(client, &schema_name, &table_name)
-            .await
-            .unwrap_or_default();
-        let ddl = get_table_ddl(client, &schema_name, &table_name)
-            .await
-            .unwrap_or_else(|_| "-- DDL generation failed".to_string());
-
-        self.table_inspector = Some(TableInspectorState {
-            table_name,
-            schema_name,
-            columns,
-            indexes,
-            ddl,
-            show_ddl: false,
+        let (Some(column), Some(cell)) = (
+            result.columns.get(self.result_selected_col),
+            result
+                .rows
+                .get(self.result_selected_row)
+                .and_then(|row| row.get(self.result_selected_col)),
+        ) else {
+            return;
+        };
+        let label = self
+            .current_entry()
+            .and_then(|e| e.column_view.label(self.result_selected_col));
+        self.cell_detail = Some(CellDetailState {
+            column: label.unwrap_or(&column.name).to_string(),
+            type_name: column.type_name.clone(),
+            text: cell.display(),
+            json: cell.pretty_json(),
+            // JSON stored as text is shown raw until asked for
+            show_json: matches!(cell, CellValue::Json(_)),
             scroll: 0,
         });
-        self.focus = Focus::TableInspector;
+        self.focus = Focus::CellDetail;
     }
 
-    fn perform_export(&mut self, format: ExportFormat) {
-        let result = match self.results.get(self.current_result) {
-            Some(r) => r,
-            None => {
-                self.set_status("No results to export".to_string(), StatusType::Warning);
-                return;
-            }
+    fn handle_cell_detail_input(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(detail) = self.cell_detail.as_mut() else {
+            self.focus = Focus::Results;
+            return Ok(());
         };
-
-        let content = match format {
-            ExportFormat::Csv => crate::export::to_csv(result),
-            ExportFormat::Json => crate::export::to_json(result),
-            ExportFormat::SqlInsert => crate::export::to_sql_insert(result, "results"),
-            ExportFormat::Tsv => crate::export::to_tsv(result),
-            ExportFormat::ClipboardCsv => {
-                let csv = crate::export::to_csv(result);
+        let max_scroll = detail.shown().lines().count().saturating_sub(1);
+        match key.code {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
+                self.cell_detail = None;
+                self.focus = Focus::Results;
+            }
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                let text = detail.shown().to_string();
                 if let Ok(mut clipboard) = arboard::Clipboard::new() {
-                    let _ = clipboard.set_text(&csv);
-                    self.set_status(
-                        format!("Copied {} rows to clipboard", result.row_count),
-                        StatusType::Success,
-                    );
+                    let _ = clipboard.set_text(&text);
+                    self.set_status("Cell copied to clipboard".to_string(), StatusType::Info);
+                }
+            }
+            KeyCode::Char('j') => {
+                if detail.json.is_some() {
+                    detail.show_json = !detail.show_json;
+                    detail.scroll = 0;
                 } else {
-                    self.set_status("Failed to access clipboard".to_string(), StatusType::Error);
+                    self.set_status("Cell is not JSON".to_string(), StatusType::Info);
                 }
-                return;
             }
-        };
-
-        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
-        let filename = format!("pgrsql_export_{}.{}", timestamp, format.extension());
+            KeyCode::Up => detail.scroll = detail.scroll.saturating_sub(1),
+            KeyCode::Down => detail.scroll = (detail.scroll + 1).min(max_scroll),
+            KeyCode::PageUp => detail.scroll = detail.scroll.saturating_sub(10),
+            KeyCode::PageDown => detail.scroll = (detail.scroll + 10).min(max_scroll),
+            _ => {}
+        }
+        Ok(())
+    }
 
-        match std::fs::write(&filename, &content) {
-            Ok(()) => {
-                self.set_status(
-                    format!("Exported {} rows to {}", result.row_count, filename),
-                    StatusType::Success,
-                );
-            }
-            Err(e) => {
-                self.set_status(format!("Export failed: {}", e), StatusType::Error);
+    /// Compare the current result against pinned result `index`, diffing
+    /// rows matched on column `key_col` when both have the same columns.
+    fn open_result_compare(&mut self, index: usize, key_col: usize) {
+        let (Some(current), Some(pinned)) = (self.current_query_result(), self.pinned.get(index))
+        else {
+            return;
+        };
+        let mut lines = current.compare(pinned);
+        lines.push(String::new());
+        match diff_results(pinned, current, key_col) {
+            Some(diffs) => {
+                let count = |f: fn(&RowDiff) -> bool| diffs.iter().filter(|d| f(d)).count();
+                lines.push(format!(
+                    "Row diff on {}: {} added, {} removed, {} changed",
+                    current.columns[key_col].name,
+                    count(|d| matches!(d, RowDiff::Added(_))),
+                    count(|d| matches!(d, RowDiff::Removed(_))),
+                    count(|d| matches!(d, RowDiff::Changed { .. })),
+                ));
+                for diff in &diffs {
+                    match diff {
+                        RowDiff::Added(row) => lines.push(format!("+ {}", row.join(" | "))),
+                        RowDiff::Removed(row) => lines.push(format!("- {}", row.join(" | "))),
+                        RowDiff::Changed { before, after } => {
+                            lines.push(format!("- {}", before.join(" | ")));
+                            lines.push(format!("+ {}", after.join(" | ")));
+                        }
+                    }
+                }
             }
+            None => lines.push("Row diff: needs the same columns in both results".to_string()),
         }
+        self.result_compare = Some(ResultCompareState {
+            pinned_index: index,
+            key_col,
+            lines,
+            scroll: 0,
+        });
+        self.focus = Focus::ResultCompare;
     }
 
-    async fn handle_sidebar_select(&mut self) -> Result<()> {
-        match self.sidebar_tab {
-            SidebarTab::Databases => {
-                if let Some(db) = self.databases.get(self.sidebar_selected) {
-                    let db_name = db.name.clone();
-                    self.connection.switch_database(&db_name).await?;
-                    self.refresh_schema().await?;
+    async fn handle_destructive_confirm_input(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(confirm) = self.destructive_confirm.as_mut() else {
+            self.focus = Focus::Editor;
+            return Ok(());
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.destructive_confirm = None;
+                self.focus = Focus::Editor;
+                let progress = self
+                    .batch
+                    .as_ref()
+                    .map(|batch| (batch.current(), batch.total, batch.transactional));
+                self.abort_batch().await;
+                match progress {
+                    Some((current, total, transactional)) => self.set_status(
+                        format!(
+                            "Statement {}/{} cancelled, stopping{}",
+                            current,
+                            total,
+                            if transactional {
+                                " and rolling back"
+                            } else {
+                                ""
+                            }
+                        ),
+                        StatusType::Info,
+                    ),
+                    None => self.set_status("Statement cancelled".to_string(), StatusType::Info),
+                }
+            }
+            KeyCode::Enter => {
+                if confirm.is_confirmed() {
+                    let query = confirm.query.clone();
+                    self.destructive_confirm = None;
+                    self.run_query_unguarded(query).await?;
+                    self.focus = Focus::Results;
+                } else {
                     self.set_status(
-                        format!("Switched to database: {}", db_name),
-                        StatusType::Success,
+                        "Typed name does not match the target".to_string(),
+                        StatusType::Warning,
                     );
                 }
             }
-            SidebarTab::Tables => {
-                // Calculate if it's a schema or table
-                let mut index = 0;
-                for schema in &self.schemas {
-                    if index == self.sidebar_selected {
-                        // Toggle schema expansion
-                        if self.expanded_schemas.contains(&schema.name) {
-                            self.expanded_schemas.retain(|s| s != &schema.name);
-                        } else {
-                            self.expanded_schemas.push(schema.name.clone());
-                        }
-                        return Ok(());
-                    }
-                    index += 1;
-
-                    if self.expanded_schemas.contains(&schema.name) {
-                        for table in &self.tables {
-                            if table.schema == schema.name {
-                                if index == self.sidebar_selected {
-                                    // Insert table name into editor
-                                    let full_name = format!("{}.{}", table.schema, table.name);
-                                    self.editor.insert_text(&full_name);
-                                    self.focus = Focus::Editor;
-                                    return Ok(());
-                                }
-                                index += 1;
-                            }
-                        }
-                    }
-                }
+            KeyCode::Backspace => {
+                confirm.input.pop();
             }
-            SidebarTab::History => {
-                let entries = self.query_history.entries();
-                if let Some(entry) = entries.get(entries.len() - 1 - self.sidebar_selected) {
-                    self.editor.set_text(&entry.query);
-                    self.focus = Focus::Editor;
-                }
+            KeyCode::Char(c) => {
+                confirm.input.push(c);
             }
+            _ => {}
         }
         Ok(())
     }
 
-    fn start_connect(&mut self) {
-        let mut config = self.connection_dialog.config.clone();
-
-        if config.host.is_empty() || config.username.is_empty() {
-            self.connection_dialog.status_message = Some((
-                "Host and username are required".to_string(),
-                StatusType::Error,
-            ));
+    /// Parse the query at the cursor and show its AST as a tree.
+    fn open_ast_tree(&mut self) {
+        let query = self.get_query_at_cursor();
+        if query.trim().is_empty() {
+            return;
+        }
+        match parse_sql(&query) {
+            Ok(statements) => {
+                let mut lines = Vec::new();
+                for (i, statement) in statements.iter().enumerate() {
+                    if i > 0 {
+                        lines.push(String::new());
+                    }
+                    lines.extend(statement.describe_tree());
+                }
+                self.ast_tree = Some(AstTreeState { lines, scroll: 0 });
+                self.focus = Focus::AstTree;
+            }
+            Err(e) => self.set_status(e.to_string(), StatusType::Error),
+        }
+    }
+
+    /// With `uppercase_keywords` on, uppercase a keyword ending at byte `end`
+    /// of the cursor line.
+    fn uppercase_keyword_before(&mut self, end: usize) {
+        if self.settings.editor.uppercase_keywords {
+            self.editor.uppercase_keyword_before(end, is_sql_keyword);
+        }
+    }
+
+    fn toggle_offline_mode(&mut self) {
+        self.offline_mode = !self.offline_mode;
+        let message = if self.offline_mode {
+            "Offline mode on: queries are parsed and analyzed locally"
+        } else {
+            "Offline mode off: queries run against the database"
+        };
+        self.set_status(message.to_string(), StatusType::Info);
+    }
+
+    fn trim_trailing_whitespace(&mut self) {
+        if self.editor.trim_trailing_whitespace() {
+            self.set_status("Trimmed trailing whitespace".to_string(), StatusType::Info);
+        }
+    }
+
+    fn toggle_autocomplete_while_typing(&mut self) {
+        self.autocomplete_while_typing = !self.autocomplete_while_typing;
+        self.autocomplete.active = false;
+        let message = if self.autocomplete_while_typing {
+            "Autocomplete while typing on"
+        } else {
+            "Autocomplete while typing off; Ctrl+Space still completes"
+        };
+        self.set_status(message.to_string(), StatusType::Info);
+    }
+
+    fn toggle_wrap_selected_row(&mut self) {
+        self.wrap_selected_row = !self.wrap_selected_row;
+        let message = if self.wrap_selected_row {
+            "Wrapping the selected row's cells"
+        } else {
+            "Truncating long cells"
+        };
+        self.set_status(message.to_string(), StatusType::Info);
+    }
+
+    fn toggle_result_chart(&mut self) {
+        if !self.show_result_chart && self.chart_columns().is_none() {
+            self.set_status(
+                "No numeric column to chart in this result".to_string(),
+                StatusType::Warning,
+            );
+            return;
+        }
+        self.show_result_chart = !self.show_result_chart;
+        let message = if self.show_result_chart {
+            "Showing the result as a bar chart"
+        } else {
+            "Showing the result as a table"
+        };
+        self.set_status(message.to_string(), StatusType::Info);
+    }
+
+    /// The `(label, value)` columns charted for the current result: the
+    /// selected column when it is numeric (else the first numeric one), and
+    /// the first non-numeric column as labels. Without one the row numbers
+    /// label the bars.
+    pub fn chart_columns(&self) -> Option<(Option<usize>, usize)> {
+        let result = self.current_query_result().filter(|r| r.error.is_none())?;
+        let (numeric, other): (Vec<usize>, Vec<usize>) = self
+            .visible_result_columns()
+            .into_iter()
+            .partition(|&col| result.is_numeric_column(col));
+        let value = if numeric.contains(&self.result_selected_col) {
+            self.result_selected_col
+        } else {
+            *numeric.first()?
+        };
+        Some((other.first().copied(), value))
+    }
+
+    fn toggle_relative_time(&mut self) {
+        self.relative_time = !self.relative_time;
+        let message = if self.relative_time {
+            "Showing timestamps relative to now"
+        } else {
+            "Showing absolute timestamps"
+        };
+        self.set_status(message.to_string(), StatusType::Info);
+    }
+
+    /// How result cells are rendered on screen.
+    pub fn display_options(&self) -> DisplayOptions {
+        DisplayOptions {
+            relative_time: self.relative_time,
+        }
+    }
+
+    fn pin_current_result(&mut self) {
+        if let Some(result) = self.current_query_result() {
+            self.pinned.push(result.clone());
+            self.set_status(
+                format!("Pinned result as #{} (c to compare)", self.pinned.len()),
+                StatusType::Info,
+            );
+        }
+    }
+
+    fn compare_with_last_pin(&mut self) {
+        if self.pinned.is_empty() {
+            self.set_status(
+                "No pinned results; press p to pin one".to_string(),
+                StatusType::Warning,
+            );
+        } else {
+            self.open_result_compare(self.pinned.len() - 1, self.result_selected_col);
+        }
+    }
+
+    fn open_export_picker(&mut self) {
+        if !self.results.is_empty() {
+            self.export_selected = 0;
+            self.focus = Focus::ExportPicker;
+        }
+    }
+
+    #[allow(clippy::collapsible_match)]
+    async fn handle_command_palette_input(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(palette) = self.command_palette.as_mut() else {
+            self.focus = Focus::Editor;
+            return Ok(());
+        };
+
+        match key.code {
+            KeyCode::Esc => {
+                self.focus = palette.return_focus;
+                self.command_palette = None;
+            }
+            KeyCode::Up => {
+                palette.selected = palette.selected.saturating_sub(1);
+            }
+            KeyCode::Down => {
+                if palette.selected + 1 < palette.matches.len() {
+                    palette.selected += 1;
+                }
+            }
+            KeyCode::Enter => {
+                let action = palette.matches.get(palette.selected).copied();
+                self.focus = palette.return_focus;
+                self.command_palette = None;
+                if let Some(action) = action {
+                    self.run_palette_action(action).await?;
+                }
+            }
+            KeyCode::Backspace => {
+                palette.query.pop();
+                palette.matches = filter_actions(&palette.query);
+                palette.selected = 0;
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                palette.query.push(c);
+                palette.matches = filter_actions(&palette.query);
+                palette.selected = 0;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Run a vim `:` command: a line number, a short alias, or any command
+    /// palette action by its config name (e.g. `:toggle_theme`).
+    async fn run_vim_command(&mut self, command: &str) -> Result<()> {
+        if let Ok(line) = command.parse::<usize>() {
+            self.editor.go_to_line(line);
+            return Ok(());
+        }
+        let action = match command {
+            "w" | "r" | "run" => Some(PaletteAction::ExecuteQuery),
+            "q" | "q!" | "quit" | "wq" => Some(PaletteAction::Quit),
+            "explain" => Some(PaletteAction::ExplainQuery),
+            "help" => Some(PaletteAction::ShowHelp),
+            other => PaletteAction::from_name(other),
+        };
+        match action {
+            Some(action) => self.run_palette_action(action).await,
+            None => {
+                self.set_status(
+                    format!("Not an editor command: {}", command),
+                    StatusType::Error,
+                );
+                Ok(())
+            }
+        }
+    }
+
+    async fn run_palette_action(&mut self, action: PaletteAction) -> Result<()> {
+        match action {
+            PaletteAction::ExecuteQuery => {
+                self.execute_query().await?;
+                // Modals opened by the query (confirmations, `\d`) keep focus
+                if self.focus == Focus::Editor {
+                    self.focus = Focus::Results;
+                }
+            }
+            PaletteAction::ExecuteAll => {
+                self.execute_all(false).await?;
+                if self.focus == Focus::Editor {
+                    self.focus = Focus::Results;
+                }
+            }
+            PaletteAction::ExecuteAllInTransaction => {
+                self.execute_all(true).await?;
+                if self.focus == Focus::Editor {
+                    self.focus = Focus::Results;
+                }
+            }
+            PaletteAction::ExplainQuery => {
+                let query = self.get_query_at_cursor();
+                if !query.trim().is_empty() && !is_explain_query(&query) {
+                    self.run_query(format!("EXPLAIN {}", query)).await?;
+                    self.focus = Focus::Results;
+                }
+            }
+            PaletteAction::CostCheck => self.check_query_cost().await,
+            PaletteAction::CountRows => {
+                self.count_query_rows().await?;
+                if self.focus == Focus::Editor {
+                    self.focus = Focus::Results;
+                }
+            }
+            PaletteAction::ClearEditor => {
+                self.editor.clear();
+                self.autocomplete.active = false;
+                self.focus = Focus::Editor;
+            }
+            PaletteAction::TrimWhitespace => self.trim_trailing_whitespace(),
+            PaletteAction::SelectStatement => self.select_statement_at_cursor(),
+            PaletteAction::Find => {
+                self.find = Some(FindState {
+                    query: String::new(),
+                    matches: Vec::new(),
+                    current: 0,
+                    origin: (self.editor.cursor_x, self.editor.cursor_y),
+                });
+                self.autocomplete.active = false;
+                self.focus = Focus::Find;
+            }
+            PaletteAction::GoToLine => {
+                self.go_to_line = Some(String::new());
+                self.autocomplete.active = false;
+                self.focus = Focus::GoToLine;
+            }
+            PaletteAction::ShowAst => self.open_ast_tree(),
+            PaletteAction::ToggleOffline => self.toggle_offline_mode(),
+            PaletteAction::ToggleVimMode => {
+                self.vim = match self.vim {
+                    Some(_) => None,
+                    None => Some(VimState::new()),
+                };
+                self.editor.clear_selection();
+                let message = if self.vim.is_some() {
+                    "Vim mode on"
+                } else {
+                    "Vim mode off"
+                };
+                self.set_status(message.to_string(), StatusType::Info);
+            }
+            PaletteAction::ExportResults => self.open_export_picker(),
+            PaletteAction::PinResult => self.pin_current_result(),
+            PaletteAction::HideColumn => self.hide_selected_column(),
+            PaletteAction::ShowAllColumns => self.show_all_columns(),
+            PaletteAction::PinColumn => self.move_selected_column(false, true),
+            PaletteAction::MoveColumnLeft => self.move_selected_column(false, false),
+            PaletteAction::MoveColumnRight => self.move_selected_column(true, false),
+            PaletteAction::CompareWithPinned => self.compare_with_last_pin(),
+            PaletteAction::ToggleWrap => self.toggle_wrap_selected_row(),
+            PaletteAction::ToggleChart => self.toggle_result_chart(),
+            PaletteAction::ToggleRelativeTime => self.toggle_relative_time(),
+            PaletteAction::IncreaseLimit => self.adjust_result_window(2, 0).await?,
+            PaletteAction::DecreaseLimit => self.adjust_result_window(-2, 0).await?,
+            PaletteAction::NextPage => self.adjust_result_window(0, 1).await?,
+            PaletteAction::PreviousPage => self.adjust_result_window(0, -1).await?,
+            PaletteAction::ToggleAutocomplete => self.toggle_autocomplete_while_typing(),
+            PaletteAction::ToggleTheme => {
+                let next = if self.prefs.theme == "light" {
+                    "dark"
+                } else {
+                    "light"
+                };
+                self.prefs.theme = next.to_string();
+                self.theme = Theme::by_name(next).unwrap_or_default();
+                self.set_status(format!("Switched to {} theme", next), StatusType::Info);
+            }
+            PaletteAction::SwitchDatabase => {
+                self.sidebar_tab = SidebarTab::Databases;
+                self.sidebar_selected = 0;
+                self.focus = Focus::Sidebar;
+            }
+            PaletteAction::OpenConnectionDialog => {
+                self.focus = Focus::ConnectionDialog;
+                self.connection_dialog.active = true;
+            }
+            PaletteAction::CancelAndDisconnect => self.cancel_all_and_disconnect().await,
+            PaletteAction::ShowHelp => {
+                self.show_help = true;
+                self.focus = Focus::Help;
+            }
+            PaletteAction::Quit => {
+                self.should_quit = true;
+            }
+        }
+        Ok(())
+    }
+
+    /// Panic button: stop the running query and any execute-all run, roll
+    /// back, disconnect and reopen the connection dialog.
+    async fn cancel_all_and_disconnect(&mut self) {
+        if !self.connection.is_connected() {
+            self.set_status("Not connected".to_string(), StatusType::Info);
+            return;
+        }
+        self.batch = None;
+        if let Some(pending) = self.pending_query.take() {
+            pending.handle.abort();
+        }
+        if let Some(handle) = self.pending_schema.take() {
+            handle.abort();
+        }
+        if let Some((_, _, handle)) = self.pending_count.take() {
+            handle.abort();
+        }
+        if let Some(pending) = self.pending_estimate.take() {
+            pending.handle.abort();
+        }
+        // A reconnect finishing later would bring the session back
+        if let Some(reconnect) = self.pending_reconnect.take() {
+            reconnect.handle.abort();
+        }
+        if let Some(handle) = self.pending_ping.take() {
+            handle.abort();
+        }
+        self.stop_loading();
+
+        let started = Instant::now();
+        let (result, rolled_back) = self.connection.cancel_all_and_disconnect().await;
+        let error = rolled_back.err().map(|e| e.to_string());
+        self.audit_statement("ROLLBACK", started, error);
+        self.connection_dialog.active = true;
+        self.focus = Focus::ConnectionDialog;
+        match result {
+            Ok(()) => self.set_status(
+                "Cancelled, rolled back and disconnected".to_string(),
+                StatusType::Warning,
+            ),
+            Err(e) => self.set_status(
+                format!("Disconnected; the cancel request failed: {}", e),
+                StatusType::Warning,
+            ),
+        }
+    }
+
+    #[allow(clippy::collapsible_match)]
+    async fn handle_export_input(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Esc => {
+                self.focus = Focus::Results;
+            }
+            KeyCode::Up => {
+                if self.export_selected > 0 {
+                    self.export_selected -= 1;
+                }
+            }
+            KeyCode::Down => {
+                if self.export_selected < EXPORT_FORMATS.len() - 1 {
+                    self.export_selected += 1;
+                }
+            }
+            KeyCode::Enter => {
+                let format = EXPORT_FORMATS[self.export_selected];
+                self.perform_export(format);
+                self.focus = Focus::Results;
+            }
+            KeyCode::Char('z') => {
+                self.export_compress = !self.export_compress;
+            }
+            KeyCode::Char(c @ '1'..='9') => {
+                let idx = (c as usize) - ('1' as usize);
+                if idx < EXPORT_FORMATS.len() {
+                    let format = EXPORT_FORMATS[idx];
+                    self.perform_export(format);
+                    self.focus = Focus::Results;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Rows of the sidebar's Tables tree in display order: each schema,
+    /// followed by its tables when expanded.
+    pub fn table_tree_rows(&self) -> impl Iterator<Item = TreeRow<'_>> {
+        self.schemas.iter().flat_map(move |schema| {
+            let expanded = self.expanded_schemas.contains(&schema.name);
+            let tables = expanded.then(|| {
+                self.tables
+                    .iter()
+                    .filter(move |table| table.schema == schema.name)
+                    .map(TreeRow::Table)
+            });
+            std::iter::once(TreeRow::Schema(schema, expanded)).chain(tables.into_iter().flatten())
+        })
+    }
+
+    /// The `(schema, table)` under the sidebar cursor, or `None` when a schema
+    /// row is selected.
+    fn selected_sidebar_table(&self) -> Option<(String, String)> {
+        match self.table_tree_rows().nth(self.sidebar_selected)? {
+            TreeRow::Table(table) => Some((table.schema.clone(), table.name.clone())),
+            TreeRow::Schema(..) => None,
+        }
+    }
+
+    /// Insert the selected table's columns as `(col1, col2, ...)` at the
+    /// editor cursor.
+    async fn insert_column_list(&mut self) {
+        if self.sidebar_tab != SidebarTab::Tables {
+            return;
+        }
+        let Some((schema_name, table_name)) = self.selected_sidebar_table() else {
+            return;
+        };
+        let Some(client) = self.connection.catalog_client() else {
+            return;
+        };
+
+        match get_columns(client, &schema_name, &table_name).await {
+            Ok(columns) if !columns.is_empty() => {
+                let names: Vec<String> = columns.iter().map(|c| quote_ident(&c.name)).collect();
+                self.editor.insert_text(&format!("({})", names.join(", ")));
+                self.focus = Focus::Editor;
+            }
+            Ok(_) => {
+                self.set_status(
+                    format!("{}.{} has no columns", schema_name, table_name),
+                    StatusType::Warning,
+                );
+            }
+            Err(e) => {
+                self.set_status(format!("Failed to load columns: {}", e), StatusType::Error);
+            }
+        }
+    }
+
+    /// Run `SELECT COUNT(*)` on the selected sidebar table in the background;
+    /// `tick` toasts the exact count.
+    fn count_selected_table_rows(&mut self) {
+        let Some((schema, table)) = self.selected_sidebar_table() else {
+            return;
+        };
+        let Some(client) = self.connection.catalog_client().cloned() else {
+            return;
+        };
+        if self.pending_count.is_some() {
+            return;
+        }
+        let name = format!("{}.{}", quote_ident(&schema), quote_ident(&table));
+        let sql = table_count_sql(&name);
+        self.set_status(format!("Counting rows in {}...", name), StatusType::Info);
+        let handle = tokio::spawn(async move {
+            let row = client.query_one(&sql, &[]).await?;
+            Ok(row.get(0))
+        });
+        self.pending_count = Some((name, Instant::now(), handle));
+    }
+
+    /// Show the first rows of the selected table in Results.
+    async fn peek_selected_table(&mut self) -> Result<()> {
+        let Some((schema, table)) = self.selected_sidebar_table() else {
+            return Ok(());
+        };
+        let sql = format!(
+            "SELECT * FROM {}.{} LIMIT {}",
+            quote_ident(&schema),
+            quote_ident(&table),
+            self.settings.sidebar.peek_limit
+        );
+        self.run_query_unguarded(sql).await?;
+        if self.connection.is_connected() {
+            self.focus = Focus::Results;
+        }
+        Ok(())
+    }
+
+    async fn open_table_inspector(&mut self) {
+        if self.sidebar_tab != SidebarTab::Tables || self.connection.client.is_none() {
+            return;
+        }
+
+        let (schema_name, table_name) = match self.selected_sidebar_table() {
+            Some(t) => t,
+            None => return,
+        };
+        self.open_table_inspector_for(schema_name, table_name).await;
+    }
+
+    async fn open_table_inspector_for(&mut self, schema_name: String, table_name: String) {
+        let Some(client) = self.connection.catalog_client() else {
+            return;
+        };
+
+        let columns = get_columns(client, &schema_name, &table_name)
+            .await
+            .unwrap_or_default();
+        let indexes = get_indexes(client, &schema_name, &table_name)
+            .await
+            .unwrap_or_default();
+        let ddl = get_table_ddl(client, &schema_name, &table_name)
+            .await
+            .unwrap_or_else(|_| "-- DDL generation failed".to_string());
+        let loaded = self
+            .tables
+            .iter()
+            .find(|t| t.schema == schema_name && t.name == table_name);
+        let comment = match loaded {
+            Some(table) => table.comment.clone(),
+            None => get_table_comment(client, &schema_name, &table_name)
+                .await
+                .unwrap_or_default(),
+        };
+
+        self.table_inspector = Some(TableInspectorState {
+            table_name,
+            schema_name,
+            columns,
+            indexes,
+            comment,
+            ddl,
+            show_ddl: false,
+            scroll: 0,
+        });
+        self.focus = Focus::TableInspector;
+    }
+
+    fn perform_export(&mut self, format: ExportFormat) {
+        let result = match self.current_query_result() {
+            Some(r) => r,
+            None => {
+                self.set_status("No results to export".to_string(), StatusType::Warning);
+                return;
+            }
+        };
+
+        let content = match format {
+            ExportFormat::Csv => crate::export::to_csv_with(result, &self.settings.export.csv),
+            ExportFormat::Json => crate::export::to_json(result),
+            ExportFormat::Ndjson => crate::export::to_ndjson(result),
+            ExportFormat::SqlInsert => {
+                let source_table = self.current_entry().and_then(|e| e.source_table.as_ref());
+                let (schema, table) = match source_table {
+                    Some((schema, table)) => (schema.as_deref(), table.as_str()),
+                    _ => (None, "results"),
+                };
+                crate::export::to_sql_insert(
+                    result,
+                    schema,
+                    table,
+                    self.settings.export.sql_type_comments,
+                )
+            }
+            ExportFormat::Tsv => crate::export::to_tsv(result),
+            ExportFormat::ClipboardCsv => {
+                let csv = crate::export::to_csv_with(result, &self.settings.export.csv);
+                if let Ok(mut clipboard) = arboard::Clipboard::new() {
+                    let _ = clipboard.set_text(&csv);
+                    self.set_status(
+                        format!("Copied {} rows to clipboard", result.row_count),
+                        StatusType::Success,
+                    );
+                } else {
+                    self.set_status("Failed to access clipboard".to_string(), StatusType::Error);
+                }
+                return;
+            }
+            ExportFormat::ClipboardHtml => {
+                // Rich editors paste the table; plain-text targets get TSV
+                let html = crate::export::to_html(result);
+                let tsv = crate::export::to_tsv(result);
+                match arboard::Clipboard::new().and_then(|mut c| c.set_html(html, Some(tsv))) {
+                    Ok(()) => self.set_status(
+                        format!("Copied {} rows to clipboard as HTML", result.row_count),
+                        StatusType::Success,
+                    ),
+                    Err(_) => {
+                        self.set_status("Failed to access clipboard".to_string(), StatusType::Error)
+                    }
+                }
+                return;
+            }
+        };
+
+        let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
+        let mut filename = format!("pgrsql_export_{}.{}", timestamp, format.extension());
+        if self.export_compress {
+            filename.push_str(".gz");
+        }
+
+        match crate::export::write_export(
+            std::path::Path::new(&filename),
+            &content,
+            self.export_compress,
+        ) {
+            Ok(()) => {
+                self.set_status(
+                    format!("Exported {} rows to {}", result.row_count, filename),
+                    StatusType::Success,
+                );
+            }
+            Err(e) => {
+                self.set_status(format!("Export failed: {}", e), StatusType::Error);
+            }
+        }
+    }
+
+    async fn handle_sidebar_select(&mut self) -> Result<()> {
+        match self.sidebar_tab {
+            SidebarTab::Databases => {
+                if let Some(db) = self.databases.get(self.sidebar_selected) {
+                    let db_name = db.name.clone();
+                    self.switch_database(&db_name).await?;
+                }
+            }
+            SidebarTab::Tables => {
+                if let Some((schema, table)) = self.selected_sidebar_table() {
+                    match self.settings.sidebar.table_enter {
+                        TableEnterAction::Peek => self.peek_selected_table().await?,
+                        TableEnterAction::Insert => {
+                            self.editor.insert_text(&format!("{}.{}", schema, table));
+                            self.focus = Focus::Editor;
+                        }
+                    }
+                    return Ok(());
+                }
+                let selected_schema = match self.table_tree_rows().nth(self.sidebar_selected) {
+                    Some(TreeRow::Schema(schema, expanded)) => {
+                        Some((schema.name.clone(), expanded))
+                    }
+                    _ => None,
+                };
+                // Toggle schema expansion
+                match selected_schema {
+                    Some((name, true)) => self.expanded_schemas.retain(|s| s != &name),
+                    Some((name, false)) => {
+                        self.expanded_schemas.push(name.clone());
+                        self.load_schema_tables(&name).await;
+                    }
+                    None => {}
+                }
+            }
+            SidebarTab::History => {
+                if let Some(entry) = self.selected_history_entry().cloned() {
+                    self.warn_if_other_database(&entry);
+                    self.editor.set_text(&entry.query);
+                    self.focus = Focus::Editor;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The history entry under the sidebar cursor (the list is shown newest first).
+    fn selected_history_entry(&self) -> Option<&HistoryEntry> {
+        let entries = self.query_history.entries();
+        entries
+            .len()
+            .checked_sub(1 + self.sidebar_selected)
+            .and_then(|idx| entries.get(idx))
+    }
+
+    /// Warn when a history entry was recorded against a different database
+    /// than the one we are currently connected to.
+    fn warn_if_other_database(&mut self, entry: &HistoryEntry) {
+        if entry.database != self.connection.current_database {
+            self.set_status(
+                format!(
+                    "History entry ran against '{}', current database is '{}'",
+                    entry.database, self.connection.current_database
+                ),
+                StatusType::Warning,
+            );
+        }
+    }
+
+    /// Re-execute the selected history entry without touching the editor.
+    async fn rerun_history_entry(&mut self) -> Result<()> {
+        let Some(entry) = self.selected_history_entry().cloned() else {
+            return Ok(());
+        };
+        self.warn_if_other_database(&entry);
+        self.run_query(entry.query).await?;
+        if self.connection.is_connected() && self.destructive_confirm.is_none() {
+            self.focus = Focus::Results;
+        }
+        Ok(())
+    }
+
+    fn start_connect(&mut self) {
+        let mut config = self.connection_dialog.config.clone();
+
+        if config.host.is_empty() || config.username.is_empty() {
+            self.connection_dialog.status_message = Some((
+                "Host and username are required".to_string(),
+                StatusType::Error,
+            ));
             return;
         }
 
         // Default database to "postgres" if left empty
         if config.database.trim().is_empty() {
             config.database = "postgres".to_string();
-            self.connection_dialog.config.database = "postgres".to_string();
-        }
-
-        // Don't start another connection if one is already in progress
-        if self.pending_connection.is_some() {
-            return;
+            self.connection_dialog.config.database = "postgres".to_string();
+        }
+
+        // Don't start another connection if one is already in progress
+        if self.pending_connection.is_some() {
+            return;
+        }
+
+        self.connection_dialog.status_message = Some((
+            format!("Connecting to {}...", config.display_string()),
+            StatusType::Info,
+        ));
+        self.start_loading(format!("Connecting to {}...", config.display_string()));
+
+        let config_for_task = config.clone();
+        let notices = self.connection.notice_sender();
+        let handle =
+            tokio::spawn(async move { create_client(&config_for_task, Some(notices)).await });
+        self.pending_connection = Some((config, handle));
+    }
+
+    /// Check the dialog's settings with a throwaway connection. The result
+    /// only shows in the dialog: nothing is saved and the session is kept.
+    fn start_connection_test(&mut self) {
+        let mut config = self.connection_dialog.config.clone();
+        if config.host.is_empty() || config.username.is_empty() {
+            self.connection_dialog.status_message = Some((
+                "Host and username are required".to_string(),
+                StatusType::Error,
+            ));
+            return;
+        }
+        if config.database.trim().is_empty() {
+            config.database = "postgres".to_string();
         }
 
         self.connection_dialog.status_message = Some((
-            format!("Connecting to {}...", config.display_string()),
+            format!("Testing {}...", config.display_string()),
             StatusType::Info,
         ));
-        self.start_loading(format!("Connecting to {}...", config.display_string()));
-
-        let config_for_task = config.clone();
-        let handle = tokio::spawn(async move { create_client(&config_for_task).await });
-        self.pending_connection = Some((config, handle));
+        self.pending_test = Some(tokio::spawn(async move { test_connection(&config).await }));
     }
 
     async fn finish_connect(&mut self, config: ConnectionConfig, client: Client) -> Result<()> {
         self.connection.apply_client(config.clone(), client);
+        let _ = self.connection.load_server_info().await;
+        self.connection.open_catalog_client();
+
+        // Restore the schema last used here, unless the user picked another database
+        let other_database = config
+            .last_database
+            .as_ref()
+            .is_some_and(|db| *db != config.database);
+        if let (Some(schema), false) = (&config.last_schema, other_database) {
+            let _ = self.connection.switch_schema(schema).await;
+        }
         self.stop_loading();
         self.connection_dialog.status_message = None;
         self.connection_dialog.active = false;
@@ -1437,7 +3235,7 @@ impl App {
         // Save as last used connection
         let _ = ConnectionManager::save_last_connection(&config.name);
 
-        self.refresh_schema().await?;
+        self.refresh_schema();
         self.set_status(
             format!("Connected to {}", config.display_string()),
             StatusType::Success,
@@ -1445,269 +3243,1084 @@ impl App {
         Ok(())
     }
 
-    async fn refresh_schema(&mut self) -> Result<()> {
-        if self.connection.client.is_some() {
+    /// Reload the sidebar's databases and schemas on a background task,
+    /// replacing any load still in flight. `tick` applies the result. Only
+    /// the tables of schemas on the search_path (for autocomplete) and of
+    /// expanded ones are fetched; the rest load when expanded.
+    fn refresh_schema(&mut self) {
+        let Some(client) = self.connection.catalog_client().cloned() else {
+            return;
+        };
+        if let Some(handle) = self.pending_schema.take() {
+            handle.abort();
+        }
+        if self.pending_query.is_none() {
             self.start_loading("Loading schema...".to_string());
+        }
+        self.tables.clear();
+        self.loaded_schemas.clear();
+
+        let mut eager = self.connection.search_path.clone();
+        for schema in &self.expanded_schemas {
+            if !eager.contains(schema) {
+                eager.push(schema.clone());
+            }
+        }
+        self.pending_schema = Some(tokio::spawn(async move {
+            let databases = get_databases(&client).await;
+            let schemas = get_schemas(&client).await;
+            let mut tables = Vec::new();
+            let mut loaded = Vec::new();
+            for schema in schemas.as_deref().unwrap_or_default() {
+                if !eager.contains(&schema.name) {
+                    continue;
+                }
+                if let Ok(schema_tables) = get_tables(&client, &schema.name).await {
+                    tables.extend(schema_tables);
+                    loaded.push(schema.name.clone());
+                }
+            }
+            SchemaLoad {
+                databases,
+                schemas,
+                tables,
+                loaded,
+            }
+        }));
+    }
+
+    /// Fetch `schema`'s tables the first time it is expanded.
+    async fn load_schema_tables(&mut self, schema: &str) {
+        if self.loaded_schemas.iter().any(|s| s == schema) {
+            return;
+        }
+        let Some(client) = self.connection.catalog_client() else {
+            return;
+        };
+        match get_tables(client, schema).await {
+            Ok(tables) => {
+                self.tables.extend(tables);
+                self.loaded_schemas.push(schema.to_string());
+            }
+            Err(e) => self.set_status(
+                format!("Failed to load tables of {}: {}", schema, e),
+                StatusType::Warning,
+            ),
+        }
+    }
+
+    fn apply_schema_load(&mut self, load: SchemaLoad) {
+        self.databases = match load.databases {
+            Ok(dbs) => dbs,
+            Err(e) => {
+                self.set_status(
+                    format!("Failed to load databases: {}", e),
+                    StatusType::Warning,
+                );
+                Vec::new()
+            }
+        };
+        self.schemas = match load.schemas {
+            Ok(s) => s,
+            Err(e) => {
+                self.set_status(
+                    format!("Failed to load schemas: {}", e),
+                    StatusType::Warning,
+                );
+                Vec::new()
+            }
+        };
+        // Keep schemas expanded (and so loaded) while this load ran
+        let loaded = std::mem::take(&mut self.loaded_schemas);
+        self.tables
+            .retain(|t| loaded.contains(&t.schema) && !load.loaded.contains(&t.schema));
+        self.loaded_schemas = loaded
+            .into_iter()
+            .filter(|s| !load.loaded.contains(s))
+            .collect();
+        self.tables.extend(load.tables);
+        self.loaded_schemas.extend(load.loaded);
+    }
+
+    /// Get the byte offset of the cursor in the full editor text.
+    fn get_cursor_offset(&self) -> usize {
+        let mut offset = 0;
+        for (i, line) in self.editor.lines.iter().enumerate() {
+            if i == self.editor.cursor_y {
+                offset += self.editor.cursor_x;
+                break;
+            }
+            offset += line.len() + 1; // +1 for newline
+        }
+        offset
+    }
+
+    /// Find the query at the current cursor position.
+    /// Splits on `;` while respecting string literals and comments.
+    fn get_query_at_cursor(&self) -> String {
+        let full_text = self.editor.text();
+        let cursor_offset = self.get_cursor_offset();
+
+        let boundaries = split_statements(&full_text);
+        for (start, end) in &boundaries {
+            if cursor_offset >= *start && cursor_offset <= *end {
+                return full_text[*start..*end].trim().to_string();
+            }
+        }
+
+        // Fallback to full text
+        full_text.trim().to_string()
+    }
+
+    /// Select the statement under the cursor, including its terminating `;`
+    /// but not the surrounding whitespace.
+    fn select_statement_at_cursor(&mut self) {
+        let full_text = self.editor.text();
+        let cursor_offset = self.get_cursor_offset();
+        let Some(&(start, end)) = split_statements(&full_text)
+            .iter()
+            .find(|(start, end)| cursor_offset >= *start && cursor_offset <= *end)
+        else {
+            return;
+        };
+        let statement = &full_text[start..end];
+        let start = start + (statement.len() - statement.trim_start().len());
+        let mut end = start + statement.trim().len();
+        if full_text[end..].starts_with(';') {
+            end += 1;
+        }
+        self.editor.select_offsets(start, end);
+    }
+
+    /// Size of the editor selection for the status bar, e.g. "142 chars, 6 lines".
+    pub fn selection_summary(&self) -> Option<String> {
+        let text = self.editor.get_selected_text()?;
+        if text.is_empty() {
+            return None;
+        }
+        let chars = text.chars().count();
+        let lines = text.lines().count().max(1);
+        let plural = |n: usize| if n == 1 { "" } else { "s" };
+        Some(format!(
+            "{} char{}, {} line{}",
+            chars,
+            plural(chars),
+            lines,
+            plural(lines)
+        ))
+    }
+
+    /// Returns (start_line, end_line) of the query block at the cursor,
+    /// for visual highlighting in the editor.
+    pub fn get_current_query_line_range(&self) -> Option<(usize, usize)> {
+        let full_text = self.editor.text();
+        let cursor_offset = self.get_cursor_offset();
+
+        let boundaries = split_statements(&full_text);
+        for (start, end) in &boundaries {
+            if cursor_offset >= *start && cursor_offset <= *end {
+                // Convert byte offsets to line numbers
+                let start_line = full_text[..*start].matches('\n').count();
+                let end_line = full_text[..*end].matches('\n').count();
+                return Some((start_line, end_line));
+            }
+        }
+        None
+    }
+
+    /// Recompute `statement_hints` when the text changed or the cursor moved
+    /// to another statement.
+    fn refresh_statement_hints(&mut self) {
+        let key = (self.editor.revision(), self.get_current_query_line_range());
+        if self.statement_hints.key == Some(key) {
+            return;
+        }
+        let query = self.get_query_at_cursor();
+        // Parses but only as `Query::Raw`, i.e. it uses constructs the
+        // structured AST doesn't cover
+        let is_raw = !query.trim().is_empty() && matches!(parse_single(&query), Ok(Query::Raw(_)));
+        let runtime = self.query_history.runtime_stats(&query).map(|stats| {
+            let last = format_duration_ms(stats.last_ms as f64);
+            if stats.runs == 1 {
+                format!("last run {}", last)
+            } else {
+                format!(
+                    "last run {}, avg {} over {} runs",
+                    last,
+                    format_duration_ms(stats.average_ms() as f64),
+                    stats.runs
+                )
+            }
+        });
+        self.statement_hints = StatementHints {
+            key: Some(key),
+            is_raw,
+            runtime,
+        };
+    }
+
+    async fn execute_query(&mut self) -> Result<()> {
+        if self.settings.editor.trim_trailing_whitespace {
+            self.editor.trim_trailing_whitespace();
+        }
+        let query = self.query_to_execute();
+        if !query.is_empty() {
+            self.executed_flash = self
+                .execution_line_range()
+                .map(|(start, end)| (start, end, Instant::now()));
+        }
+        if is_meta_command(&query) {
+            return self.run_meta_command(&query).await;
+        }
+        self.run_query(query).await
+    }
+
+    /// The selected text when there is a selection, so a fragment or a
+    /// hand-picked statement runs exactly as selected; otherwise the
+    /// statement under the cursor.
+    fn query_to_execute(&self) -> String {
+        match self.editor.get_selected_text() {
+            Some(text) if !text.trim().is_empty() => {
+                text.trim().trim_end_matches(';').trim_end().to_string()
+            }
+            _ => self.get_query_at_cursor(),
+        }
+    }
+
+    /// Editor lines of what `query_to_execute` returns.
+    fn execution_line_range(&self) -> Option<(usize, usize)> {
+        match self.editor.get_selection() {
+            Some(((_, start), (_, end))) => Some((start, end)),
+            None => self.get_current_query_line_range(),
+        }
+    }
+
+    /// Run every statement in the editor one after another, stopping at the
+    /// first error when `query.stop_on_error` is set. A `transactional` run
+    /// wraps them in one transaction and always stops at the first error.
+    async fn execute_all(&mut self, transactional: bool) -> Result<()> {
+        if self.settings.editor.trim_trailing_whitespace {
+            self.editor.trim_trailing_whitespace();
+        }
+        let text = self.editor.text();
+        if self.offline_mode {
+            self.analyze_locally(&text);
+            return Ok(());
+        }
+        if self.pending_query.is_some() || self.batch.is_some() {
+            self.set_status(
+                "A query is already running".to_string(),
+                StatusType::Warning,
+            );
+            return Ok(());
+        }
+        if !self.connection.is_connected() {
+            self.set_status("Not connected to database".to_string(), StatusType::Error);
+            return Ok(());
+        }
+
+        let remaining: VecDeque<String> = statement_texts(&text)
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        if remaining.is_empty() {
+            return Ok(());
+        }
+        if transactional {
+            if remaining.iter().any(|s| controls_transaction(s)) {
+                self.set_status(
+                    "The script begins or ends transactions itself; use execute all".to_string(),
+                    StatusType::Warning,
+                );
+                return Ok(());
+            }
+            let started = Instant::now();
+            let begun = self.connection.begin_transaction().await;
+            let error = begun.as_ref().err().map(ToString::to_string);
+            self.audit_statement("BEGIN", started, error);
+            if let Err(e) = begun {
+                self.set_status(
+                    format!("Could not begin a transaction: {}", e),
+                    StatusType::Error,
+                );
+                return Ok(());
+            }
+        }
+        self.batch = Some(BatchRun {
+            total: remaining.len(),
+            remaining,
+            failed: 0,
+            transactional,
+        });
+        self.run_next_in_batch().await
+    }
+
+    /// Start the next statement of the execute-all run, or report the
+    /// outcome once none are left.
+    async fn run_next_in_batch(&mut self) -> Result<()> {
+        let Some(batch) = self.batch.as_mut() else {
+            return Ok(());
+        };
+        match batch.remaining.pop_front() {
+            Some(query) => self.run_query(query).await,
+            None => {
+                let (total, failed) = (batch.total, batch.failed);
+                let transactional = batch.transactional;
+                self.batch = None;
+                if transactional {
+                    let started = Instant::now();
+                    let committed = self.connection.commit_transaction().await;
+                    let error = committed.as_ref().err().map(ToString::to_string);
+                    self.audit_statement("COMMIT", started, error);
+                    match committed {
+                        Ok(()) => self.set_status(
+                            format!("Ran {} statements and committed", total),
+                            StatusType::Success,
+                        ),
+                        Err(e) => {
+                            self.set_status(format!("Commit failed: {}", e), StatusType::Error)
+                        }
+                    }
+                } else if failed == 0 {
+                    self.set_status(format!("Ran {} statements", total), StatusType::Success);
+                } else {
+                    self.set_status(
+                        format!("Ran {} statements, {} failed", total, failed),
+                        StatusType::Warning,
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Drop the execute-all run, rolling back its transaction if it has one.
+    async fn abort_batch(&mut self) {
+        if let Some(batch) = self.batch.take() {
+            if batch.transactional {
+                let started = Instant::now();
+                let rolled_back = self.connection.rollback_transaction().await;
+                let error = rolled_back.err().map(|e| e.to_string());
+                self.audit_statement("ROLLBACK", started, error);
+            }
+        }
+    }
+
+    /// "Running 3/7..." while an execute-all run is in progress.
+    fn batch_progress(&self) -> Option<String> {
+        self.batch
+            .as_ref()
+            .map(|batch| format!("Running {}/{}...", batch.current(), batch.total))
+    }
+
+    /// Handle a psql-style backslash command client-side: listings run as
+    /// their equivalent catalog query, `\d name` opens the table inspector.
+    async fn run_meta_command(&mut self, input: &str) -> Result<()> {
+        let command = match parse_meta_command(input) {
+            Ok(command) => command,
+            Err(message) => {
+                self.set_status(message, StatusType::Error);
+                return Ok(());
+            }
+        };
+        if !self.connection.is_connected() {
+            self.set_status("Not connected to database".to_string(), StatusType::Error);
+            return Ok(());
+        }
+
+        match command {
+            MetaCommand::Describe { schema, table } => {
+                let schema = schema.unwrap_or_else(|| self.resolve_table_schema(&table));
+                let exists = match self.connection.catalog_client() {
+                    Some(client) => get_columns(client, &schema, &table)
+                        .await
+                        .is_ok_and(|columns| !columns.is_empty()),
+                    None => false,
+                };
+                if exists {
+                    self.open_table_inspector_for(schema, table).await;
+                } else {
+                    self.set_status(
+                        format!("Did not find any relation named \"{}.{}\"", schema, table),
+                        StatusType::Error,
+                    );
+                }
+            }
+            MetaCommand::Connect { database } => {
+                self.switch_database(&database).await?;
+            }
+            MetaCommand::Grep {
+                needle,
+                pattern,
+                include_large,
+            } => {
+                self.grep_tables(&needle, pattern.as_deref(), include_large)
+                    .await?;
+            }
+            other => {
+                if let Some(sql) = other.catalog_query() {
+                    self.run_query_unguarded(sql).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// `\grep`: list the tables to search with their text columns, then run
+    /// one query over all of them. Large tables are skipped with a warning
+    /// unless `include_large` is set.
+    async fn grep_tables(
+        &mut self,
+        needle: &str,
+        pattern: Option<&str>,
+        include_large: bool,
+    ) -> Result<()> {
+        let Some(client) = self.connection.catalog_client() else {
+            return Ok(());
+        };
+        let rows = match client.query(&grep_tables_query(pattern), &[]).await {
+            Ok(rows) => rows,
+            Err(e) => {
+                self.set_status(format!("Failed to list tables: {}", e), StatusType::Error);
+                return Ok(());
+            }
+        };
+
+        let mut tables = Vec::new();
+        let mut skipped = 0;
+        for row in rows {
+            let estimate: Option<i64> = row.get(3);
+            if !include_large && estimate.unwrap_or(0) > GREP_LARGE_TABLE_ROWS {
+                skipped += 1;
+                continue;
+            }
+            tables.push(GrepTable {
+                schema: row.get(0),
+                name: row.get(1),
+                columns: row.get(2),
+            });
+        }
+        if skipped > 0 {
+            self.set_status(
+                format!(
+                    "Skipped {} table(s) over {} rows; run \\grep! to search them too",
+                    skipped,
+                    format_row_count(GREP_LARGE_TABLE_ROWS as usize)
+                ),
+                StatusType::Warning,
+            );
+        }
+        match grep_query(needle, &tables) {
+            Some(sql) => self.run_query_unguarded(sql).await?,
+            None if skipped == 0 => self.set_status(
+                "No tables with text columns to search".to_string(),
+                StatusType::Warning,
+            ),
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// Reconnect to `db_name`, reload the schema browser and remember the
+    /// choice for this connection. Failures are reported as a toast and
+    /// leave the current session in place.
+    async fn switch_database(&mut self, db_name: &str) -> Result<()> {
+        // Switching replaces the session, which would move the rest of a
+        // running batch to the new database and drop an open transaction
+        if self.batch.is_some() || self.pending_query.is_some() || self.connection.in_transaction {
+            self.set_status(
+                format!(
+                    "Can't switch to {} while a query or transaction is running",
+                    db_name
+                ),
+                StatusType::Warning,
+            );
+            return Ok(());
+        }
+        if let Err(e) = self.connection.switch_database(db_name).await {
+            let detail = e.root_cause().to_string();
+            self.set_status(format!("{}: {}", e, detail), StatusType::Error);
+            return Ok(());
+        }
+        self.remember_location();
+        self.refresh_schema();
+        self.set_status(
+            format!("Switched to database: {}", db_name),
+            StatusType::Success,
+        );
+        Ok(())
+    }
+
+    /// Schema an unqualified table name resolves to: the first schema on the
+    /// search_path that has it, else the current schema.
+    fn resolve_table_schema(&self, table: &str) -> String {
+        let search_path = &self.connection.search_path;
+        self.tables
+            .iter()
+            .filter(|t| t.name == table)
+            .filter_map(|t| t.search_path_rank(search_path).map(|rank| (rank, t)))
+            .min_by_key(|(rank, _)| *rank)
+            .map(|(_, t)| t.schema.clone())
+            .unwrap_or_else(|| self.connection.current_schema.clone())
+    }
+
+    /// Pre-flight check: run plain `EXPLAIN` (never ANALYZE) on the query at
+    /// the cursor and toast the planner's top-level cost and row estimate.
+    async fn check_query_cost(&mut self) {
+        let query = self.get_query_at_cursor();
+        let query = query.trim().trim_end_matches(';').trim_end();
+        if query.is_empty() {
+            return;
+        }
+        if is_explain_query(query) {
+            self.set_status(
+                "Query is already an EXPLAIN; run it with F5".to_string(),
+                StatusType::Warning,
+            );
+            return;
+        }
+        // EXPLAIN only covers the first statement; anything after it would run
+        if split_statements(query).len() > 1 {
+            self.set_status(
+                "Cost check needs a single statement".to_string(),
+                StatusType::Warning,
+            );
+            return;
+        }
+        let Some(client) = self.connection.client.clone() else {
+            self.set_status("Not connected to database".to_string(), StatusType::Error);
+            return;
+        };
+
+        // The extended protocol refuses multiple statements, so nothing but
+        // the EXPLAIN can reach the server
+        let explain = format!("EXPLAIN {}", query);
+        let started = Instant::now();
+        let explained = client.query(&explain, &[]).await;
+        let error = explained.as_ref().err().map(ToString::to_string);
+        self.audit_statement(&explain, started, error);
+        let rows = match explained {
+            Ok(rows) => rows,
+            Err(e) => {
+                let err = StructuredError::from_pg_error(&e, query);
+                self.set_status(
+                    format!("Cost check failed: {}", err.message),
+                    StatusType::Error,
+                );
+                return;
+            }
+        };
+        let text = rows
+            .iter()
+            .filter_map(|row| row.try_get::<_, String>(0).ok())
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        match parse_explain_output(&text).and_then(|plan| plan.estimate_summary()) {
+            Some(summary) => self.set_status(summary, StatusType::Info),
+            None => self.set_status(
+                "Could not read an estimate from the plan".to_string(),
+                StatusType::Warning,
+            ),
+        }
+    }
+
+    /// Run the SELECT at the cursor wrapped as `SELECT COUNT(*) FROM (...)`,
+    /// leaving the editor untouched. Its ORDER BY is dropped since the count
+    /// doesn't depend on it.
+    async fn count_query_rows(&mut self) -> Result<()> {
+        let query = self.get_query_at_cursor();
+        let query = query.trim().trim_end_matches(';').trim_end();
+        if query.is_empty() {
+            return Ok(());
+        }
+        let body = without_select_order(query).or_else(|| {
+            // Wrapped as written, for the server to judge, when the ORDER BY
+            // can't be dropped safely
+            let first = query.split_whitespace().next().unwrap_or_default();
+            ["SELECT", "WITH", "VALUES", "TABLE"]
+                .iter()
+                .any(|k| first.eq_ignore_ascii_case(k))
+                .then(|| query.to_string())
+        });
+        let Some(body) = body else {
+            self.set_status(
+                "Only a SELECT can be counted".to_string(),
+                StatusType::Warning,
+            );
+            return Ok(());
+        };
+        self.run_query(format!("SELECT COUNT(*) FROM (\n{}\n) sub", body))
+            .await
+    }
+
+    /// Execute `query` against the current connection and push its result,
+    /// independent of what is in the editor buffer.
+    async fn run_query(&mut self, query: String) -> Result<()> {
+        if query.trim().is_empty() {
+            return Ok(());
+        }
+
+        if self.offline_mode {
+            self.analyze_locally(&query);
+            return Ok(());
+        }
 
-            let client = self.connection.client.as_ref().unwrap();
+        self.warn_before_execute(&query);
+
+        // DROP/TRUNCATE wait for the user to type the target name
+        if self.connection.is_connected() {
+            if let Some(statement) = destructive_statement(&query) {
+                self.destructive_confirm = Some(DestructiveConfirmState {
+                    query,
+                    statement,
+                    input: String::new(),
+                });
+                self.focus = Focus::DestructiveConfirm;
+                return Ok(());
+            }
+        }
 
-            let db_result = get_databases(client).await;
-            let schema_result = get_schemas(client).await;
+        self.run_query_unguarded(query).await
+    }
 
-            let schemas_for_tables = match &schema_result {
-                Ok(s) => s.clone(),
-                Err(_) => Vec::new(),
-            };
-            let mut all_tables = Vec::new();
-            for schema in &schemas_for_tables {
-                if let Ok(tables) = get_tables(client, &schema.name).await {
-                    all_tables.extend(tables);
+    /// Toast the high-severity analysis warnings for `query`, such as an
+    /// implicit cross join, as it is sent. Unparsable SQL is left to the
+    /// server to report.
+    fn warn_before_execute(&mut self, query: &str) {
+        let Ok(statements) = parse_sql(query) else {
+            return;
+        };
+        for statement in &statements {
+            for warning in analyze_query(statement).warnings {
+                if warning.severity == WarningSeverity::High {
+                    self.set_status(warning.message, StatusType::Warning);
                 }
             }
+        }
+    }
 
-            // All client usage is done above; now we can mutably borrow self
-            let databases = match db_result {
-                Ok(dbs) => dbs,
-                Err(e) => {
-                    self.set_status(
-                        format!("Failed to load databases: {}", e),
-                        StatusType::Warning,
-                    );
-                    Vec::new()
-                }
-            };
-            let schemas = match schema_result {
-                Ok(s) => s,
-                Err(e) => {
-                    self.set_status(
-                        format!("Failed to load schemas: {}", e),
-                        StatusType::Warning,
-                    );
-                    Vec::new()
-                }
-            };
-
-            self.databases = databases;
-            self.schemas = schemas;
-            self.tables = all_tables;
-            self.stop_loading();
+    /// Execute `query` without the destructive-statement confirmation. The
+    /// query runs on a background task; `tick` collects the result.
+    async fn run_query_unguarded(&mut self, query: String) -> Result<()> {
+        if self.pending_query.is_some() {
+            self.set_status(
+                "A query is already running".to_string(),
+                StatusType::Warning,
+            );
+        } else if self.connection.client.is_some() {
+            let message = self
+                .batch_progress()
+                .unwrap_or_else(|| "Executing query...".to_string());
+            self.start_loading(message);
+            self.spawn_query(query, 0, Duration::ZERO);
+        } else {
+            self.set_status("Not connected to database".to_string(), StatusType::Error);
         }
+
         Ok(())
     }
 
-    /// Get the byte offset of the cursor in the full editor text.
-    fn get_cursor_offset(&self) -> usize {
-        let mut offset = 0;
-        for (i, line) in self.editor.lines.iter().enumerate() {
-            if i == self.editor.cursor_y {
-                offset += self.editor.cursor_x;
-                break;
+    fn spawn_query(&mut self, query: String, attempt: u32, delay: Duration) {
+        let Some(client) = self.connection.client.clone() else {
+            return;
+        };
+        let rows_received = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&rows_received);
+        let sql = query.clone();
+        let handle = tokio::spawn(async move {
+            if !delay.is_zero() {
+                tokio::time::sleep(delay).await;
             }
-            offset += line.len() + 1; // +1 for newline
-        }
-        offset
+            execute_query(&client, &sql, &counter).await
+        });
+        self.pending_query = Some(PendingQuery {
+            query,
+            attempt,
+            rows_received,
+            started: Instant::now(),
+            handle,
+        });
     }
 
-    /// Find the query at the current cursor position.
-    /// Splits on `;` while respecting string literals and comments.
-    fn get_query_at_cursor(&self) -> String {
-        let full_text = self.editor.text();
-        let cursor_offset = self.get_cursor_offset();
-
-        let boundaries = Self::find_query_boundaries(&full_text);
-        for (start, end) in &boundaries {
-            if cursor_offset >= *start && cursor_offset <= *end {
-                return full_text[*start..*end].trim().to_string();
-            }
+    /// Record `query` and its outcome in the audit log when one is
+    /// configured for the current connection.
+    fn audit(&mut self, query: &str, result: &QueryResult) {
+        let Some(path) = self.settings.audit.log_path(&self.connection.config) else {
+            return;
+        };
+        let record = AuditRecord::new(&self.connection.config, query, result);
+        if let Err(e) = crate::audit::append(&path, &record) {
+            self.set_status(
+                format!("Could not write audit log {}: {}", path.display(), e),
+                StatusType::Error,
+            );
         }
+    }
 
-        // Fallback to full text
-        full_text.trim().to_string()
+    /// Audit a statement pgrsql sent on its own (transaction control,
+    /// EXPLAIN, counts) rather than through `finish_query`.
+    fn audit_statement(&mut self, query: &str, started: Instant, error: Option<String>) {
+        let result = match error {
+            Some(message) => {
+                QueryResult::error(StructuredError::from_string(message), started.elapsed())
+            }
+            None => {
+                let mut result = QueryResult::empty();
+                result.execution_time = started.elapsed();
+                result
+            }
+        };
+        self.audit(query, &result);
     }
 
-    /// Returns (start_line, end_line) of the query block at the cursor,
-    /// for visual highlighting in the editor.
-    pub fn get_current_query_line_range(&self) -> Option<(usize, usize)> {
-        let full_text = self.editor.text();
-        let cursor_offset = self.get_cursor_offset();
+    /// Whether a failed keepalive ping means the session itself is gone:
+    /// the socket closed, or the server reported a connection exception
+    /// (SQLSTATE class 08). A slow server or an aborted transaction is not.
+    fn session_lost(&self, error: &anyhow::Error) -> bool {
+        let closed = self
+            .connection
+            .client
+            .as_ref()
+            .is_some_and(|c| c.is_closed());
+        let connection_state = error
+            .downcast_ref::<tokio_postgres::Error>()
+            .and_then(|e| e.code())
+            .is_some_and(|code| code.code().starts_with("08"));
+        closed || connection_state
+    }
 
-        let boundaries = Self::find_query_boundaries(&full_text);
-        for (start, end) in &boundaries {
-            if cursor_offset >= *start && cursor_offset <= *end {
-                // Convert byte offsets to line numbers
-                let start_line = full_text[..*start].matches('\n').count();
-                let end_line = full_text[..*end].matches('\n').count();
-                return Some((start_line, end_line));
+    /// Handle a completed query: retry transient failures, otherwise record
+    /// it in history and show the result.
+    async fn finish_query(
+        &mut self,
+        query: String,
+        attempt: u32,
+        result: QueryResult,
+    ) -> Result<()> {
+        let retries = self.settings.query.retry_attempts;
+        // A statement can't be retried alone inside an aborted transaction
+        let in_transaction = self.batch.as_ref().is_some_and(|b| b.transactional);
+        let retryable = match &result.error {
+            Some(err) if err.code == "40001" => true,
+            // Whether a write reached the server is unknown after a lost
+            // connection, and a new session has none of the user's
+            // transaction, so only plain reads are run again
+            Some(err) if err.category == ErrorCategory::Connection => {
+                !self.connection.in_transaction && is_read_only_query(&query)
+            }
+            _ => false,
+        };
+        if retryable && attempt < retries && !in_transaction {
+            let attempt = attempt + 1;
+            self.set_status(
+                format!("Transient error, retrying ({}/{})...", attempt, retries),
+                StatusType::Warning,
+            );
+
+            // A dropped connection is re-established first; tick retries
+            // once it is back
+            let closed = self
+                .connection
+                .client
+                .as_ref()
+                .is_some_and(|c| c.is_closed());
+            if closed {
+                self.start_reconnect(Some((query, attempt, result)));
+            } else {
+                let delay = self.settings.query.retry_delay(attempt);
+                self.spawn_query(query, attempt, delay);
             }
+            return Ok(());
         }
-        None
+        self.complete_query(query, result).await
     }
 
-    /// Find all query boundaries in the text, returning (start, end) byte offsets.
-    /// Respects single-quoted strings, double-quoted identifiers, and `--` line comments.
-    fn find_query_boundaries(text: &str) -> Vec<(usize, usize)> {
-        let mut boundaries = Vec::new();
-        let mut start = 0;
-        let mut in_single_quote = false;
-        let mut in_double_quote = false;
-        let mut in_line_comment = false;
-        let mut in_block_comment = false;
-        let chars: Vec<char> = text.chars().collect();
-        let len = chars.len();
-        let mut byte_pos = 0;
-        let mut i = 0;
-
-        while i < len {
-            let c = chars[i];
-            let c_len = c.len_utf8();
-
-            if in_line_comment {
-                if c == '\n' {
-                    in_line_comment = false;
-                }
-            } else if in_block_comment {
-                if c == '*' && i + 1 < len && chars[i + 1] == '/' {
-                    in_block_comment = false;
-                    i += 1;
-                    byte_pos += chars[i].len_utf8();
-                }
-            } else if in_single_quote {
-                if c == '\'' {
-                    // Handle escaped quotes ('')
-                    if i + 1 < len && chars[i + 1] == '\'' {
-                        i += 1;
-                        byte_pos += chars[i].len_utf8();
-                    } else {
-                        in_single_quote = false;
-                    }
-                }
-            } else if in_double_quote {
-                if c == '"' {
-                    in_double_quote = false;
-                }
-            } else {
-                match c {
-                    '\'' => in_single_quote = true,
-                    '"' => in_double_quote = true,
-                    '-' if i + 1 < len && chars[i + 1] == '-' => {
-                        in_line_comment = true;
-                    }
-                    '/' if i + 1 < len && chars[i + 1] == '*' => {
-                        in_block_comment = true;
-                        i += 1;
-                        byte_pos += chars[i].len_utf8();
-                    }
-                    ';' => {
-                        let end = byte_pos;
-                        if !text[start..end].trim().is_empty() {
-                            boundaries.push((start, end));
-                        }
-                        start = byte_pos + c_len;
+    /// Open a replacement for the lost session on a background task; `tick`
+    /// applies it and re-runs `retry`, if any.
+    fn start_reconnect(&mut self, retry: Option<(String, u32, QueryResult)>) {
+        if self.pending_reconnect.is_some() {
+            return;
+        }
+        let config = self.connection.config.clone();
+        let notices = self.connection.notice_sender();
+        let handle = tokio::spawn(async move { create_client(&config, Some(notices)).await });
+        self.pending_reconnect = Some(PendingReconnect { handle, retry });
+    }
+
+    /// Apply a finished reconnect: restore the schema on the new session,
+    /// then re-run the query waiting on it or report the lost session.
+    async fn finish_reconnect(&mut self, reconnect: PendingReconnect, client: Result<Client>) {
+        let client = match client {
+            Ok(client) => client,
+            Err(e) => {
+                match reconnect.retry {
+                    Some((query, _, failed)) => {
+                        let _ = self.complete_query(query, failed).await;
                     }
-                    _ => {}
+                    None => self.set_status(
+                        format!("Connection lost; reconnect failed: {}", e),
+                        StatusType::Error,
+                    ),
                 }
+                return;
             }
-
-            byte_pos += c_len;
-            i += 1;
+        };
+        let config = self.connection.config.clone();
+        let schema = self.connection.current_schema.clone();
+        self.connection.apply_client(config, client);
+        let _ = self.connection.load_server_info().await;
+        self.connection.open_catalog_client();
+        if self.connection.current_schema != schema {
+            let _ = self.connection.switch_schema(&schema).await;
+        }
+        match reconnect.retry {
+            Some((query, attempt, _)) => {
+                let delay = self.settings.query.retry_delay(attempt);
+                self.spawn_query(query, attempt, delay);
+            }
+            None => self.set_status(
+                "Connection was lost; reconnected (session settings were reset)".to_string(),
+                StatusType::Warning,
+            ),
         }
+    }
 
-        // Last query (after final `;` or if no `;` at all)
-        if start < text.len() && !text[start..].trim().is_empty() {
-            boundaries.push((start, text.len()));
+    /// Record a query that won't be retried in history and the audit log,
+    /// and show its result.
+    async fn complete_query(&mut self, query: String, result: QueryResult) -> Result<()> {
+        self.stop_loading();
+        self.last_query_at = Some(chrono::Local::now());
+
+        // Track search_path changes so the schema is remembered per connection
+        let upper = query.trim_start().to_uppercase();
+        if result.error.is_none()
+            && (upper.starts_with("SET SEARCH_PATH") || upper.starts_with("SET SCHEMA"))
+        {
+            let _ = self.connection.refresh_current_schema().await;
+            self.remember_location();
         }
 
-        // If empty, treat entire text as one query
-        if boundaries.is_empty() && !text.trim().is_empty() {
-            boundaries.push((0, text.len()));
+        // Add to history
+        let entry = HistoryEntry {
+            query: query.clone(),
+            timestamp: chrono::Utc::now(),
+            database: self.connection.current_database.clone(),
+            execution_time_ms: result.execution_time.as_millis() as u64,
+            success: result.error.is_none(),
+        };
+        self.query_history.add(entry);
+        // The runtime hint now has another run to report
+        self.statement_hints.key = None;
+        let _ = self.query_history.save();
+        self.audit(&query, &result);
+        if result.error.is_none() && controls_transaction(&query) {
+            self.connection.in_transaction = opens_transaction(&query);
         }
 
-        boundaries
-    }
+        // Update status; execute-all runs only report errors per statement
+        let size = result.approx_bytes();
+        let failed = result.error.is_some();
+        if let Some(err) = &result.error {
+            self.set_status(
+                format!("{}: {}", err.category, err.message),
+                StatusType::Error,
+            );
+        } else if self.batch.is_some() {
+            // Progress is shown in the loading indicator instead
+        } else if let Some(affected) = result.affected_rows {
+            self.set_status(
+                format!(
+                    "{} rows affected ({:.2}ms)",
+                    affected,
+                    result.execution_time.as_secs_f64() * 1000.0
+                ),
+                StatusType::Success,
+            );
+        } else {
+            self.set_status(
+                format!(
+                    "{} rows returned, ~{} ({:.2}ms)",
+                    result.row_count,
+                    format_bytes(size),
+                    result.execution_time.as_secs_f64() * 1000.0
+                ),
+                StatusType::Success,
+            );
+        }
+        let notices = self.connection.drain_notices();
+        // An error result shows the error instead of its notices
+        self.show_notices(&notices, !failed);
+
+        // Parse EXPLAIN plan if applicable
+        let plan = if is_explain_query(&query) {
+            // Build the text output from the result rows
+            let text: String = result
+                .rows
+                .iter()
+                .filter_map(|row| row.first().map(|cell| cell.display()))
+                .collect::<Vec<String>>()
+                .join("\n");
+            parse_explain_output(&text)
+        } else {
+            None
+        };
 
-    async fn execute_query(&mut self) -> Result<()> {
-        let query = self.get_query_at_cursor();
-        if query.trim().is_empty() {
-            return Ok(());
+        let source_table = single_source_table(&query);
+
+        // Tell apart columns sharing a name, e.g. `id` from both sides of a join
+        let qualifiers = parse_single(&query)
+            .ok()
+            .and_then(|q| q.projection_qualifiers());
+        let labels = result.column_labels(qualifiers.as_deref());
+
+        // Compare with the previous run of the same query
+        let key = query.trim().to_string();
+        let changes = self
+            .last_result_by_query
+            .get(&key)
+            .and_then(|&index| self.results.get(index))
+            .and_then(|before| result.cell_changes(&before.result));
+        if !failed && !result.columns.is_empty() {
+            self.last_result_by_query.insert(key, self.results.len());
         }
 
-        if self.connection.client.is_some() {
-            self.start_loading("Executing query...".to_string());
+        self.show_visual_plan = plan.is_some();
+        self.results.push(ResultEntry {
+            result,
+            query: query.clone(),
+            plan,
+            source_table,
+            notices,
+            row_estimate: None,
+            size,
+            column_view: ColumnView::with_labels(labels),
+            position: ResultPosition::default(),
+            changes,
+        });
+        self.start_row_estimate(&query, self.results.len() - 1);
+        self.show_result(self.results.len() - 1);
+
+        if let Some(batch) = self.batch.as_mut() {
+            if failed {
+                batch.failed += 1;
+                if batch.transactional {
+                    let message = format!(
+                        "Statement {}/{} failed; rolled back the transaction",
+                        batch.current(),
+                        batch.total
+                    );
+                    self.abort_batch().await;
+                    self.set_status(message, StatusType::Error);
+                    return Ok(());
+                }
+                if self.settings.query.stop_on_error {
+                    let message = format!(
+                        "Stopped at statement {}/{}; {} not run",
+                        batch.current(),
+                        batch.total,
+                        batch.remaining.len()
+                    );
+                    self.batch = None;
+                    self.set_status(message, StatusType::Error);
+                    return Ok(());
+                }
+            }
+            self.run_next_in_batch().await?;
+        }
 
-            let client = self.connection.client.as_ref().unwrap();
-            let result = execute_query(client, &query).await?;
-            self.stop_loading();
+        Ok(())
+    }
 
-            // Add to history
-            let entry = HistoryEntry {
-                query: query.clone(),
-                timestamp: chrono::Utc::now(),
-                database: self.connection.current_database.clone(),
-                execution_time_ms: result.execution_time.as_millis() as u64,
-                success: result.error.is_none(),
-            };
-            self.query_history.add(entry);
-            let _ = self.query_history.save();
+    /// Toast server notices: one alone as is, several as a count pointing at
+    /// the results panel when `listed` there, else with the latest one.
+    fn show_notices(&mut self, notices: &[Notice], listed: bool) {
+        let Some(latest) = notices.last() else {
+            return;
+        };
+        let status = if notices.iter().any(|n| n.severity == "WARNING") {
+            StatusType::Warning
+        } else {
+            StatusType::Info
+        };
+        let message = match (notices.len(), listed) {
+            (1, _) => latest.to_string(),
+            (n, true) => format!("{} notices (see the results panel)", n),
+            (n, false) => format!("{} notices, latest: {}", n, latest),
+        };
+        self.set_status(message, status);
+    }
 
-            // Update status
-            if let Some(err) = &result.error {
-                self.set_status(
-                    format!("{}: {}", err.category, err.message),
-                    StatusType::Error,
-                );
-            } else if let Some(affected) = result.affected_rows {
-                self.set_status(
-                    format!(
-                        "{} rows affected ({:.2}ms)",
-                        affected,
-                        result.execution_time.as_secs_f64() * 1000.0
-                    ),
-                    StatusType::Success,
-                );
-            } else {
+    /// Offline counterpart of `run_query`: parse the query and show the AST
+    /// analysis in the results panel without touching the database.
+    fn analyze_locally(&mut self, query: &str) {
+        let result = match parse_sql(query) {
+            Ok(statements) => {
+                let mut rows = Vec::new();
+                let mut warning_count = 0;
+                for (i, statement) in statements.iter().enumerate() {
+                    let n = (i + 1).to_string();
+                    let analysis = analyze_query(statement);
+                    rows.push(vec![n.clone(), "kind".into(), statement.kind().into()]);
+                    let features = analysis.features();
+                    if !features.is_empty() {
+                        rows.push(vec![n.clone(), "features".into(), features.join(", ")]);
+                    }
+                    if analysis.join_count > 0 {
+                        rows.push(vec![
+                            n.clone(),
+                            "joins".into(),
+                            analysis.join_count.to_string(),
+                        ]);
+                    }
+                    warning_count += analysis.warnings.len();
+                    for warning in analysis.warnings {
+                        rows.push(vec![n.clone(), "warning".into(), warning.message]);
+                    }
+                }
+                let status_type = if warning_count > 0 {
+                    StatusType::Warning
+                } else {
+                    StatusType::Success
+                };
                 self.set_status(
                     format!(
-                        "{} rows returned ({:.2}ms)",
-                        result.row_count,
-                        result.execution_time.as_secs_f64() * 1000.0
+                        "Parsed {} statement(s), {} warning(s)",
+                        statements.len(),
+                        warning_count
                     ),
-                    StatusType::Success,
+                    status_type,
                 );
+                QueryResult::from_text_rows(&["#", "property", "value"], rows)
             }
+            Err(e) => {
+                let mut err = StructuredError::from_string(e.to_string());
+                err.category = ErrorCategory::Syntax;
+                self.set_status(err.message.clone(), StatusType::Error);
+                QueryResult::error(err, Duration::ZERO)
+            }
+        };
 
-            // Parse EXPLAIN plan if applicable
-            let plan = if is_explain_query(&query) {
-                // Build the text output from the result rows
-                let text: String = result
-                    .rows
-                    .iter()
-                    .filter_map(|row| row.first().map(|cell| cell.display()))
-                    .collect::<Vec<String>>()
-                    .join("\n");
-                parse_explain_output(&text)
-            } else {
-                None
-            };
+        self.results
+            .push(ResultEntry::new(query.to_string(), result));
+        self.show_result(self.results.len() - 1);
+    }
 
-            self.results.push(result);
-            self.explain_plans.push(plan);
-            self.current_result = self.results.len() - 1;
-            self.result_selected_row = 0;
-            self.result_selected_col = 0;
-            self.plan_scroll = 0;
-            self.show_visual_plan = self
-                .explain_plans
-                .last()
-                .map(|p| p.is_some())
-                .unwrap_or(false);
-        } else {
-            self.set_status("Not connected to database".to_string(), StatusType::Error);
+    /// Refresh the autocomplete popup for the word at the cursor. `manual`
+    /// is set for Ctrl+Space, which works even with autocomplete while
+    /// typing turned off and from the first character.
+    fn update_autocomplete(&mut self, manual: bool) {
+        // Once open, the popup keeps filtering as the user types
+        let open = manual || self.autocomplete.active;
+        if !open && !self.autocomplete_while_typing {
+            return;
         }
+        let min_chars = if open {
+            1
+        } else {
+            self.settings.editor.autocomplete_min_chars.max(1)
+        };
 
-        Ok(())
-    }
-
-    fn update_autocomplete(&mut self) {
         let line = self.editor.current_line().to_string();
         let cursor_x = self.editor.cursor_x;
 
@@ -1719,7 +4332,17 @@ impl App {
             .unwrap_or(0);
         let prefix = &before_cursor[prefix_start..];
 
-        if prefix.len() < 2 {
+        // Inside `col = '...` or `col LIKE '...`: offer values from the result
+        if let Some((column, ilike, literal)) = value_completion_context(before_cursor) {
+            let suggestions = self.value_suggestions(column, ilike, literal);
+            self.autocomplete.active = !suggestions.is_empty();
+            self.autocomplete.suggestions = suggestions;
+            self.autocomplete.selected = 0;
+            self.autocomplete.prefix = literal.to_string();
+            return;
+        }
+
+        if prefix.chars().count() < min_chars {
             self.autocomplete.active = false;
             return;
         }
@@ -1729,22 +4352,36 @@ impl App {
 
         let mut suggestions: Vec<AutocompleteSuggestion> = Vec::new();
 
-        // Table names from loaded schema (schema-qualified)
+        // Table names from loaded schema. Tables on the search_path come
+        // first and complete unqualified unless another schema shadows them;
+        // an explicit `schema.` prefix always completes qualified.
+        let search_path = &self.connection.search_path;
+        let mut matching: Vec<&TableInfo> = self
+            .tables
+            .iter()
+            .filter(|table| {
+                // Match on bare table name OR schema.table qualified name
+                table.name.to_lowercase().starts_with(&prefix_lower)
+                    || table
+                        .qualified_name()
+                        .to_lowercase()
+                        .starts_with(&prefix_lower)
+            })
+            .collect();
+        matching.sort_by_key(|table| table.search_path_rank(search_path).unwrap_or(usize::MAX));
+
         let mut seen_tables = std::collections::HashSet::new();
-        for table in &self.tables {
-            let qualified = format!("{}.{}", table.schema, table.name);
-            if seen_tables.contains(&qualified) {
-                continue;
-            }
-            // Match on bare table name OR schema.table qualified name
-            if table.name.to_lowercase().starts_with(&prefix_lower)
-                || qualified.to_lowercase().starts_with(&prefix_lower)
-            {
+        for table in matching {
+            let text = if prefix.contains('.') {
+                table.qualified_name()
+            } else {
+                table.completion_name(&self.tables, search_path)
+            };
+            if seen_tables.insert(text.clone()) {
                 suggestions.push(AutocompleteSuggestion {
-                    text: qualified.clone(),
+                    text,
                     kind: SuggestionKind::Table,
                 });
-                seen_tables.insert(qualified);
             }
         }
 
@@ -1791,6 +4428,46 @@ impl App {
         }
     }
 
+    /// Distinct values of `column` in the current result that start with
+    /// `prefix` (ignoring case for ILIKE), quoted for a SQL string literal.
+    fn value_suggestions(
+        &self,
+        column: &str,
+        ilike: bool,
+        prefix: &str,
+    ) -> Vec<AutocompleteSuggestion> {
+        let Some(result) = self.current_query_result() else {
+            return Vec::new();
+        };
+        let Some(index) = result
+            .columns
+            .iter()
+            .position(|c| c.name.eq_ignore_ascii_case(column))
+        else {
+            return Vec::new();
+        };
+
+        let prefix_lower = prefix.to_lowercase();
+        let mut seen = std::collections::HashSet::new();
+        result
+            .rows
+            .iter()
+            .filter_map(|row| row.get(index))
+            .filter(|cell| !matches!(cell, CellValue::Null))
+            .map(CellValue::display)
+            .filter(|value| match ilike {
+                true => value.to_lowercase().starts_with(&prefix_lower),
+                false => value.starts_with(prefix),
+            })
+            .filter(|value| seen.insert(value.clone()))
+            .take(10)
+            .map(|value| AutocompleteSuggestion {
+                text: value.replace('\'', "''"),
+                kind: SuggestionKind::Value,
+            })
+            .collect()
+    }
+
     fn accept_autocomplete(&mut self) {
         if let Some(suggestion) = self
             .autocomplete
@@ -1798,21 +4475,109 @@ impl App {
             .get(self.autocomplete.selected)
         {
             let text = suggestion.text.clone();
-            let prefix_len = self.autocomplete.prefix.len();
+            let kind = suggestion.kind;
+            let prefix_len = self.autocomplete.prefix.chars().count();
 
             // Delete the prefix
             for _ in 0..prefix_len {
                 self.editor.backspace();
             }
 
-            // Insert the suggestion
-            self.editor.insert_text(&text);
+            // Insert the suggestion, with placeholder arguments for
+            // functions that have a known signature
+            let known = match kind {
+                SuggestionKind::Function => function_signature(text.trim_end_matches("()")),
+                _ => None,
+            };
+            match known.filter(|signature| signature.has_placeholders()) {
+                Some(signature) => {
+                    let open = self.editor.cursor_x + signature.name.len();
+                    self.editor.insert_text(&format!(
+                        "{}({})",
+                        signature.name,
+                        signature.args.join(", ")
+                    ));
+                    self.snippet = Some(SignatureSnippet {
+                        y: self.editor.cursor_y,
+                        open,
+                        current: 0,
+                    });
+                    self.select_snippet_argument();
+                }
+                None => {
+                    self.editor.insert_text(&text);
+                    // Leave the cursor inside the parentheses unless the
+                    // function is known to take no arguments
+                    if kind == SuggestionKind::Function
+                        && known.is_none_or(|signature| !signature.args.is_empty())
+                    {
+                        self.editor.enter_inserted_pair();
+                    }
+                }
+            }
+
+            // Close the string literal a value completes
+            let line = self.editor.current_line();
+            if kind == SuggestionKind::Value && !line[self.editor.cursor_x..].starts_with('\'') {
+                self.editor.insert_char('\'');
+            }
         }
         self.autocomplete.active = false;
     }
 
+    /// Signature of the function call the cursor is inside, and the index of
+    /// the argument being typed, for the editor's hint line.
+    pub fn signature_hint(&self) -> Option<(&'static FunctionSignature, usize)> {
+        let y = self.editor.cursor_y;
+        let line = &self.editor.lines[y];
+        let mut before = self.editor.lines[..y].join("\n");
+        if y > 0 {
+            before.push('\n');
+        }
+        before.push_str(&line[..self.editor.cursor_x.min(line.len())]);
+        enclosing_call(&before)
+    }
+
+    /// Select the current placeholder of the function call snippet. Past
+    /// the last one, move the cursor after the `)` and end the snippet.
+    fn select_snippet_argument(&mut self) {
+        let Some(snippet) = self.snippet else {
+            return;
+        };
+        let Some((ranges, close)) = self
+            .editor
+            .lines
+            .get(snippet.y)
+            .and_then(|line| argument_ranges(line, snippet.open))
+        else {
+            self.snippet = None;
+            return;
+        };
+        self.editor.cursor_y = snippet.y;
+        match ranges.get(snippet.current) {
+            Some(&(start, end)) => {
+                self.editor.selection_start = Some((start, snippet.y));
+                self.editor.cursor_x = end;
+            }
+            None => {
+                self.editor.clear_selection();
+                self.editor.cursor_x = close + 1;
+                self.snippet = None;
+            }
+        }
+    }
+
     fn copy_selected_cell(&mut self) {
-        if let Some(result) = self.results.get(self.current_result) {
+        if let Some(result) = self.current_query_result() {
+            // Error results have no cells; copy the full error report instead
+            if let Some(error) = &result.error {
+                let text = error.display_full();
+                if let Ok(mut clipboard) = arboard::Clipboard::new() {
+                    let _ = clipboard.set_text(&text);
+                    self.set_status("Error copied to clipboard".to_string(), StatusType::Info);
+                }
+                return;
+            }
             if let Some(row) = result.rows.get(self.result_selected_row) {
                 if let Some(cell) = row.get(self.result_selected_col) {
                     let text = cell.display();
@@ -1847,12 +4612,182 @@ impl App {
     pub async fn tick(&mut self) -> Result<()> {
         // Remove expired toasts
         self.toasts.retain(|t| !t.is_expired());
+        if self
+            .executed_flash
+            .is_some_and(|(.., at)| at.elapsed() >= EXECUTE_FLASH)
+        {
+            self.executed_flash = None;
+        }
+
+        // Follow the environment of the live connection, across connects,
+        // disconnects and theme switches
+        self.theme.environment = self
+            .connection
+            .config
+            .environment
+            .filter(|_| self.connection.is_connected())
+            .map(|env| self.theme.environment_color(env));
 
         // Advance spinner frame when loading
         if self.is_loading {
             self.spinner_frame = (self.spinner_frame + 1) % SPINNER_FRAMES.len();
         }
 
+        self.connection.poll_catalog_client().await;
+        self.refresh_statement_hints();
+
+        // Periodically measure round-trip latency while idle; with a
+        // keepalive configured, a failed ping also reconnects
+        let keepalive = self.settings.connection.keepalive();
+        let interval = keepalive.map_or(PING_INTERVAL, |k| k.min(PING_INTERVAL));
+        if self.pending_ping.as_ref().is_some_and(|h| h.is_finished()) {
+            let handle = self.pending_ping.take().unwrap();
+            let pinged = handle.await.unwrap_or_else(|e| Err(anyhow::anyhow!(e)));
+            self.connection.record_ping(&pinged);
+            // Reconnecting would silently drop an open transaction
+            let in_transaction = self.connection.in_transaction
+                || self.batch.as_ref().is_some_and(|b| b.transactional);
+            if let (Err(e), Some(_), false) = (&pinged, keepalive, in_transaction) {
+                if self.session_lost(e) {
+                    self.start_reconnect(None);
+                }
+            }
+        }
+        if self.pending_ping.is_none()
+            && !self.is_loading
+            && self.connection.is_connected()
+            && self.connection.ping_due(interval)
+        {
+            self.pending_ping = self.connection.start_ping();
+        }
+
+        // Notices raised outside a query, e.g. while loading the schema
+        if self.pending_query.is_none() {
+            let notices = self.connection.drain_notices();
+            self.show_notices(&notices, false);
+        }
+
+        // Poll pending query task, reporting rows streamed in so far
+        if let Some(pending) = &self.pending_query {
+            if pending.handle.is_finished() {
+                let pending = self.pending_query.take().unwrap();
+                match pending.handle.await {
+                    Ok(Ok(result)) => {
+                        self.finish_query(pending.query, pending.attempt, result)
+                            .await?;
+                    }
+                    Ok(Err(e)) => {
+                        self.stop_loading();
+                        let failed = QueryResult::error(
+                            StructuredError::from_string(e.to_string()),
+                            pending.started.elapsed(),
+                        );
+                        self.audit(&pending.query, &failed);
+                        self.abort_batch().await;
+                        self.set_status(format!("Query failed: {}", e), StatusType::Error);
+                    }
+                    Err(e) => {
+                        self.stop_loading();
+                        let failed = QueryResult::error(
+                            StructuredError::from_string(e.to_string()),
+                            pending.started.elapsed(),
+                        );
+                        self.audit(&pending.query, &failed);
+                        self.abort_batch().await;
+                        self.set_status(format!("Query task failed: {}", e), StatusType::Error);
+                    }
+                }
+            } else {
+                let rows = pending.rows_received.load(Ordering::Relaxed);
+                if rows > 0 {
+                    let progress = self
+                        .batch_progress()
+                        .unwrap_or_else(|| "Executing query...".to_string());
+                    self.loading_message =
+                        format!("{} {} rows so far", progress, format_row_count(rows));
+                }
+            }
+        }
+
+        // Poll background schema load
+        if self
+            .pending_schema
+            .as_ref()
+            .is_some_and(|h| h.is_finished())
+        {
+            let handle = self.pending_schema.take().unwrap();
+            match handle.await {
+                Ok(load) => self.apply_schema_load(load),
+                Err(e) => {
+                    self.set_status(format!("Schema load failed: {}", e), StatusType::Warning)
+                }
+            }
+            if self.pending_query.is_none() {
+                self.stop_loading();
+            }
+        }
+
+        // Poll row count started from the sidebar
+        if let Some((name, started, handle)) = self
+            .pending_count
+            .take_if(|(.., handle)| handle.is_finished())
+        {
+            let counted = handle.await.unwrap_or_else(|e| Err(anyhow::anyhow!(e)));
+            let error = counted.as_ref().err().map(ToString::to_string);
+            self.audit_statement(&table_count_sql(&name), started, error);
+            match counted {
+                Ok(count) => self.set_status(
+                    format!("{}: {} rows", name, format_row_count(count as usize)),
+                    StatusType::Success,
+                ),
+                Err(e) => self.set_status(
+                    format!("Failed to count rows in {}: {}", name, e),
+                    StatusType::Error,
+                ),
+            }
+        }
+
+        // Poll the row estimate of a LIMITed result
+        if let Some(pending) = self
+            .pending_estimate
+            .take_if(|pending| pending.handle.is_finished())
+        {
+            let estimated = pending
+                .handle
+                .await
+                .unwrap_or_else(|e| Err(anyhow::anyhow!(e)));
+            let error = estimated.as_ref().err().map(ToString::to_string);
+            self.audit_statement(&pending.explain, pending.started, error);
+            // A failed estimate only means no note is shown
+            if let Ok(Some(rows)) = estimated {
+                if let Some(entry) = self.results.get_mut(pending.index) {
+                    entry.row_estimate = Some(rows);
+                }
+            }
+        }
+
+        // Poll connection test started from the dialog
+        if self.pending_test.as_ref().is_some_and(|h| h.is_finished()) {
+            let handle = self.pending_test.take().unwrap();
+            self.connection_dialog.status_message = Some(match handle.await {
+                Ok(Ok(server)) => (format!("Connection OK: {}", server), StatusType::Success),
+                Ok(Err(e)) => (format!("Connection failed: {}", e), StatusType::Error),
+                Err(e) => (format!("Connection test failed: {}", e), StatusType::Error),
+            });
+        }
+
+        if self
+            .pending_reconnect
+            .as_ref()
+            .is_some_and(|r| r.handle.is_finished())
+        {
+            let mut reconnect = self.pending_reconnect.take().unwrap();
+            let client = (&mut reconnect.handle)
+                .await
+                .unwrap_or_else(|e| Err(anyhow::anyhow!(e)));
+            self.finish_reconnect(reconnect, client).await;
+        }
+
         // Poll pending connection task
         if let Some((_, handle)) = &self.pending_connection {
             if handle.is_finished() {
@@ -1883,6 +4818,11 @@ impl App {
     }
 }
 
+/// The exact count run for a sidebar table's qualified `name`.
+fn table_count_sql(name: &str) -> String {
+    format!("SELECT COUNT(*) FROM {}", name)
+}
+
 fn dialog_field_len(config: &ConnectionConfig, field_index: usize) -> usize {
     match field_index {
         0 => config.name.len(),
@@ -1894,3 +4834,22 @@ fn dialog_field_len(config: &ConnectionConfig, field_index: usize) -> usize {
         _ => 0,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_switch_database_refused_in_transaction() {
+        let mut app = App::new();
+        app.toasts.clear();
+        app.connection.in_transaction = true;
+        app.switch_database("other").await.unwrap();
+        let toast = app.toasts.last().unwrap();
+        assert!(matches!(toast.status_type, StatusType::Warning));
+        assert!(toast
+            .message
+            .contains("while a query or transaction is running"));
+        assert!(app.connection.in_transaction);
+    }
+}
@@ -0,0 +1,268 @@
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+use ratatui::style::{Color, Modifier, Style};
+use serde::{Deserialize, Serialize};
+
+use crate::db::CellValue;
+
+/// A `[[format_rules]]` entry of `config.toml`: cells of matching columns
+/// that satisfy `when` are drawn in `color`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FormatRule {
+    /// Column name, or a pattern with `*` and `?` wildcards. Matching
+    /// ignores case; empty matches every column.
+    #[serde(default)]
+    pub column: String,
+    /// `IS NULL`, `IS NOT NULL`, or a comparison such as `> 1000`,
+    /// `<= 0` or `= 'failed'`.
+    pub when: String,
+    /// Color name (`red`, `lightgreen`, ...) or `#rrggbb`.
+    pub color: String,
+    #[serde(default)]
+    pub bold: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Condition {
+    IsNull,
+    IsNotNull,
+    Compare(Op, String),
+}
+
+/// A `FormatRule` parsed once at startup so drawing only evaluates it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellRule {
+    column: String,
+    condition: Condition,
+    pub style: Style,
+}
+
+impl CellRule {
+    pub fn parse(rule: &FormatRule) -> Result<Self, String> {
+        let condition = parse_condition(&rule.when)
+            .ok_or_else(|| format!("Unknown format rule condition '{}'", rule.when))?;
+        let color = Color::from_str(rule.color.trim())
+            .map_err(|_| format!("Unknown format rule color '{}'", rule.color))?;
+        let mut style = Style::default().fg(color);
+        if rule.bold {
+            style = style.add_modifier(Modifier::BOLD);
+        }
+        Ok(Self {
+            column: rule.column.trim().to_lowercase(),
+            condition,
+            style,
+        })
+    }
+
+    pub fn applies_to(&self, column: &str) -> bool {
+        self.column.is_empty() || wildcard_match(&self.column, &column.to_lowercase())
+    }
+
+    pub fn matches(&self, cell: &CellValue) -> bool {
+        let is_null = matches!(cell, CellValue::Null);
+        match &self.condition {
+            Condition::IsNull => is_null,
+            Condition::IsNotNull => !is_null,
+            Condition::Compare(..) if is_null => false,
+            Condition::Compare(op, value) => {
+                let ordering = compare_values(&cell.display(), value);
+                match op {
+                    Op::Eq => ordering == Ordering::Equal,
+                    Op::Ne => ordering != Ordering::Equal,
+                    Op::Lt => ordering == Ordering::Less,
+                    Op::Le => ordering != Ordering::Greater,
+                    Op::Gt => ordering == Ordering::Greater,
+                    Op::Ge => ordering != Ordering::Less,
+                }
+            }
+        }
+    }
+}
+
+/// Parse every rule, returning the valid ones and a message per bad one.
+pub fn compile_format_rules(rules: &[FormatRule]) -> (Vec<CellRule>, Vec<String>) {
+    let mut compiled = Vec::new();
+    let mut errors = Vec::new();
+    for rule in rules {
+        match CellRule::parse(rule) {
+            Ok(rule) => compiled.push(rule),
+            Err(e) => errors.push(e),
+        }
+    }
+    (compiled, errors)
+}
+
+/// Style of the first rule matching `cell` in `column`.
+pub fn cell_rule_style(rules: &[CellRule], column: &str, cell: &CellValue) -> Option<Style> {
+    rules
+        .iter()
+        .find(|rule| rule.applies_to(column) && rule.matches(cell))
+        .map(|rule| rule.style)
+}
+
+fn parse_condition(when: &str) -> Option<Condition> {
+    let when = when.trim();
+    let normalized = when.split_whitespace().collect::<Vec<_>>().join(" ");
+    match normalized.to_uppercase().as_str() {
+        "IS NULL" => return Some(Condition::IsNull),
+        "IS NOT NULL" => return Some(Condition::IsNotNull),
+        _ => {}
+    }
+    // Two-character operators first so `>=` is not read as `>`
+    let ops = [
+        (">=", Op::Ge),
+        ("<=", Op::Le),
+        ("<>", Op::Ne),
+        ("!=", Op::Ne),
+        ("=", Op::Eq),
+        (">", Op::Gt),
+        ("<", Op::Lt),
+    ];
+    let (op, rest) = ops
+        .iter()
+        .find_map(|(token, op)| when.strip_prefix(token).map(|rest| (*op, rest)))?;
+    let value = rest.trim();
+    if value.is_empty() {
+        return None;
+    }
+    let value = value
+        .strip_prefix('\'')
+        .and_then(|v| v.strip_suffix('\''))
+        .map(|v| v.replace("''", "'"))
+        .unwrap_or_else(|| value.to_string());
+    Some(Condition::Compare(op, value))
+}
+
+/// Compare as numbers when both sides parse as one, otherwise as text.
+fn compare_values(cell: &str, value: &str) -> Ordering {
+    match (cell.trim().parse::<f64>(), value.parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        _ => cell.cmp(value),
+    }
+}
+
+/// `*` matches any run of characters and `?` any single one.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(column: &str, when: &str) -> CellRule {
+        CellRule::parse(&FormatRule {
+            column: column.into(),
+            when: when.into(),
+            color: "red".into(),
+            bold: false,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_numeric_comparisons() {
+        let negative = rule("balance", "< 0");
+        assert!(negative.matches(&CellValue::Int32(-5)));
+        assert!(negative.matches(&CellValue::Text("-0.5".into())));
+        assert!(!negative.matches(&CellValue::Int32(0)));
+        assert!(!negative.matches(&CellValue::Null));
+
+        let big = rule("", ">= 1000");
+        assert!(big.matches(&CellValue::Int64(1000)));
+        // 200 > 1000 as text, but both are numbers
+        assert!(!big.matches(&CellValue::Text("200".into())));
+    }
+
+    #[test]
+    fn test_text_and_null_conditions() {
+        let failed = rule("status", "= 'it''s failed'");
+        assert!(failed.matches(&CellValue::Text("it's failed".into())));
+        assert!(!failed.matches(&CellValue::Text("ok".into())));
+        assert!(rule("", "<> ok").matches(&CellValue::Text("failed".into())));
+        assert!(rule("", "is  null").matches(&CellValue::Null));
+        assert!(!rule("", "IS NOT NULL").matches(&CellValue::Null));
+        assert!(rule("", "IS NOT NULL").matches(&CellValue::Bool(false)));
+    }
+
+    #[test]
+    fn test_column_patterns() {
+        assert!(rule("Balance", "< 0").applies_to("balance"));
+        assert!(!rule("balance", "< 0").applies_to("balances"));
+        assert!(rule("*_at", "IS NULL").applies_to("created_at"));
+        assert!(!rule("*_at", "IS NULL").applies_to("status"));
+        assert!(rule("amount?", "< 0").applies_to("amount1"));
+        assert!(rule("", "< 0").applies_to("anything"));
+    }
+
+    #[test]
+    fn test_invalid_rules_are_reported() {
+        let rules = vec![
+            FormatRule {
+                column: "a".into(),
+                when: "~ 5".into(),
+                color: "red".into(),
+                bold: false,
+            },
+            FormatRule {
+                column: "a".into(),
+                when: "> 5".into(),
+                color: "reddish".into(),
+                bold: false,
+            },
+            FormatRule {
+                column: "a".into(),
+                when: "> 5".into(),
+                color: "#ff8800".into(),
+                bold: true,
+            },
+        ];
+        let (compiled, errors) = compile_format_rules(&rules);
+        assert_eq!(compiled.len(), 1);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            cell_rule_style(&compiled, "a", &CellValue::Int32(6)),
+            Some(
+                Style::default()
+                    .fg(Color::Rgb(255, 136, 0))
+                    .add_modifier(Modifier::BOLD)
+            )
+        );
+        assert_eq!(cell_rule_style(&compiled, "a", &CellValue::Int32(5)), None);
+    }
+}
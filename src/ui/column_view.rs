@@ -0,0 +1,170 @@
+use std::collections::BTreeSet;
+
+/// How one result's columns are shown: their display order and which of
+/// them are hidden. Indices refer to `QueryResult.columns`, which is never
+/// reordered. Kept per result for the session and never persisted.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ColumnView {
+    /// Display order; empty until a column is moved.
+    order: Vec<usize>,
+    hidden: BTreeSet<usize>,
+    /// Header text per column, when it differs from the column name.
+    labels: Vec<String>,
+}
+
+impl ColumnView {
+    pub fn with_labels(labels: Vec<String>) -> Self {
+        Self {
+            labels,
+            ..Default::default()
+        }
+    }
+
+    /// Header text for column `col`, if the view overrides its name.
+    pub fn label(&self, col: usize) -> Option<&str> {
+        self.labels.get(col).map(String::as_str)
+    }
+
+    /// All `count` column indices in display order.
+    pub fn order(&self, count: usize) -> Vec<usize> {
+        if self.order.len() == count {
+            self.order.clone()
+        } else {
+            (0..count).collect()
+        }
+    }
+
+    /// Indices of the columns to draw, out of `count`, in display order.
+    pub fn visible(&self, count: usize) -> Vec<usize> {
+        self.order(count)
+            .into_iter()
+            .filter(|col| !self.hidden.contains(col))
+            .collect()
+    }
+
+    pub fn hidden_count(&self) -> usize {
+        self.hidden.len()
+    }
+
+    /// Hide column `col`. The last visible column stays, so the table never
+    /// goes blank; returns whether the column was hidden.
+    pub fn hide(&mut self, col: usize, count: usize) -> bool {
+        if col >= count || self.visible(count).len() <= 1 {
+            return false;
+        }
+        self.hidden.insert(col)
+    }
+
+    pub fn show_all(&mut self) {
+        self.hidden.clear();
+    }
+
+    /// The nearest visible column after (or before) `col` in display
+    /// order; `None` at either end.
+    pub fn step(&self, col: usize, count: usize, forward: bool) -> Option<usize> {
+        let order = self.order(count);
+        let pos = order.iter().position(|&c| c == col)?;
+        let visible = |c: &usize| !self.hidden.contains(c);
+        if forward {
+            order[pos + 1..].iter().copied().find(visible)
+        } else {
+            order[..pos].iter().copied().rfind(visible)
+        }
+    }
+
+    /// Swap `col` with its visible neighbour on the left or right; returns
+    /// whether it moved.
+    pub fn shift(&mut self, col: usize, count: usize, forward: bool) -> bool {
+        let Some(other) = self.step(col, count, forward) else {
+            return false;
+        };
+        let mut order = self.order(count);
+        let a = order.iter().position(|&c| c == col);
+        let b = order.iter().position(|&c| c == other);
+        if let (Some(a), Some(b)) = (a, b) {
+            order.swap(a, b);
+        }
+        self.order = order;
+        true
+    }
+
+    /// Move `col` in front of every other column.
+    pub fn pin(&mut self, col: usize, count: usize) {
+        if col >= count {
+            return;
+        }
+        let mut order = self.order(count);
+        order.retain(|&c| c != col);
+        order.insert(0, col);
+        self.order = order;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hide_and_show_all() {
+        let mut view = ColumnView::default();
+        assert!(view.hide(1, 3));
+        assert!(!view.hide(1, 3));
+        assert_eq!(view.visible(3), vec![0, 2]);
+        assert_eq!(view.hidden_count(), 1);
+        view.show_all();
+        assert_eq!(view.visible(3), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_last_visible_column_stays() {
+        let mut view = ColumnView::default();
+        assert!(view.hide(0, 2));
+        assert!(!view.hide(1, 2));
+        assert!(!view.hide(5, 2));
+        assert_eq!(view.visible(2), vec![1]);
+    }
+
+    #[test]
+    fn test_step_skips_hidden_columns() {
+        let mut view = ColumnView::default();
+        view.hide(1, 4);
+        assert_eq!(view.step(0, 4, true), Some(2));
+        assert_eq!(view.step(2, 4, false), Some(0));
+        assert_eq!(view.step(3, 4, true), None);
+        assert_eq!(view.step(0, 4, false), None);
+        // From a column just hidden, land on a visible neighbour
+        assert_eq!(view.step(1, 4, true), Some(2));
+        assert_eq!(view.step(1, 4, false), Some(0));
+    }
+
+    #[test]
+    fn test_pin_moves_column_to_front() {
+        let mut view = ColumnView::default();
+        view.pin(2, 4);
+        assert_eq!(view.visible(4), vec![2, 0, 1, 3]);
+        view.pin(3, 4);
+        assert_eq!(view.visible(4), vec![3, 2, 0, 1]);
+        // Navigation follows the display order
+        assert_eq!(view.step(3, 4, true), Some(2));
+        assert_eq!(view.step(0, 4, false), Some(2));
+    }
+
+    #[test]
+    fn test_shift_skips_hidden_columns() {
+        let mut view = ColumnView::default();
+        view.hide(1, 4);
+        assert!(view.shift(2, 4, false));
+        assert_eq!(view.order(4), vec![2, 1, 0, 3]);
+        assert_eq!(view.visible(4), vec![2, 0, 3]);
+        assert!(!view.shift(2, 4, false));
+        assert!(view.shift(3, 4, false));
+        assert_eq!(view.visible(4), vec![2, 3, 0]);
+    }
+
+    #[test]
+    fn test_order_ignores_other_result_shapes() {
+        let mut view = ColumnView::default();
+        view.pin(1, 2);
+        assert_eq!(view.order(3), vec![0, 1, 2]);
+    }
+}
@@ -1,21 +1,23 @@
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Modifier, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Cell, Clear, List, ListItem, Paragraph, Row, Table, Tabs, Wrap},
     Frame,
 };
 
-use crate::db::SslMode;
+use crate::db::{CellChange, Notice, SslMode};
+use crate::editor::FunctionSignature;
 use crate::explain::{
     format_duration_ms, node_color_class, rows_mismatch, NodeColorClass, PlanNode, QueryPlan,
 };
 use crate::ui::{
-    is_sql_function, is_sql_keyword, is_sql_type, App, Focus, SidebarTab, StatusType, Theme,
-    EXPORT_FORMATS, SPINNER_FRAMES,
+    cell_rule_style, is_numeric_literal, is_sql_function, is_sql_keyword, is_sql_type, App,
+    FindState, Focus, SidebarTab, StatusType, Theme, TreeRow, EXPORT_FORMATS, PALETTE_ACTIONS,
+    SPINNER_FRAMES,
 };
 
-pub fn draw(frame: &mut Frame, app: &App) {
+pub fn draw(frame: &mut Frame, app: &mut App) {
     // Create main layout
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -35,8 +37,8 @@ pub fn draw(frame: &mut Frame, app: &App) {
         .constraints([Constraint::Length(app.sidebar_width), Constraint::Min(0)])
         .split(chunks[1]);
 
-    draw_sidebar(frame, app, main_chunks[0]);
-    draw_main_panel(frame, app, main_chunks[1]);
+    app.sidebar_visible_rows = draw_sidebar(frame, app, main_chunks[0]) as usize;
+    app.editor_visible_rows = draw_main_panel(frame, app, main_chunks[1]) as usize;
 
     // Draw status bar
     draw_status_bar(frame, app, chunks[2]);
@@ -64,6 +66,26 @@ pub fn draw(frame: &mut Frame, app: &App) {
         draw_table_inspector(frame, app);
     }
 
+    // Draw AST tree if active
+    if app.ast_tree.is_some() {
+        draw_ast_tree(frame, app);
+    }
+
+    // Draw pinned result comparison if active
+    if app.result_compare.is_some() {
+        draw_result_compare(frame, app);
+    }
+
+    // Draw cell detail if open
+    if app.cell_detail.is_some() {
+        draw_cell_detail(frame, app);
+    }
+
+    // Draw DROP/TRUNCATE confirmation if pending
+    if app.destructive_confirm.is_some() {
+        draw_destructive_confirm(frame, app);
+    }
+
     // Draw connection dialog if active
     if app.connection_dialog.active {
         draw_connection_dialog(frame, app);
@@ -74,6 +96,16 @@ pub fn draw(frame: &mut Frame, app: &App) {
         draw_export_picker(frame, app);
     }
 
+    // Draw command palette if open
+    if app.command_palette.is_some() {
+        draw_command_palette(frame, app);
+    }
+
+    // Draw go-to-line prompt if open
+    if app.go_to_line.is_some() {
+        draw_go_to_line(frame, app);
+    }
+
     // Draw help overlay if active
     if app.show_help {
         draw_help_overlay(frame, app);
@@ -84,28 +116,54 @@ fn draw_header(frame: &mut Frame, app: &App, area: Rect) {
     let theme = &app.theme;
 
     let connection_info = if app.connection.is_connected() {
-        format!(
+        let mut info = format!(
             " {} | {} | {} ",
             app.connection.config.display_string(),
             app.connection.current_database,
             app.connection.current_schema
-        )
+        );
+        if let Some(role) = &app.connection.current_role {
+            info.push_str(&format!("| role: {} ", role));
+        }
+        if let Some(version) = &app.connection.server_version {
+            info.push_str(&format!("| {} ", version));
+        }
+        info
     } else {
         " Not Connected ".to_string()
     };
 
-    let header_text = format!(
-        " pgrsql {}{}",
-        connection_info,
-        " ".repeat(area.width.saturating_sub(connection_info.len() as u16 + 10) as usize)
-    );
+    let mut spans = vec![Span::raw(format!(" pgrsql {}", connection_info))];
+    if let (true, Some(env)) = (
+        app.connection.is_connected(),
+        app.connection.config.environment,
+    ) {
+        spans.push(Span::styled(
+            format!(" {} ", env.label()),
+            Style::default()
+                .fg(theme.environment_color(env))
+                .bg(theme.bg_primary)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+    if app.connection.is_connected() && app.connection.is_superuser {
+        // Constant reminder that destructive statements are not guarded by privileges
+        spans.push(Span::styled(
+            " SUPERUSER ",
+            Style::default()
+                .fg(theme.bg_secondary)
+                .bg(theme.warning)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
 
-    let header = Paragraph::new(header_text).style(theme.header());
+    let header = Paragraph::new(Line::from(spans)).style(theme.header());
 
     frame.render_widget(header, area);
 }
 
-fn draw_sidebar(frame: &mut Frame, app: &App, area: Rect) {
+/// Draws the sidebar and returns how many list rows it shows.
+fn draw_sidebar(frame: &mut Frame, app: &App, area: Rect) -> u16 {
     let theme = &app.theme;
     let focused = app.focus == Focus::Sidebar;
 
@@ -141,22 +199,39 @@ fn draw_sidebar(frame: &mut Frame, app: &App, area: Rect) {
 
     frame.render_widget(tabs, chunks[0]);
 
-    // Draw content based on selected tab
+    // Draw content based on selected tab; the list sits inside borders
+    let height = chunks[1].height.saturating_sub(2);
     match app.sidebar_tab {
-        SidebarTab::Databases => draw_databases_list(frame, app, chunks[1]),
-        SidebarTab::Tables => draw_tables_tree(frame, app, chunks[1]),
-        SidebarTab::History => draw_history_list(frame, app, chunks[1]),
+        SidebarTab::Databases => draw_databases_list(frame, app, chunks[1], height as usize),
+        SidebarTab::Tables => draw_tables_tree(frame, app, chunks[1], height as usize),
+        SidebarTab::History => draw_history_list(frame, app, chunks[1], height as usize),
+    }
+    height
+}
+
+/// First sidebar row to draw: `scroll`, moved just enough that `selected`
+/// is among the `height` rows shown.
+fn sidebar_window_start(scroll: usize, selected: usize, height: usize) -> usize {
+    if selected < scroll {
+        selected
+    } else if height > 0 && selected >= scroll + height {
+        selected + 1 - height
+    } else {
+        scroll
     }
 }
 
-fn draw_databases_list(frame: &mut Frame, app: &App, area: Rect) {
+fn draw_databases_list(frame: &mut Frame, app: &App, area: Rect, height: usize) {
     let theme = &app.theme;
     let focused = app.focus == Focus::Sidebar;
 
+    let start = sidebar_window_start(app.sidebar_scroll, app.sidebar_selected, height);
     let items: Vec<ListItem> = app
         .databases
         .iter()
         .enumerate()
+        .skip(start)
+        .take(height)
         .map(|(i, db)| {
             let style = if i == app.sidebar_selected {
                 theme.selected()
@@ -187,50 +262,43 @@ fn draw_databases_list(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(list, area);
 }
 
-fn draw_tables_tree(frame: &mut Frame, app: &App, area: Rect) {
+fn draw_tables_tree(frame: &mut Frame, app: &App, area: Rect, height: usize) {
     let theme = &app.theme;
     let focused = app.focus == Focus::Sidebar;
 
-    let mut items: Vec<ListItem> = Vec::new();
-    let mut index = 0;
-
-    for schema in &app.schemas {
-        let expanded = app.expanded_schemas.contains(&schema.name);
-        let icon = if expanded { "▼" } else { "▶" };
-
-        let style = if index == app.sidebar_selected {
-            theme.selected()
-        } else {
-            Style::default().fg(theme.text_accent)
-        };
-
-        items.push(ListItem::new(format!(" {} {}", icon, schema.name)).style(style));
-        index += 1;
-
-        if expanded {
-            for table in &app.tables {
-                if table.schema == schema.name {
-                    let table_icon = match table.table_type {
-                        crate::db::TableType::Table => "󰓫",
-                        crate::db::TableType::View => "󰈈",
-                        crate::db::TableType::MaterializedView => "󰈈",
-                        crate::db::TableType::ForeignTable => "󰒍",
-                    };
-
-                    let style = if index == app.sidebar_selected {
-                        theme.selected()
-                    } else {
-                        Style::default().fg(theme.text_primary)
-                    };
-
-                    items.push(
-                        ListItem::new(format!("   {} {}", table_icon, table.name)).style(style),
-                    );
-                    index += 1;
-                }
+    // Only the rows in view become list items
+    let start = sidebar_window_start(app.sidebar_scroll, app.sidebar_selected, height);
+    let items: Vec<ListItem> = app
+        .table_tree_rows()
+        .enumerate()
+        .skip(start)
+        .take(height)
+        .map(|(index, row)| match row {
+            TreeRow::Schema(schema, expanded) => {
+                let icon = if expanded { "▼" } else { "▶" };
+                let style = if index == app.sidebar_selected {
+                    theme.selected()
+                } else {
+                    Style::default().fg(theme.text_accent)
+                };
+                ListItem::new(format!(" {} {}", icon, schema.name)).style(style)
             }
-        }
-    }
+            TreeRow::Table(table) => {
+                let table_icon = match table.table_type {
+                    crate::db::TableType::Table => "󰓫",
+                    crate::db::TableType::View => "󰈈",
+                    crate::db::TableType::MaterializedView => "󰈈",
+                    crate::db::TableType::ForeignTable => "󰒍",
+                };
+                let style = if index == app.sidebar_selected {
+                    theme.selected()
+                } else {
+                    Style::default().fg(theme.text_primary)
+                };
+                ListItem::new(format!("   {} {}", table_icon, table.name)).style(style)
+            }
+        })
+        .collect();
 
     let list = List::new(items).block(
         Block::default()
@@ -247,15 +315,18 @@ fn draw_tables_tree(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(list, area);
 }
 
-fn draw_history_list(frame: &mut Frame, app: &App, area: Rect) {
+fn draw_history_list(frame: &mut Frame, app: &App, area: Rect, height: usize) {
     let theme = &app.theme;
     let focused = app.focus == Focus::Sidebar;
 
+    let start = sidebar_window_start(app.sidebar_scroll, app.sidebar_selected, height);
     let entries = app.query_history.entries();
     let items: Vec<ListItem> = entries
         .iter()
         .rev()
         .enumerate()
+        .skip(start)
+        .take(height)
         .map(|(i, entry)| {
             let status_icon = if entry.success { "✓" } else { "✗" };
             let query_preview: String = entry
@@ -270,8 +341,17 @@ fn draw_history_list(frame: &mut Frame, app: &App, area: Rect) {
             } else {
                 Style::default().fg(theme.text_primary)
             };
+            let db_style = if entry.database == app.connection.current_database {
+                theme.muted()
+            } else {
+                theme.status_warning()
+            };
 
-            ListItem::new(format!(" {} {}", status_icon, query_preview)).style(style)
+            ListItem::new(Line::from(vec![
+                Span::raw(format!(" {} {}", status_icon, query_preview)),
+                Span::styled(format!(" @{}", entry.database), db_style),
+            ]))
+            .style(style)
         })
         .collect();
 
@@ -290,7 +370,9 @@ fn draw_history_list(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(list, area);
 }
 
-fn draw_main_panel(frame: &mut Frame, app: &App, area: Rect) {
+/// Returns the rows of text the editor shows, for scrolling its cursor
+/// into view.
+fn draw_main_panel(frame: &mut Frame, app: &App, area: Rect) -> u16 {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -299,18 +381,56 @@ fn draw_main_panel(frame: &mut Frame, app: &App, area: Rect) {
         ])
         .split(area);
 
-    draw_editor(frame, app, chunks[0]);
+    let editor_rows = draw_editor(frame, app, chunks[0]);
     draw_results(frame, app, chunks[1]);
+    editor_rows
 }
 
-fn draw_editor(frame: &mut Frame, app: &App, area: Rect) {
+fn draw_editor(frame: &mut Frame, app: &App, area: Rect) -> u16 {
     let theme = &app.theme;
-    let focused = app.focus == Focus::Editor;
+    let focused = matches!(app.focus, Focus::Editor | Focus::Find);
+    let title_text = if app.offline_mode {
+        " Query Editor [offline: F5 to parse & analyze] "
+    } else {
+        " Query Editor (F5 or Ctrl+Enter to execute) "
+    };
+    let mut title_spans = vec![Span::raw(title_text)];
+    if app.statement_hints.is_raw {
+        // The statement executes fine, but the AST only holds it as raw SQL
+        title_spans.push(Span::styled("(raw) ", theme.muted()));
+    }
+    if let Some(hint) = &app.statement_hints.runtime {
+        title_spans.push(Span::styled(format!("({}) ", hint), theme.muted()));
+    }
+    let title = Line::from(title_spans);
+
+    // Vim mode indicator, or the `/` / `:` line being typed
+    let vim_prompt = app.vim.as_ref().and_then(|vim| vim.prompt_line());
+    let vim_status = app.vim.as_ref().map(|vim| match &vim_prompt {
+        Some(prompt) => Line::from(Span::styled(
+            format!(" {} ", prompt),
+            Style::default().fg(theme.text_primary),
+        )),
+        None => Line::from(Span::styled(
+            format!(" -- {} -- ", vim.mode_label()),
+            Style::default()
+                .fg(theme.text_accent)
+                .add_modifier(Modifier::BOLD),
+        )),
+    });
+
+    let find_bar = app.find.as_ref().map(|find| draw_find_bar(find, theme));
+    let signature_hint = match app.focus {
+        Focus::Editor => app
+            .signature_hint()
+            .map(|(signature, arg)| draw_signature_hint(signature, arg, theme)),
+        _ => None,
+    };
 
     let inner_area = Block::default()
         .borders(Borders::ALL)
         .border_style(theme.border_style(focused))
-        .title(" Query Editor (F5 or Ctrl+Enter to execute) ")
+        .title(title.clone())
         .title_style(if focused {
             Style::default().fg(theme.text_accent)
         } else {
@@ -319,18 +439,24 @@ fn draw_editor(frame: &mut Frame, app: &App, area: Rect) {
         .inner(area);
 
     // Render block
-    frame.render_widget(
-        Block::default()
-            .borders(Borders::ALL)
-            .border_style(theme.border_style(focused))
-            .title(" Query Editor (F5 or Ctrl+Enter to execute) ")
-            .title_style(if focused {
-                Style::default().fg(theme.text_accent)
-            } else {
-                Style::default().fg(theme.text_secondary)
-            }),
-        area,
-    );
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(theme.border_style(focused))
+        .title(title)
+        .title_style(if focused {
+            Style::default().fg(theme.text_accent)
+        } else {
+            Style::default().fg(theme.text_secondary)
+        });
+    if let Some(bar) = find_bar {
+        block = block.title_bottom(bar);
+    } else if let Some(status) = vim_status {
+        block = block.title_bottom(status);
+    }
+    if let Some(hint) = signature_hint {
+        block = block.title_bottom(hint.right_aligned());
+    }
+    frame.render_widget(block, area);
 
     // Determine active query range for visual highlighting
     let query_range = app.get_current_query_line_range();
@@ -349,7 +475,32 @@ fn draw_editor(frame: &mut Frame, app: &App, area: Rect) {
             let in_active_query = query_range
                 .map(|(start, end)| actual_line >= start && actual_line <= end)
                 .unwrap_or(false);
-            highlight_sql_line(line_text, theme, actual_line, &app.editor, in_active_query)
+            // Other matches stand out; the current one is shown as the selection
+            let matches: Vec<(usize, usize)> = app
+                .find
+                .iter()
+                .flat_map(|find| {
+                    find.matches
+                        .iter()
+                        .enumerate()
+                        .filter(move |&(i, &(_, y))| y == actual_line && i != find.current)
+                        .map(move |(_, &(x, _))| (x, x + find.query.len()))
+                })
+                .collect();
+            let line = highlight_sql_line(
+                line_text,
+                theme,
+                actual_line,
+                &app.editor,
+                in_active_query,
+                &matches,
+            );
+            match app.executed_flash {
+                Some((start, end, _)) if actual_line >= start && actual_line <= end => {
+                    line.style(Style::default().bg(theme.bg_selected))
+                }
+                _ => line,
+            }
         })
         .collect();
 
@@ -357,13 +508,109 @@ fn draw_editor(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(paragraph, inner_area);
 
     // Show cursor (offset by 2 for gutter prefix)
-    if focused {
-        let cursor_x = inner_area.x + 2 + app.editor.cursor_x as u16;
+    if let (Focus::Find, Some(find)) = (app.focus, &app.find) {
+        // Cursor sits at the end of the query in the find bar
+        let cursor_x = area.x + 8 + find.query.chars().count() as u16;
+        frame.set_cursor_position((cursor_x, area.y + area.height.saturating_sub(1)));
+    } else if let (true, Some(prompt)) = (focused, &vim_prompt) {
+        // Cursor sits at the end of the prompt on the bottom border
+        let cursor_x = area.x + 2 + prompt.chars().count() as u16;
+        frame.set_cursor_position((cursor_x, area.y + area.height.saturating_sub(1)));
+    } else if focused {
+        let cursor_x = inner_area.x + 2 + app.editor.display_column() as u16;
         let cursor_y = inner_area.y + (app.editor.cursor_y - app.editor.scroll_offset) as u16;
         if cursor_y < inner_area.y + inner_area.height {
             frame.set_cursor_position((cursor_x, cursor_y));
         }
     }
+    inner_area.height
+}
+
+/// The find bar shown on the editor's bottom border: the query, then the
+/// current match position, "No matches", or a hint while the query is empty.
+fn draw_find_bar<'a>(find: &FindState, theme: &Theme) -> Line<'a> {
+    let status = if find.query.is_empty() {
+        Span::styled(
+            "Type to search, Enter/↓: Next, ↑: Previous, Esc: Close ",
+            Style::default().fg(theme.text_muted),
+        )
+    } else if find.matches.is_empty() {
+        Span::styled("No matches ", Style::default().fg(theme.error))
+    } else {
+        Span::styled(
+            format!("({}/{}) ", find.current + 1, find.matches.len()),
+            Style::default().fg(theme.text_muted),
+        )
+    };
+    Line::from(vec![
+        Span::styled(" Find: ", Style::default().fg(theme.text_accent)),
+        Span::styled(
+            format!("{} ", find.query),
+            Style::default().fg(theme.text_primary),
+        ),
+        status,
+    ])
+}
+
+/// `NAME(arg, arg)` with the argument being typed highlighted; on a
+/// variadic `...` tail, later arguments highlight the `...`.
+fn draw_signature_hint<'a>(signature: &FunctionSignature, arg: usize, theme: &Theme) -> Line<'a> {
+    let current = match signature.args.last() {
+        Some(&"...") => arg.min(signature.args.len() - 1),
+        _ => arg,
+    };
+    let mut spans = vec![Span::styled(
+        format!(" {}(", signature.name),
+        Style::default().fg(theme.text_muted),
+    )];
+    for (i, name) in signature.args.iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::styled(", ", Style::default().fg(theme.text_muted)));
+        }
+        let style = if i == current {
+            Style::default()
+                .fg(theme.text_accent)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme.text_secondary)
+        };
+        spans.push(Span::styled(name.to_string(), style));
+    }
+    spans.push(Span::styled(") ", Style::default().fg(theme.text_muted)));
+    Line::from(spans)
+}
+
+/// Give the byte ranges `ranges` of a line a `bg` background, splitting the
+/// spans that cover it as needed.
+fn highlight_ranges<'a>(
+    spans: Vec<Span<'a>>,
+    ranges: &[(usize, usize)],
+    bg: Color,
+) -> Vec<Span<'a>> {
+    let mut out = Vec::with_capacity(spans.len());
+    let mut offset = 0;
+    for span in spans {
+        let text = span.content.to_string();
+        let end = offset + text.len();
+        let mut pos = 0;
+        for &(start, stop) in ranges {
+            let s = start.clamp(offset, end) - offset;
+            let e = stop.clamp(offset, end) - offset;
+            if s >= e || s < pos {
+                continue;
+            }
+            if s > pos {
+                out.push(Span::styled(text[pos..s].to_string(), span.style));
+            }
+            out.push(Span::styled(text[s..e].to_string(), span.style.bg(bg)));
+            pos = e;
+        }
+        if pos < text.len() {
+            out.push(Span::styled(text[pos..].to_string(), span.style));
+        }
+        offset = end;
+    }
+    out
 }
 
 /// Determine if a line starts inside a block comment by scanning all previous lines.
@@ -393,12 +640,18 @@ fn highlight_sql_line<'a>(
     line_number: usize,
     editor: &crate::editor::TextBuffer,
     in_active_query: bool,
+    find_matches: &[(usize, usize)],
 ) -> Line<'a> {
     let mut spans: Vec<Span> = Vec::new();
     let mut current_word = String::new();
     let mut in_string = false;
     let mut string_char = '"';
-    let mut in_block_comment = is_in_block_comment(&editor.lines, line_number);
+    let mut dollar_tag = crate::editor::open_dollar_quote(
+        &editor.lines[..line_number.min(editor.lines.len())].join("\n"),
+    )
+    .map(str::to_string);
+    let mut in_block_comment =
+        dollar_tag.is_none() && is_in_block_comment(&editor.lines, line_number);
 
     let chars: Vec<char> = line.chars().collect();
     let len = chars.len();
@@ -443,6 +696,38 @@ fn highlight_sql_line<'a>(
             Style::default()
         };
 
+        // Inside a dollar-quoted body, everything up to the closing tag is
+        // part of the string
+        if let Some(tag) = &dollar_tag {
+            if line[byte_idx..].starts_with(tag.as_str()) {
+                current_word.push_str(tag);
+                spans.push(Span::styled(
+                    std::mem::take(&mut current_word),
+                    base_style.fg(theme.syntax_string),
+                ));
+                i += tag.chars().count();
+                dollar_tag = None;
+            } else {
+                current_word.push(c);
+                i += 1;
+            }
+            continue;
+        }
+
+        // Start a dollar-quoted body ($$ or $tag$)
+        if !in_string && c == '$' {
+            if let Some(tag) = crate::editor::dollar_quote_tag(line, byte_idx) {
+                if !current_word.is_empty() {
+                    spans.extend(create_word_spans(&current_word, theme, base_style));
+                    current_word.clear();
+                }
+                current_word.push_str(tag);
+                i += tag.chars().count();
+                dollar_tag = Some(tag.to_string());
+                continue;
+            }
+        }
+
         // Handle block comments
         if in_block_comment {
             if i + 1 < len && c == '*' && chars[i + 1] == '/' {
@@ -465,7 +750,7 @@ fn highlight_sql_line<'a>(
         // Start block comment
         if !in_string && i + 1 < len && c == '/' && chars[i + 1] == '*' {
             if !current_word.is_empty() {
-                spans.push(create_word_span(&current_word, theme, base_style));
+                spans.extend(create_word_spans(&current_word, theme, base_style));
                 current_word.clear();
             }
             spans.push(Span::styled(
@@ -480,7 +765,7 @@ fn highlight_sql_line<'a>(
         // Handle line comments
         if !in_string && i + 1 < len && c == '-' && chars[i + 1] == '-' {
             if !current_word.is_empty() {
-                spans.push(create_word_span(&current_word, theme, base_style));
+                spans.extend(create_word_spans(&current_word, theme, base_style));
                 current_word.clear();
             }
             let rest: String = chars[i..].iter().collect();
@@ -507,7 +792,7 @@ fn highlight_sql_line<'a>(
                 in_string = false;
             } else if !in_string {
                 if !current_word.is_empty() {
-                    spans.push(create_word_span(&current_word, theme, base_style));
+                    spans.extend(create_word_spans(&current_word, theme, base_style));
                     current_word.clear();
                 }
                 in_string = true;
@@ -526,6 +811,24 @@ fn highlight_sql_line<'a>(
             continue;
         }
 
+        // Keep signs that belong to a number inside the word: the exponent
+        // sign in `1.5e-3`, and a leading minus like `= -5` (but not `a - 5`).
+        if (c == '-' || c == '+') && chars.get(i + 1).is_some_and(|n| n.is_ascii_digit()) {
+            let is_exponent_sign = current_word.starts_with(|ch: char| ch.is_ascii_digit())
+                && current_word.ends_with(['e', 'E']);
+            let prev = chars[..i].iter().rev().find(|ch| !ch.is_whitespace());
+            let after_operator = match prev {
+                Some(prev) => "(,=<>+-*/%[".contains(*prev),
+                None => true,
+            };
+            let is_leading_minus = c == '-' && current_word.is_empty() && after_operator;
+            if is_exponent_sign || is_leading_minus {
+                current_word.push(c);
+                i += 1;
+                continue;
+            }
+        }
+
         // Handle PostgreSQL operators: ::, ->, ->>, #>, #>>, @>, <@, ?|, ?&, ||
         if i + 1 < len {
             let two_char: String = chars[i..i + 2].iter().collect();
@@ -535,7 +838,7 @@ fn highlight_sql_line<'a>(
             );
             if is_pg_operator {
                 if !current_word.is_empty() {
-                    spans.push(create_word_span(&current_word, theme, base_style));
+                    spans.extend(create_word_spans(&current_word, theme, base_style));
                     current_word.clear();
                 }
                 // Check for 3-char operators: ->>, #>>
@@ -556,12 +859,18 @@ fn highlight_sql_line<'a>(
             }
         }
 
-        // Handle word boundaries
-        if c.is_alphanumeric() || c == '_' {
+        // Handle word boundaries. A dot between identifier characters keeps
+        // qualified names (`pg_catalog.now`) and decimals (`1.5`) together.
+        let continues_qualified_name = c == '.'
+            && !current_word.is_empty()
+            && chars
+                .get(i + 1)
+                .is_some_and(|next| next.is_alphanumeric() || *next == '_');
+        if c.is_alphanumeric() || c == '_' || continues_qualified_name {
             current_word.push(c);
         } else {
             if !current_word.is_empty() {
-                spans.push(create_word_span(&current_word, theme, base_style));
+                spans.extend(create_word_spans(&current_word, theme, base_style));
                 current_word.clear();
             }
 
@@ -583,20 +892,80 @@ fn highlight_sql_line<'a>(
 
     // Handle remaining word
     if !current_word.is_empty() {
-        let style = if in_string {
-            Style::default().fg(theme.syntax_string)
-        } else if in_block_comment {
-            Style::default().fg(theme.syntax_comment)
+        if in_string || dollar_tag.is_some() {
+            // An unterminated string runs on to the next line; keep its words
+            // from being colored as keywords
+            spans.push(Span::styled(
+                current_word,
+                Style::default().fg(theme.syntax_string),
+            ));
         } else {
-            Style::default()
-        };
-        spans.push(create_word_span(&current_word, theme, style));
+            let style = if in_block_comment {
+                Style::default().fg(theme.syntax_comment)
+            } else {
+                Style::default()
+            };
+            spans.extend(create_word_spans(&current_word, theme, style));
+        }
+    }
+
+    if !find_matches.is_empty() {
+        let body = highlight_ranges(spans.split_off(1), find_matches, theme.bg_highlight);
+        spans.extend(body);
+    }
+
+    if line.contains('\t') {
+        // Skip the gutter span so tab stops line up with the buffer's columns
+        let body = expand_tab_spans(spans.split_off(1), editor.tab_width);
+        spans.extend(body);
     }
 
     Line::from(spans)
 }
 
-fn create_word_span<'a>(word: &str, theme: &Theme, base_style: Style) -> Span<'a> {
+/// Replace tab characters with spaces up to the next tab stop, tracking the
+/// display column across span boundaries.
+fn expand_tab_spans(spans: Vec<Span<'_>>, tab_width: usize) -> Vec<Span<'_>> {
+    let tab_width = tab_width.max(1);
+    let mut col = 0;
+    spans
+        .into_iter()
+        .map(|span| {
+            let mut content = String::with_capacity(span.content.len());
+            for c in span.content.chars() {
+                if c == '\t' {
+                    let n = tab_width - col % tab_width;
+                    content.push_str(&" ".repeat(n));
+                    col += n;
+                } else {
+                    content.push(c);
+                    col += 1;
+                }
+            }
+            Span::styled(content, span.style)
+        })
+        .collect()
+}
+
+fn create_word_spans<'a>(word: &str, theme: &Theme, base_style: Style) -> Vec<Span<'a>> {
+    let is_number = is_numeric_literal(word);
+    if let (false, Some((qualifier, name))) = (is_number, word.rsplit_once('.')) {
+        // Qualified name: color the last segment as a function/type, but never
+        // as a keyword (`t.order` is a column, not ORDER).
+        let name_style = if is_sql_function(name) {
+            base_style.fg(theme.syntax_function)
+        } else if is_sql_type(name) {
+            base_style.fg(theme.syntax_type)
+        } else {
+            base_style.fg(theme.text_primary)
+        };
+        return vec![
+            Span::styled(qualifier.to_string(), base_style.fg(theme.text_primary)),
+            Span::styled(".".to_string(), base_style.fg(theme.text_muted)),
+            Span::styled(name.to_string(), name_style),
+        ];
+    }
+
     let style = if is_sql_keyword(word) {
         base_style
             .fg(theme.syntax_keyword)
@@ -605,12 +974,12 @@ fn create_word_span<'a>(word: &str, theme: &Theme, base_style: Style) -> Span<'a
         base_style.fg(theme.syntax_function)
     } else if is_sql_type(word) {
         base_style.fg(theme.syntax_type)
-    } else if word.chars().all(|c| c.is_ascii_digit() || c == '.') && !word.is_empty() {
+    } else if is_number {
         base_style.fg(theme.syntax_number)
     } else {
         base_style.fg(theme.text_primary)
     };
-    Span::styled(word.to_string(), style)
+    vec![Span::styled(word.to_string(), style)]
 }
 
 fn draw_results(frame: &mut Frame, app: &App, area: Rect) {
@@ -625,14 +994,16 @@ fn draw_results(frame: &mut Frame, app: &App, area: Rect) {
     let result_total = app.results.len();
 
     // Build title with execution time, row count, and cell position
-    let title = if let Some(result) = app.results.get(app.current_result) {
+    let title = if let Some(result) = app.current_query_result() {
         let time_ms = result.execution_time.as_secs_f64() * 1000.0;
         let position = if !result.columns.is_empty() && !result.rows.is_empty() {
-            format!(
-                " [R{}/C{}]",
-                app.result_selected_row + 1,
-                app.result_selected_col + 1
-            )
+            // Column position as displayed, after any reordering or hiding
+            let col = app
+                .visible_result_columns()
+                .iter()
+                .position(|&c| c == app.result_selected_col)
+                .unwrap_or(app.result_selected_col);
+            format!(" [R{}/C{}]", app.result_selected_row + 1, col + 1)
         } else {
             String::new()
         };
@@ -647,12 +1018,18 @@ fn draw_results(frame: &mut Frame, app: &App, area: Rect) {
                 result_index, result_total, affected, time_ms
             )
         } else {
+            let size = app
+                .current_entry()
+                .map(|e| e.size)
+                .map(|bytes| format!(", ~{}", crate::db::format_bytes(bytes)))
+                .unwrap_or_default();
             format!(
-                " Results ({}/{}) - {} rows x {} cols ({:.2}ms){} ",
+                " Results ({}/{}) - {} rows x {} cols{} ({:.2}ms){} ",
                 result_index,
                 result_total,
                 result.row_count,
                 result.columns.len(),
+                size,
                 time_ms,
                 position
             )
@@ -661,6 +1038,55 @@ fn draw_results(frame: &mut Frame, app: &App, area: Rect) {
         format!(" Results ({}/{}) ", result_index, result_total)
     };
 
+    // Only a slice of a much larger set was fetched under the LIMIT
+    let title = match app.truncated_row_estimate() {
+        Some(estimate) => format!(
+            "{}[LIMIT hit: ~{} rows est.] ",
+            title,
+            crate::db::format_row_count(estimate as usize)
+        ),
+        None => title,
+    };
+
+    let changed = app
+        .current_entry()
+        .and_then(|e| e.changes.as_ref())
+        .map_or(0, |rows| {
+            rows.iter()
+                .flatten()
+                .filter(|c| **c != CellChange::Unchanged)
+                .count()
+        });
+    let title = match changed {
+        0 => title,
+        n => format!("{}[{} changed] ", title, n),
+    };
+
+    let hidden = app
+        .current_entry()
+        .map_or(0, |e| e.column_view.hidden_count());
+    let title = if hidden == 0 {
+        title
+    } else {
+        format!("{}[{} hidden] ", title, hidden)
+    };
+
+    let title = if app.pinned.is_empty() {
+        title
+    } else {
+        format!("{}[{} pinned] ", title, app.pinned.len())
+    };
+
+    let notices = app
+        .current_entry()
+        .map(|e| e.notices.as_slice())
+        .unwrap_or_default();
+    let title = match notices.len() {
+        0 => title,
+        1 => format!("{}[1 notice] ", title),
+        n => format!("{}[{} notices] ", title, n),
+    };
+
     let block = Block::default()
         .borders(Borders::ALL)
         .border_style(theme.border_style(focused))
@@ -675,28 +1101,52 @@ fn draw_results(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(block, area);
 
     // Check if we should show a visual explain plan
-    let show_plan = app.show_visual_plan
-        && app
-            .explain_plans
-            .get(app.current_result)
-            .and_then(|p| p.as_ref())
-            .is_some();
-
-    if show_plan {
-        if let Some(Some(plan)) = app.explain_plans.get(app.current_result) {
-            draw_explain_plan(frame, app, plan, inner);
-        }
-    } else if let Some(result) = app.results.get(app.current_result) {
+    let plan = app
+        .current_entry()
+        .and_then(|e| e.plan.as_ref())
+        .filter(|_| app.show_visual_plan);
+
+    if let Some(plan) = plan {
+        draw_explain_plan(frame, app, plan, inner);
+    } else if let Some(result) = app.current_query_result() {
         if let Some(error) = &result.error {
             draw_structured_error(frame, app, error, inner);
         } else if result.columns.is_empty() {
             if let Some(affected) = result.affected_rows {
-                let msg = format!("{} rows affected", affected);
-                let text = Paragraph::new(msg).style(theme.status_success());
+                let mut lines = vec![Line::styled(
+                    format!("{} rows affected", affected),
+                    theme.status_success(),
+                )];
+                if !notices.is_empty() {
+                    lines.push(Line::raw(""));
+                    lines.extend(
+                        notices
+                            .iter()
+                            .map(|n| Line::styled(n.to_string(), theme.muted())),
+                    );
+                }
+                let text = Paragraph::new(lines).wrap(Wrap { trim: false });
                 frame.render_widget(text, inner);
             }
         } else {
-            draw_result_table(frame, app, result, inner);
+            // Notices raised by the query are listed under its rows
+            let area = if notices.is_empty() {
+                inner
+            } else {
+                let shown = notices.len().min(MAX_NOTICE_LINES) as u16;
+                let chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(3), Constraint::Length(shown + 1)])
+                    .split(inner);
+                draw_notice_list(frame, app, notices, chunks[1]);
+                chunks[0]
+            };
+            match app.chart_columns().filter(|_| app.show_result_chart) {
+                Some((label_col, value_col)) => {
+                    draw_result_chart(frame, app, result, label_col, value_col, area)
+                }
+                None => draw_result_table(frame, app, result, area),
+            }
         }
     } else {
         let text = Paragraph::new("No results yet. Execute a query with F5 or Ctrl+Enter.")
@@ -705,24 +1155,61 @@ fn draw_results(frame: &mut Frame, app: &App, area: Rect) {
     }
 }
 
+/// Most notices listed under a result's rows; the latest ones are kept.
+const MAX_NOTICE_LINES: usize = 5;
+
+fn draw_notice_list(frame: &mut Frame, app: &App, notices: &[Notice], area: Rect) {
+    let theme = &app.theme;
+    let skipped = notices.len().saturating_sub(MAX_NOTICE_LINES);
+    let header = match skipped {
+        0 => "── Notices".to_string(),
+        n => format!("── Notices ({} earlier not shown)", n),
+    };
+    let mut lines = vec![Line::styled(header, theme.muted())];
+    lines.extend(notices[skipped..].iter().map(|n| {
+        let style = if n.severity == "WARNING" {
+            Style::default().fg(theme.warning)
+        } else {
+            theme.muted()
+        };
+        Line::styled(n.to_string(), style)
+    }));
+    frame.render_widget(Paragraph::new(lines), area);
+}
+
 fn draw_result_table(frame: &mut Frame, app: &App, result: &crate::db::QueryResult, area: Rect) {
     let theme = &app.theme;
+    let visible = app.visible_result_columns();
+    let display_options = app.display_options();
+    let now = chrono::Utc::now();
+    let changes = app.current_entry().and_then(|e| e.changes.as_ref());
+    // Cells that moved since the previous run: up green, down red
+    let change_color = |row: usize, col: usize| match changes?.get(row)?.get(col)? {
+        CellChange::Unchanged => None,
+        CellChange::Increased => Some(theme.success),
+        CellChange::Decreased => Some(theme.error),
+        CellChange::Changed => Some(theme.warning),
+    };
 
     // Calculate column widths
-    let col_widths: Vec<Constraint> = result
-        .columns
+    let view = app.current_entry().map(|e| &e.column_view);
+    let header_label = |i: usize, name: &str| -> String {
+        view.and_then(|v| v.label(i)).unwrap_or(name).to_string()
+    };
+    let text_widths: Vec<usize> = visible
         .iter()
-        .map(|col| {
-            let width = col.max_width.min(40).max(col.name.len()) + 2;
-            Constraint::Length(width as u16)
-        })
+        .filter_map(|&i| result.columns.get(i).map(|col| (i, col)))
+        .map(|(i, col)| col.max_width.min(40).max(header_label(i, &col.name).len()))
+        .collect();
+    let col_widths: Vec<Constraint> = text_widths
+        .iter()
+        .map(|width| Constraint::Length(*width as u16 + 2))
         .collect();
 
     // Create header
-    let header_cells: Vec<Cell> = result
-        .columns
+    let header_cells: Vec<Cell> = visible
         .iter()
-        .enumerate()
+        .filter_map(|&i| result.columns.get(i).map(|col| (i, col)))
         .map(|(i, col)| {
             let style = if i == app.result_selected_col {
                 Style::default()
@@ -733,7 +1220,7 @@ fn draw_result_table(frame: &mut Frame, app: &App, result: &crate::db::QueryResu
                     .fg(theme.text_primary)
                     .add_modifier(Modifier::BOLD)
             };
-            Cell::from(col.name.clone()).style(style)
+            Cell::from(header_label(i, &col.name)).style(style)
         })
         .collect();
 
@@ -752,12 +1239,23 @@ fn draw_result_table(frame: &mut Frame, app: &App, result: &crate::db::QueryResu
         .skip(start_row)
         .take(visible_height)
         .map(|(row_idx, row)| {
-            let cells: Vec<Cell> = row
+            // The selected row can wrap its cells instead of truncating them
+            let wrap = app.wrap_selected_row && row_idx == app.result_selected_row;
+            let mut height = 1;
+            let cells: Vec<Cell> = visible
                 .iter()
                 .enumerate()
-                .map(|(col_idx, cell)| {
-                    let display = cell.display();
-                    let truncated: String = display.chars().take(40).collect();
+                .filter_map(|(pos, &col_idx)| row.get(col_idx).map(|cell| (pos, col_idx, cell)))
+                .map(|(pos, col_idx, cell)| {
+                    let display = cell.display_with(display_options, now);
+                    let text = if wrap {
+                        let width = text_widths.get(pos).copied().unwrap_or(40);
+                        let lines = wrap_cell_text(&display, width);
+                        height = height.max(lines.len().min(visible_height.max(1)));
+                        lines.join("\n")
+                    } else {
+                        display.chars().take(40).collect()
+                    };
 
                     let style = if row_idx == app.result_selected_row {
                         if col_idx == app.result_selected_col {
@@ -767,17 +1265,25 @@ fn draw_result_table(frame: &mut Frame, app: &App, result: &crate::db::QueryResu
                         } else {
                             Style::default().bg(theme.bg_selected)
                         }
+                    } else if let Some(color) = change_color(row_idx, col_idx) {
+                        Style::default().fg(color)
+                    } else if let Some(style) = result
+                        .columns
+                        .get(col_idx)
+                        .and_then(|col| cell_rule_style(&app.format_rules, &col.name, cell))
+                    {
+                        style
                     } else if matches!(cell, crate::db::CellValue::Null) {
                         Style::default().fg(theme.text_muted)
                     } else {
                         Style::default().fg(theme.text_primary)
                     };
 
-                    Cell::from(truncated).style(style)
+                    Cell::from(text).style(style)
                 })
                 .collect();
 
-            Row::new(cells).height(1)
+            Row::new(cells).height(height as u16)
         })
         .collect();
 
@@ -788,45 +1294,191 @@ fn draw_result_table(frame: &mut Frame, app: &App, result: &crate::db::QueryResu
     frame.render_widget(table, area);
 }
 
-fn draw_structured_error(
+/// Eighth-block characters for the fractional end of a chart bar.
+const BAR_EIGHTHS: [&str; 8] = ["", "▏", "▎", "▍", "▌", "▋", "▊", "▉"];
+
+/// Draw `result` as horizontal bars of `value_col`, one per row, labelled
+/// by `label_col` or the row number. Bars scale to the largest magnitude in
+/// the whole result so they stay comparable while scrolling; negative
+/// values are drawn in the error color.
+fn draw_result_chart(
     frame: &mut Frame,
     app: &App,
-    error: &crate::db::StructuredError,
+    result: &crate::db::QueryResult,
+    label_col: Option<usize>,
+    value_col: usize,
     area: Rect,
 ) {
     let theme = &app.theme;
-    let mut lines: Vec<Line> = Vec::new();
+    let display_options = app.display_options();
+    let now = chrono::Utc::now();
+    let column_name = |col: usize| {
+        result
+            .columns
+            .get(col)
+            .map_or(String::new(), |c| c.name.clone())
+    };
 
-    // Category + severity header
-    let category_color = match error.category {
-        crate::db::ErrorCategory::Syntax => theme.error,
-        crate::db::ErrorCategory::Semantic => theme.warning,
-        crate::db::ErrorCategory::Execution => theme.error,
-        crate::db::ErrorCategory::Transaction => theme.warning,
-        crate::db::ErrorCategory::Connection => theme.error,
-        crate::db::ErrorCategory::Unknown => theme.error,
+    let label = |row_idx: usize, row: &[crate::db::CellValue]| match label_col {
+        Some(col) => row
+            .get(col)
+            .map(|cell| cell.display_with(display_options, now))
+            .unwrap_or_default(),
+        None => (row_idx + 1).to_string(),
     };
+    let values: Vec<Option<f64>> = result
+        .rows
+        .iter()
+        .map(|row| row.get(value_col).and_then(|cell| cell.as_f64()))
+        .collect();
+    let max = values
+        .iter()
+        .flatten()
+        .fold(0.0_f64, |max, value| max.max(value.abs()));
 
-    lines.push(Line::from(vec![
+    let visible_height = area.height.saturating_sub(2) as usize;
+    let start_row = app.result_scroll_y.min(result.rows.len());
+    let shown = start_row..(start_row + visible_height).min(result.rows.len());
+
+    let label_header = label_col.map_or("#".to_string(), column_name);
+    let label_width = result.rows[shown.clone()]
+        .iter()
+        .enumerate()
+        .map(|(i, row)| label(start_row + i, row).chars().count())
+        .chain([label_header.chars().count()])
+        .max()
+        .unwrap_or(0)
+        .min(24)
+        .min(area.width as usize / 3);
+    let value_texts: Vec<String> = result.rows[shown.clone()]
+        .iter()
+        .map(|row| {
+            row.get(value_col)
+                .map(|cell| cell.display_with(display_options, now))
+                .unwrap_or_default()
+        })
+        .collect();
+    let value_width = value_texts
+        .iter()
+        .map(|text| text.chars().count())
+        .max()
+        .unwrap_or(0);
+    let bar_width = (area.width as usize).saturating_sub(label_width + value_width + 3);
+
+    let mut lines = vec![Line::from(vec![
         Span::styled(
-            format!(" {} ", error.category),
+            format!("{:<width$} ", label_header, width = label_width),
             Style::default()
-                .fg(category_color)
+                .fg(theme.text_primary)
                 .add_modifier(Modifier::BOLD),
         ),
-        if !error.code.is_empty() {
-            Span::styled(
-                format!("[{}] ", error.code),
-                Style::default().fg(theme.text_muted),
-            )
-        } else {
-            Span::raw("")
-        },
-    ]));
-
-    // Main error message
-    lines.push(Line::from(Span::styled(
-        format!(" {}", error.message),
+        Span::styled(
+            column_name(value_col),
+            Style::default()
+                .fg(theme.text_accent)
+                .add_modifier(Modifier::BOLD),
+        ),
+    ])
+    .style(Style::default().bg(theme.bg_secondary))];
+
+    for (offset, (row, value_text)) in result.rows[shown.clone()]
+        .iter()
+        .zip(&value_texts)
+        .enumerate()
+    {
+        let row_idx = start_row + offset;
+        let text: String = label(row_idx, row).chars().take(label_width).collect();
+        let value = values[row_idx];
+        let eighths = match value {
+            Some(v) if max > 0.0 => (v.abs() / max * bar_width as f64 * 8.0).round() as usize,
+            _ => 0,
+        };
+        let bar = format!("{}{}", "█".repeat(eighths / 8), BAR_EIGHTHS[eighths % 8]);
+        let bar_color = if value.is_some_and(|v| v < 0.0) {
+            theme.error
+        } else {
+            theme.text_accent
+        };
+        let bar_len = bar.chars().count();
+        let value_style = if value.is_none() {
+            Style::default().fg(theme.text_muted)
+        } else {
+            Style::default().fg(theme.text_primary)
+        };
+
+        let line = Line::from(vec![
+            Span::styled(
+                format!("{:<width$} ", text, width = label_width),
+                Style::default().fg(theme.text_secondary),
+            ),
+            Span::styled(bar, Style::default().fg(bar_color)),
+            Span::raw(" ".repeat(bar_width.saturating_sub(bar_len) + 1)),
+            Span::styled(value_text.clone(), value_style),
+        ]);
+        lines.push(if row_idx == app.result_selected_row {
+            line.style(Style::default().bg(theme.bg_selected))
+        } else {
+            line
+        });
+    }
+
+    frame.render_widget(Paragraph::new(lines), area);
+}
+
+/// Hard-wrap `text` into lines of at most `width` characters, keeping its
+/// own line breaks.
+fn wrap_cell_text(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    for line in text.split('\n') {
+        let chars: Vec<char> = line.chars().collect();
+        if chars.is_empty() {
+            lines.push(String::new());
+        }
+        lines.extend(chars.chunks(width).map(|chunk| chunk.iter().collect()));
+    }
+    lines
+}
+
+fn draw_structured_error(
+    frame: &mut Frame,
+    app: &App,
+    error: &crate::db::StructuredError,
+    area: Rect,
+) {
+    let theme = &app.theme;
+    let mut lines: Vec<Line> = Vec::new();
+
+    // Category + severity header
+    let category_color = match error.category {
+        crate::db::ErrorCategory::Syntax => theme.error,
+        crate::db::ErrorCategory::Semantic => theme.warning,
+        crate::db::ErrorCategory::Execution => theme.error,
+        crate::db::ErrorCategory::Transaction => theme.warning,
+        crate::db::ErrorCategory::Connection => theme.error,
+        crate::db::ErrorCategory::Unknown => theme.error,
+    };
+
+    lines.push(Line::from(vec![
+        Span::styled(
+            format!(" {} ", error.category),
+            Style::default()
+                .fg(category_color)
+                .add_modifier(Modifier::BOLD),
+        ),
+        if !error.code.is_empty() {
+            Span::styled(
+                format!("[{}] ", error.code),
+                Style::default().fg(theme.text_muted),
+            )
+        } else {
+            Span::raw("")
+        },
+    ]));
+
+    // Main error message
+    lines.push(Line::from(Span::styled(
+        format!(" {}", error.message),
         Style::default().fg(theme.text_primary),
     )));
 
@@ -842,26 +1494,36 @@ fn draw_structured_error(
 
     // Detail
     if let Some(detail) = &error.detail {
-        lines.push(Line::from(vec![
-            Span::styled(
-                " Detail: ",
-                Style::default()
-                    .fg(theme.text_secondary)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::styled(detail.clone(), Style::default().fg(theme.text_primary)),
-        ]));
+        lines.extend(labeled_lines(
+            " Detail: ",
+            detail,
+            Style::default()
+                .fg(theme.text_secondary)
+                .add_modifier(Modifier::BOLD),
+            Style::default().fg(theme.text_primary),
+        ));
     }
 
-    // Hint
+    // Hint: usually tells the user how to fix the error, so it stands out
     if let Some(hint) = &error.hint {
-        lines.push(Line::from(vec![
-            Span::styled(
-                " Hint: ",
-                Style::default().fg(theme.info).add_modifier(Modifier::BOLD),
-            ),
-            Span::styled(hint.clone(), Style::default().fg(theme.text_primary)),
-        ]));
+        lines.extend(labeled_lines(
+            " Hint: ",
+            hint,
+            Style::default().fg(theme.info).add_modifier(Modifier::BOLD),
+            Style::default().fg(theme.info),
+        ));
+    }
+
+    // Plain-language gloss of the SQLSTATE code
+    if let Some(suggestion) = error.suggestion() {
+        lines.extend(labeled_lines(
+            " Suggestion: ",
+            suggestion,
+            Style::default()
+                .fg(theme.text_secondary)
+                .add_modifier(Modifier::BOLD),
+            Style::default().fg(theme.text_secondary),
+        ));
     }
 
     // Schema/table/column context
@@ -921,6 +1583,27 @@ fn draw_structured_error(
     frame.render_widget(paragraph, area);
 }
 
+/// A `label: text` field whose text may span several lines; continuation
+/// lines are indented to align under the first.
+fn labeled_lines<'a>(
+    label: &'a str,
+    text: &str,
+    label_style: Style,
+    text_style: Style,
+) -> Vec<Line<'a>> {
+    text.lines()
+        .enumerate()
+        .map(|(i, part)| {
+            let prefix = if i == 0 {
+                Span::styled(label, label_style)
+            } else {
+                Span::raw(" ".repeat(label.len()))
+            };
+            Line::from(vec![prefix, Span::styled(part.to_string(), text_style)])
+        })
+        .collect()
+}
+
 fn draw_explain_plan(frame: &mut Frame, app: &App, plan: &QueryPlan, area: Rect) {
     let theme = &app.theme;
     let mut lines: Vec<Line> = Vec::new();
@@ -960,7 +1643,7 @@ fn draw_explain_plan(frame: &mut Frame, app: &App, plan: &QueryPlan, area: Rect)
     // Hint
     lines.push(Line::from(""));
     lines.push(Line::from(Span::styled(
-        "Ctrl+E: Toggle raw/visual view",
+        "Ctrl+E: Toggle raw/visual view  Up/Down: Scroll",
         Style::default().fg(theme.text_muted),
     )));
 
@@ -1096,22 +1779,48 @@ fn draw_status_bar(frame: &mut Frame, app: &App, area: Rect) {
     let left_text = if app.is_loading {
         let spinner = SPINNER_FRAMES[app.spinner_frame];
         format!(" {} {}", spinner, app.loading_message)
+    } else if app.offline_mode {
+        " Offline: parse & analyze only".to_string()
     } else if app.connection.is_connected() {
-        format!(" Connected: {}", app.connection.config.display_string())
+        let latency = match (app.connection.last_ping, app.connection.last_ping_at) {
+            (Some(ping), _) => format!(" ({} ms)", ping.as_millis()),
+            (None, Some(_)) => " (not responding)".to_string(),
+            (None, None) => String::new(),
+        };
+        format!(
+            " Connected: {}{}",
+            app.connection.config.display_string(),
+            latency
+        )
     } else {
         " Disconnected".to_string()
     };
 
+    let stalled = app.connection.last_ping.is_none() && app.connection.last_ping_at.is_some();
     let left_style = if app.is_loading {
         Style::default().fg(theme.info).bg(theme.bg_secondary)
+    } else if app.connection.is_connected() && stalled {
+        Style::default().fg(theme.warning).bg(theme.bg_secondary)
     } else if app.connection.is_connected() {
         Style::default().fg(theme.success).bg(theme.bg_secondary)
     } else {
         Style::default().fg(theme.text_muted).bg(theme.bg_secondary)
     };
 
-    // Right section: help hints
-    let right_text = "? Help | Ctrl+Q/D Quit ";
+    // Right section: selection size, last run and clock, then help hints
+    let mut right_parts = Vec::new();
+    if let Some(selection) = app
+        .selection_summary()
+        .filter(|_| app.focus == Focus::Editor)
+    {
+        right_parts.push(selection);
+    }
+    if let Some(at) = app.last_query_at {
+        right_parts.push(format!("Last run {}", at.format("%H:%M:%S")));
+    }
+    right_parts.push(chrono::Local::now().format("%H:%M:%S").to_string());
+    right_parts.push("? Help | Ctrl+Q/D Quit ".to_string());
+    let right_text = right_parts.join(" | ");
 
     // Calculate padding
     let left_len = left_text.len() as u16;
@@ -1125,7 +1834,7 @@ fn draw_status_bar(frame: &mut Frame, app: &App, area: Rect) {
             Style::default().bg(theme.bg_secondary),
         ),
         Span::styled(
-            right_text.to_string(),
+            right_text,
             Style::default().fg(theme.text_muted).bg(theme.bg_secondary),
         ),
     ]);
@@ -1141,7 +1850,7 @@ fn draw_connection_dialog(frame: &mut Frame, app: &App) {
     // Calculate dialog size and position (taller to fit saved connections list)
     let area = frame.area();
     let dialog_width = 80.min(area.width.saturating_sub(4));
-    let dialog_height = 25.min(area.height.saturating_sub(4));
+    let dialog_height = 27.min(area.height.saturating_sub(4));
 
     let dialog_x = (area.width - dialog_width) / 2;
     let dialog_y = (area.height - dialog_height) / 2;
@@ -1177,6 +1886,7 @@ fn draw_connection_dialog(frame: &mut Frame, app: &App) {
             Constraint::Length(2), // Username
             Constraint::Length(2), // Password
             Constraint::Length(2), // SSL Mode
+            Constraint::Length(2), // Environment
             Constraint::Length(1), // Status message
             Constraint::Length(1), // Buttons
             Constraint::Min(0),    // Saved connections
@@ -1273,6 +1983,29 @@ fn draw_connection_dialog(frame: &mut Frame, app: &App) {
     let ssl_paragraph = Paragraph::new(ssl_text).style(ssl_style);
     frame.render_widget(ssl_paragraph, chunks[6]);
 
+    // Environment field (field index 7)
+    let env_focused = dialog.field_index == 7;
+    let (env_value, env_color) = match dialog.config.environment {
+        Some(env) => (env.label(), theme.environment_color(env)),
+        None => ("None", theme.text_primary),
+    };
+    let env_hint = if env_focused {
+        Span::styled(" (←/→ to change)", Style::default().fg(theme.text_accent))
+    } else {
+        Span::raw("")
+    };
+    let env_label_style = if env_focused {
+        Style::default().fg(theme.text_accent)
+    } else {
+        Style::default().fg(theme.text_primary)
+    };
+    let env_line = Line::from(vec![
+        Span::styled(format!(" {:12} ", "Environment:"), env_label_style),
+        Span::styled(env_value, Style::default().fg(env_color)),
+        env_hint,
+    ]);
+    frame.render_widget(Paragraph::new(env_line), chunks[7]);
+
     // Draw inline status message
     if let Some((ref msg, ref status_type)) = dialog.status_message {
         let color = match status_type {
@@ -1288,7 +2021,7 @@ fn draw_connection_dialog(frame: &mut Frame, app: &App) {
             format!(" {}", msg)
         };
         let status = Paragraph::new(status_line).style(Style::default().fg(color));
-        frame.render_widget(status, chunks[7]);
+        frame.render_widget(status, chunks[8]);
     }
 
     // Draw dynamic hint text
@@ -1297,14 +2030,14 @@ fn draw_connection_dialog(frame: &mut Frame, app: &App) {
     } else if dialog.selected_saved.is_some() {
         " Enter to load | Del to delete | Tab to switch fields | Esc to cancel "
     } else {
-        " Enter to connect | Tab to switch fields | Esc to cancel "
+        " Enter connect | Ctrl+T test | Ctrl+S save | Ctrl+O offline | Esc cancel "
     };
     let button = Paragraph::new(button_text).style(Style::default().fg(theme.text_muted));
-    frame.render_widget(button, chunks[8]);
+    frame.render_widget(button, chunks[9]);
 
     // Draw saved connections list
     if !dialog.saved_connections.is_empty() {
-        let saved_area = chunks[9];
+        let saved_area = chunks[10];
 
         // Title line
         let title = Paragraph::new(" Saved connections (↑/↓ to select):")
@@ -1409,7 +2142,7 @@ fn draw_table_inspector(frame: &mut Frame, app: &App) {
 
     let area = frame.area();
     let width = 70.min(area.width.saturating_sub(4));
-    let height = (area.height - 4).min(30);
+    let height = area.height.saturating_sub(4).min(30);
     let x = (area.width - width) / 2;
     let y = (area.height - height) / 2;
     let dialog_area = Rect::new(x, y, width, height);
@@ -1446,11 +2179,23 @@ fn draw_table_inspector(frame: &mut Frame, app: &App) {
         }
         lines.push(Line::from(""));
         lines.push(Line::from(Span::styled(
-            "  [D] Structure  [Ctrl+C] Copy DDL  [Esc] Close",
+            "  [D] Structure  [Ctrl+C] Copy DDL  [S] Copy schema DDL  [Esc] Close",
             Style::default().fg(theme.text_muted),
         )));
     } else {
         // Structure view
+        let comment_style = Style::default()
+            .fg(theme.text_muted)
+            .add_modifier(Modifier::ITALIC);
+        if let Some(comment) = &inspector.comment {
+            for comment_line in comment.lines() {
+                lines.push(Line::from(Span::styled(
+                    format!("  {}", comment_line),
+                    comment_style,
+                )));
+            }
+            lines.push(Line::from(""));
+        }
         lines.push(Line::from(Span::styled(
             "  COLUMNS",
             Style::default()
@@ -1476,6 +2221,14 @@ fn draw_table_inspector(frame: &mut Frame, app: &App) {
                 Style::default().fg(theme.text_primary)
             };
             lines.push(Line::from(Span::styled(line_text, style)));
+            if let Some(comment) = &col.comment {
+                for comment_line in comment.lines() {
+                    lines.push(Line::from(Span::styled(
+                        format!("    -- {}", comment_line),
+                        comment_style,
+                    )));
+                }
+            }
         }
 
         if !inspector.indexes.is_empty() {
@@ -1505,7 +2258,7 @@ fn draw_table_inspector(frame: &mut Frame, app: &App) {
 
         lines.push(Line::from(""));
         lines.push(Line::from(Span::styled(
-            "  [D] DDL  [Esc] Close",
+            "  [D] DDL  [Ctrl+C] Copy DDL  [S] Copy schema DDL  [Esc] Close",
             Style::default().fg(theme.text_muted),
         )));
     }
@@ -1521,18 +2274,343 @@ fn draw_table_inspector(frame: &mut Frame, app: &App) {
     frame.render_widget(paragraph, inner);
 }
 
+fn draw_cell_detail(frame: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let Some(detail) = &app.cell_detail else {
+        return;
+    };
+
+    let area = frame.area();
+    let width = (area.width * 4 / 5)
+        .max(40)
+        .min(area.width.saturating_sub(2));
+    let height = (area.height * 4 / 5)
+        .max(10)
+        .min(area.height.saturating_sub(2));
+    let x = (area.width - width) / 2;
+    let y = (area.height - height) / 2;
+    let dialog_area = Rect::new(x, y, width, height);
+
+    frame.render_widget(Clear, dialog_area);
+
+    let hints = if detail.json.is_none() {
+        " ↑/↓ Scroll | Ctrl+C Copy | Esc Close "
+    } else if detail.show_json {
+        " j Raw | ↑/↓ Scroll | Ctrl+C Copy | Esc Close "
+    } else {
+        " j Pretty JSON | ↑/↓ Scroll | Ctrl+C Copy | Esc Close "
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.border_focused))
+        .title(format!(" {} ({}) ", detail.column, detail.type_name))
+        .title_style(
+            Style::default()
+                .fg(theme.text_accent)
+                .add_modifier(Modifier::BOLD),
+        )
+        .title_bottom(Line::from(Span::styled(
+            hints,
+            Style::default().fg(theme.text_muted),
+        )))
+        .style(Style::default().bg(theme.bg_primary));
+
+    let inner = block.inner(dialog_area);
+    frame.render_widget(block, dialog_area);
+
+    let lines: Vec<Line> = if detail.show_json {
+        detail
+            .shown()
+            .lines()
+            .map(|line| Line::from(json_line_spans(line, theme)))
+            .collect()
+    } else {
+        detail
+            .shown()
+            .lines()
+            .map(|line| Line::styled(line.to_string(), Style::default().fg(theme.text_primary)))
+            .collect()
+    };
+    let text = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .scroll((detail.scroll as u16, 0));
+    frame.render_widget(text, inner);
+}
+
+/// Color one line of pretty-printed JSON: keys, strings, numbers and
+/// `true`/`false`/`null`.
+fn json_line_spans(line: &str, theme: &Theme) -> Vec<Span<'static>> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut spans = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        let style = match chars[i] {
+            '"' => {
+                i += 1;
+                while i < chars.len() && chars[i] != '"' {
+                    i += if chars[i] == '\\' { 2 } else { 1 };
+                }
+                i = (i + 1).min(chars.len());
+                let is_key = chars[i..].iter().find(|c| !c.is_whitespace()) == Some(&':');
+                if is_key {
+                    Style::default().fg(theme.text_accent)
+                } else {
+                    Style::default().fg(theme.syntax_string)
+                }
+            }
+            c if c == '-' || c.is_ascii_digit() => {
+                while i < chars.len()
+                    && (chars[i].is_ascii_alphanumeric() || "+-.".contains(chars[i]))
+                {
+                    i += 1;
+                }
+                Style::default().fg(theme.syntax_number)
+            }
+            c if c.is_ascii_alphabetic() => {
+                while i < chars.len() && chars[i].is_ascii_alphabetic() {
+                    i += 1;
+                }
+                Style::default().fg(theme.syntax_keyword)
+            }
+            _ => {
+                i += 1;
+                Style::default().fg(theme.text_muted)
+            }
+        };
+        spans.push(Span::styled(
+            chars[start..i].iter().collect::<String>(),
+            style,
+        ));
+    }
+    spans
+}
+
+fn draw_result_compare(frame: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let compare = match &app.result_compare {
+        Some(c) => c,
+        None => return,
+    };
+
+    let area = frame.area();
+    let width = 70.min(area.width.saturating_sub(4));
+    let height = 24.min(area.height.saturating_sub(2));
+    let x = (area.width - width) / 2;
+    let y = (area.height - height) / 2;
+    let dialog_area = Rect::new(x, y, width, height);
+
+    frame.render_widget(Clear, dialog_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.border_focused))
+        .title(format!(
+            " Pinned #{} -> Current result ",
+            compare.pinned_index + 1
+        ))
+        .title_style(
+            Style::default()
+                .fg(theme.text_accent)
+                .add_modifier(Modifier::BOLD),
+        )
+        .title_bottom(Line::from(Span::styled(
+            " ←/→ Pinned result | ↑/↓ Scroll | Esc Close ",
+            Style::default().fg(theme.text_muted),
+        )))
+        .style(Style::default().bg(theme.bg_primary));
+
+    let inner = block.inner(dialog_area);
+    frame.render_widget(block, dialog_area);
+
+    let lines: Vec<Line> = compare
+        .lines
+        .iter()
+        .skip(compare.scroll)
+        .take(inner.height as usize)
+        .map(|line| {
+            // Row diff entries are colored like a unified diff
+            let diff_color = if line.starts_with("+ ") {
+                Some(theme.success)
+            } else if line.starts_with("- ") {
+                Some(theme.error)
+            } else {
+                None
+            };
+            if let Some(color) = diff_color {
+                return Line::from(Span::styled(
+                    format!(" {}", line),
+                    Style::default().fg(color),
+                ));
+            }
+            let (label, value) = line.split_once(':').unwrap_or((line.as_str(), ""));
+            Line::from(vec![
+                Span::styled(
+                    format!(" {}:", label),
+                    Style::default()
+                        .fg(theme.text_secondary)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(value.to_string(), Style::default().fg(theme.text_primary)),
+            ])
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+fn draw_destructive_confirm(frame: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let confirm = match &app.destructive_confirm {
+        Some(c) => c,
+        None => return,
+    };
+
+    let area = frame.area();
+    let width = 70.min(area.width.saturating_sub(4));
+    let height = 9.min(area.height.saturating_sub(2));
+    let x = (area.width - width) / 2;
+    let y = (area.height - height) / 2;
+    let dialog_area = Rect::new(x, y, width, height);
+
+    frame.render_widget(Clear, dialog_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.error))
+        .title(format!(" Confirm {} ", confirm.statement.action))
+        .title_style(
+            Style::default()
+                .fg(theme.error)
+                .add_modifier(Modifier::BOLD),
+        )
+        .title_bottom(Line::from(Span::styled(
+            " Enter Confirm | Esc Cancel ",
+            Style::default().fg(theme.text_muted),
+        )))
+        .style(Style::default().bg(theme.bg_primary));
+
+    let inner = block.inner(dialog_area);
+    frame.render_widget(block, dialog_area);
+
+    let input_style = if confirm.is_confirmed() {
+        Style::default().fg(theme.success)
+    } else {
+        Style::default().fg(theme.text_primary)
+    };
+    let lines = vec![
+        Line::from(vec![
+            Span::styled(
+                format!(" {} ", confirm.statement.action),
+                Style::default().fg(theme.text_primary),
+            ),
+            Span::styled(
+                confirm.statement.target.clone(),
+                Style::default()
+                    .fg(theme.warning)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        Line::from(Span::styled(
+            " This cannot be undone.",
+            Style::default().fg(theme.error),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            " Type the name to confirm:",
+            Style::default().fg(theme.text_secondary),
+        )),
+        Line::from(vec![
+            Span::styled(" > ", Style::default().fg(theme.text_accent)),
+            Span::styled(confirm.input.clone(), input_style),
+        ]),
+    ];
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), inner);
+
+    let cursor_x = inner.x + 3 + confirm.input.chars().count() as u16;
+    if inner.height > 4 && cursor_x < inner.x + inner.width {
+        frame.set_cursor_position((cursor_x, inner.y + 4));
+    }
+}
+
+fn draw_ast_tree(frame: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let tree = match &app.ast_tree {
+        Some(t) => t,
+        None => return,
+    };
+
+    let area = frame.area();
+    let width = 80.min(area.width.saturating_sub(4));
+    let height = area.height.saturating_sub(4).min(30);
+    let x = (area.width - width) / 2;
+    let y = (area.height - height) / 2;
+    let dialog_area = Rect::new(x, y, width, height);
+
+    frame.render_widget(Clear, dialog_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.border_focused))
+        .title(" Query AST ")
+        .title_style(
+            Style::default()
+                .fg(theme.text_accent)
+                .add_modifier(Modifier::BOLD),
+        )
+        .title_bottom(Line::from(Span::styled(
+            " ↑/↓ Scroll | Esc Close ",
+            Style::default().fg(theme.text_muted),
+        )))
+        .style(Style::default().bg(theme.bg_primary));
+
+    let inner = block.inner(dialog_area);
+    frame.render_widget(block, dialog_area);
+
+    let lines: Vec<Line> = tree
+        .lines
+        .iter()
+        .skip(tree.scroll)
+        .take(inner.height as usize)
+        .map(|line| {
+            // Split the tree connectors from the node label
+            let label_start = line
+                .char_indices()
+                .find(|(_, c)| !matches!(c, ' ' | '│' | '├' | '└' | '─'))
+                .map(|(i, _)| i)
+                .unwrap_or(line.len());
+            let (prefix, label) = line.split_at(label_start);
+            let label_style = if label.starts_with("RAW") {
+                Style::default()
+                    .fg(theme.warning)
+                    .add_modifier(Modifier::BOLD)
+            } else if prefix.is_empty() {
+                Style::default()
+                    .fg(theme.syntax_keyword)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(theme.text_primary)
+            };
+            Line::from(vec![
+                Span::styled(
+                    format!(" {}", prefix),
+                    Style::default().fg(theme.text_muted),
+                ),
+                Span::styled(label.to_string(), label_style),
+            ])
+        })
+        .collect();
+
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
 fn draw_export_picker(frame: &mut Frame, app: &App) {
     let theme = &app.theme;
     let area = frame.area();
 
-    let row_count = app
-        .results
-        .get(app.current_result)
-        .map(|r| r.row_count)
-        .unwrap_or(0);
+    let row_count = app.current_query_result().map(|r| r.row_count).unwrap_or(0);
 
-    let picker_width = 40.min(area.width.saturating_sub(4));
-    let picker_height = (EXPORT_FORMATS.len() as u16 + 4).min(area.height.saturating_sub(4));
+    let picker_width = 60.min(area.width.saturating_sub(4));
+    let picker_height = (EXPORT_FORMATS.len() as u16 + 5).min(area.height.saturating_sub(4));
 
     let picker_x = (area.width - picker_width) / 2;
     let picker_y = (area.height - picker_height) / 2;
@@ -1576,10 +2654,22 @@ fn draw_export_picker(frame: &mut Frame, app: &App) {
         inner.x,
         inner.y,
         inner.width,
-        inner.height.saturating_sub(1),
+        inner.height.saturating_sub(2),
     );
     frame.render_widget(list, list_area);
 
+    // Compression toggle, applied to file exports
+    let compress_area = Rect::new(
+        inner.x,
+        inner.y + inner.height.saturating_sub(2),
+        inner.width,
+        1,
+    );
+    let checkbox = if app.export_compress { "[x]" } else { "[ ]" };
+    let compress = Paragraph::new(format!("  {} Gzip files (.gz)", checkbox))
+        .style(Style::default().fg(theme.text_secondary));
+    frame.render_widget(compress, compress_area);
+
     // Hint text at bottom
     let hint_area = Rect::new(
         inner.x,
@@ -1587,11 +2677,149 @@ fn draw_export_picker(frame: &mut Frame, app: &App) {
         inner.width,
         1,
     );
-    let hint = Paragraph::new(" Enter: Export | 1-5: Quick select | Esc: Cancel")
-        .style(Style::default().fg(theme.text_muted));
+    let hint = Paragraph::new(format!(
+        " Enter: Export | 1-{}: Quick select | z: Gzip | Esc: Cancel",
+        EXPORT_FORMATS.len()
+    ))
+    .style(Style::default().fg(theme.text_muted));
     frame.render_widget(hint, hint_area);
 }
 
+fn draw_command_palette(frame: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = frame.area();
+    let palette = match &app.command_palette {
+        Some(p) => p,
+        None => return,
+    };
+
+    let palette_width = 60.min(area.width.saturating_sub(4));
+    let palette_height = (PALETTE_ACTIONS.len() as u16 + 4).min(area.height.saturating_sub(4));
+    let palette_area = Rect::new(
+        (area.width - palette_width) / 2,
+        (area.height - palette_height) / 3,
+        palette_width,
+        palette_height,
+    );
+    frame.render_widget(Clear, palette_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.border_focused))
+        .title(" Command Palette ")
+        .title_style(
+            Style::default()
+                .fg(theme.text_accent)
+                .add_modifier(Modifier::BOLD),
+        )
+        .title_bottom(Line::from(Span::styled(
+            " Enter: Run | ↑/↓: Select | Esc: Close ",
+            Style::default().fg(theme.text_muted),
+        )))
+        .style(Style::default().bg(theme.bg_primary));
+    let inner = block.inner(palette_area);
+    frame.render_widget(block, palette_area);
+
+    // Query input line
+    let input = Paragraph::new(Line::from(vec![
+        Span::styled("> ", Style::default().fg(theme.text_accent)),
+        Span::styled(
+            palette.query.as_str(),
+            Style::default().fg(theme.text_primary),
+        ),
+    ]));
+    frame.render_widget(input, Rect::new(inner.x, inner.y, inner.width, 1));
+    frame.set_cursor_position((inner.x + 2 + palette.query.chars().count() as u16, inner.y));
+
+    let list_area = Rect::new(
+        inner.x,
+        inner.y + 1,
+        inner.width,
+        inner.height.saturating_sub(1),
+    );
+    if palette.matches.is_empty() {
+        let empty =
+            Paragraph::new("  No matching actions").style(Style::default().fg(theme.text_muted));
+        frame.render_widget(empty, list_area);
+        return;
+    }
+
+    let width = list_area.width as usize;
+    let items: Vec<ListItem> = palette
+        .matches
+        .iter()
+        .enumerate()
+        .map(|(i, action)| {
+            let label = format!("  {}", action.label());
+            let keys = app
+                .keymap
+                .describe(*action)
+                .unwrap_or_else(|| action.fixed_keybinding().to_string());
+            let keys = format!("{} ", keys);
+            let padding = width.saturating_sub(label.chars().count() + keys.chars().count());
+            let (label_style, bg) = if i == palette.selected {
+                (
+                    Style::default()
+                        .fg(theme.text_accent)
+                        .add_modifier(Modifier::BOLD),
+                    theme.bg_selected,
+                )
+            } else {
+                (Style::default().fg(theme.text_primary), theme.bg_primary)
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(label, label_style),
+                Span::raw(" ".repeat(padding)),
+                Span::styled(keys, Style::default().fg(theme.text_muted)),
+            ]))
+            .style(Style::default().bg(bg))
+        })
+        .collect();
+    frame.render_widget(List::new(items), list_area);
+}
+
+fn draw_go_to_line(frame: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = frame.area();
+    let input = match &app.go_to_line {
+        Some(input) => input,
+        None => return,
+    };
+
+    let width = 36.min(area.width.saturating_sub(4));
+    let prompt_area = Rect::new(
+        (area.width - width) / 2,
+        (area.height.saturating_sub(3)) / 3,
+        width,
+        3.min(area.height),
+    );
+    frame.render_widget(Clear, prompt_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.border_focused))
+        .title(format!(" Go to Line (1-{}) ", app.editor.line_count()))
+        .title_style(
+            Style::default()
+                .fg(theme.text_accent)
+                .add_modifier(Modifier::BOLD),
+        )
+        .title_bottom(Line::from(Span::styled(
+            " Enter: Go | Esc: Cancel ",
+            Style::default().fg(theme.text_muted),
+        )))
+        .style(Style::default().bg(theme.bg_primary));
+    let inner = block.inner(prompt_area);
+    frame.render_widget(block, prompt_area);
+
+    let line = Paragraph::new(Line::from(vec![
+        Span::styled("> ", Style::default().fg(theme.text_accent)),
+        Span::styled(input.as_str(), Style::default().fg(theme.text_primary)),
+    ]));
+    frame.render_widget(line, inner);
+    frame.set_cursor_position((inner.x + 2 + input.len() as u16, inner.y));
+}
+
 fn draw_help_overlay(frame: &mut Frame, app: &App) {
     let theme = &app.theme;
     let area = frame.area();
@@ -1614,6 +2842,8 @@ fn draw_help_overlay(frame: &mut Frame, app: &App) {
         " GLOBAL",
         "   Ctrl+Q/D       Quit",
         "   Ctrl+C         Connect dialog",
+        "   Ctrl+P         Command palette",
+        "   F12            Cancel all and disconnect",
         "   ?              Toggle help",
         "",
         " NAVIGATION",
@@ -1622,28 +2852,52 @@ fn draw_help_overlay(frame: &mut Frame, app: &App) {
         "   (Sidebar → Editor → Results → ...)",
         "",
         " EDITOR",
-        "   F5/Ctrl+Enter  Execute query at cursor",
+        "   F5/Ctrl+Enter  Execute selection or query at cursor",
+        "   Shift+F5       Execute all statements",
+        "   Ctrl+F5        Execute all in one transaction",
+        "   F6             Estimate cost (EXPLAIN)",
+        "   Shift+F6       Count rows of query",
         "   Ctrl+L         Clear editor",
+        "   Ctrl+O         Toggle offline parse mode",
+        "   Ctrl+T         Show query AST tree",
+        "   Ctrl+W         Trim trailing whitespace",
+        "   Alt+U / Alt+L  Uppercase / lowercase selection",
         "   Ctrl+↑/↓       Navigate history",
         "   Ctrl+Shift+↑/↓ Resize editor/results",
         "   Ctrl+C/X/V     Copy/Cut/Paste",
         "   Ctrl+Z         Undo",
         "   Ctrl+Shift+Z/Y Redo",
         "   Ctrl+A         Select all",
+        "   Alt+S          Select statement at cursor",
+        "   Ctrl+G         Go to line",
+        "   Ctrl+F         Find (Enter/↑/↓ to step)",
         "   Ctrl+Space     Trigger autocomplete",
         "   Tab            Insert spaces",
         "",
         " SIDEBAR",
         "   1/2/3          Switch tabs",
         "   Enter          Select item",
+        "   Shift+Enter    Insert column list",
         "   ↑/↓            Navigate",
         "   Ctrl+I         Inspect table (DDL)",
+        "   n              Count table rows",
+        "   p              Peek at table rows",
+        "   x              Re-run history entry",
         "",
         " RESULTS",
         "   Tab/Shift+Tab  Next/Prev column",
         "   Arrow keys     Navigate cells",
         "   Esc            Back to editor",
-        "   Ctrl+C         Copy cell value",
+        "   Ctrl+C         Copy cell value / error",
+        "   Enter          Cell detail (j: pretty JSON)",
+        "   p / c          Pin result / Compare with pinned",
+        "   w              Wrap selected row's cells",
+        "   g              Bar chart of selected column",
+        "   h / H          Hide column / Show all",
+        "   t              Relative timestamps",
+        "   + / -          Re-run with LIMIT x2 / ÷2",
+        "   n / N          Re-run next / previous page",
+        "   f / < / >      Column to front / left / right",
         "   Ctrl+E         Toggle EXPLAIN plan view",
         "   Ctrl+S         Export results",
         "   Ctrl+[/]       Prev/Next result set",
@@ -1682,7 +2936,7 @@ fn draw_autocomplete(frame: &mut Frame, app: &App, editor_area: Rect) {
     }
 
     // Position popup below the cursor
-    let cursor_x = editor_area.x + app.editor.cursor_x as u16;
+    let cursor_x = editor_area.x + app.editor.display_column() as u16;
     let cursor_y = editor_area.y + (app.editor.cursor_y - app.editor.scroll_offset) as u16 + 1;
 
     let max_items = ac.suggestions.len().min(8);
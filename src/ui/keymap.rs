@@ -0,0 +1,445 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::config::KeySpec;
+use crate::ui::PaletteAction;
+
+/// Pane a binding applies in. `Global` bindings work from the sidebar,
+/// editor and results.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyContext {
+    Global,
+    Editor,
+    Results,
+}
+
+/// A single key combination such as `Ctrl+Shift+Z` or `F5`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    pub const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    /// Parse `Ctrl+Alt+Shift+<key>` (case-insensitive modifiers). Keys are a
+    /// single character or a name such as `Enter`, `Esc`, `Tab`, `Space`,
+    /// `Up` or `F1`..`F12`. Letters ignore case after a modifier
+    /// (`Ctrl+K` is `Ctrl+k`); a bare uppercase letter means Shift.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = spec.split('+').map(str::trim).collect();
+        // A trailing "+" means the key itself is '+', e.g. "Ctrl++"
+        let (mods, key) = match parts.as_slice() {
+            [rest @ .., "", ""] => (rest, "+"),
+            [rest @ .., key] => (rest, *key),
+            [] => return Err(format!("Empty key binding: '{}'", spec)),
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for m in mods {
+            modifiers |= match m.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("Unknown modifier '{}' in '{}'", m, spec)),
+            };
+        }
+
+        let code = match key.to_ascii_lowercase().as_str() {
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "space" => KeyCode::Char(' '),
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            lower => {
+                let mut chars = key.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => {
+                        if c.is_ascii_uppercase() && mods.is_empty() {
+                            modifiers |= KeyModifiers::SHIFT;
+                        }
+                        KeyCode::Char(c.to_ascii_lowercase())
+                    }
+                    _ => match lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                        Some(n @ 1..=12) => KeyCode::F(n),
+                        _ => return Err(format!("Unknown key '{}' in '{}'", key, spec)),
+                    },
+                }
+            }
+        };
+
+        Ok(Self::new(code, modifiers).normalized())
+    }
+
+    /// Terminals report Shift+letter as an uppercase char and may or may not
+    /// set SHIFT for punctuation, so compare letters lowercased and ignore
+    /// SHIFT on other characters.
+    fn normalized(self) -> Self {
+        match self.code {
+            KeyCode::Char(c) if c.is_ascii_alphabetic() => {
+                let modifiers = if c.is_ascii_uppercase() {
+                    self.modifiers | KeyModifiers::SHIFT
+                } else {
+                    self.modifiers
+                };
+                Self::new(KeyCode::Char(c.to_ascii_lowercase()), modifiers)
+            }
+            KeyCode::Char(c) => Self::new(KeyCode::Char(c), self.modifiers - KeyModifiers::SHIFT),
+            _ => self,
+        }
+    }
+
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        *self == Self::new(key.code, key.modifiers).normalized()
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) if self.modifiers.is_empty() => write!(f, "{}", c),
+            KeyCode::Char(c) => write!(f, "{}", c.to_ascii_uppercase()),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            KeyCode::Enter => write!(f, "Enter"),
+            KeyCode::Esc => write!(f, "Esc"),
+            KeyCode::Tab => write!(f, "Tab"),
+            KeyCode::Backspace => write!(f, "Backspace"),
+            KeyCode::Delete => write!(f, "Delete"),
+            KeyCode::Up => write!(f, "Up"),
+            KeyCode::Down => write!(f, "Down"),
+            KeyCode::Left => write!(f, "Left"),
+            KeyCode::Right => write!(f, "Right"),
+            KeyCode::Home => write!(f, "Home"),
+            KeyCode::End => write!(f, "End"),
+            KeyCode::PageUp => write!(f, "PageUp"),
+            KeyCode::PageDown => write!(f, "PageDown"),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
+const CTRL: KeyModifiers = KeyModifiers::CONTROL;
+const ALT: KeyModifiers = KeyModifiers::ALT;
+const SHIFT: KeyModifiers = KeyModifiers::SHIFT;
+const NONE: KeyModifiers = KeyModifiers::NONE;
+
+const DEFAULT_BINDINGS: &[(PaletteAction, KeyBinding)] = &[
+    (
+        PaletteAction::ExecuteQuery,
+        KeyBinding::new(KeyCode::F(5), NONE),
+    ),
+    (
+        PaletteAction::ExecuteQuery,
+        KeyBinding::new(KeyCode::Enter, CTRL),
+    ),
+    (
+        PaletteAction::ExecuteAll,
+        KeyBinding::new(KeyCode::F(5), SHIFT),
+    ),
+    (
+        PaletteAction::ExecuteAllInTransaction,
+        KeyBinding::new(KeyCode::F(5), CTRL),
+    ),
+    (
+        PaletteAction::CostCheck,
+        KeyBinding::new(KeyCode::F(6), NONE),
+    ),
+    (
+        PaletteAction::CountRows,
+        KeyBinding::new(KeyCode::F(6), SHIFT),
+    ),
+    (
+        PaletteAction::ClearEditor,
+        KeyBinding::new(KeyCode::Char('l'), CTRL),
+    ),
+    (
+        PaletteAction::TrimWhitespace,
+        KeyBinding::new(KeyCode::Char('w'), CTRL),
+    ),
+    (
+        PaletteAction::SelectStatement,
+        KeyBinding::new(KeyCode::Char('s'), ALT),
+    ),
+    (
+        PaletteAction::GoToLine,
+        KeyBinding::new(KeyCode::Char('g'), CTRL),
+    ),
+    (
+        PaletteAction::Find,
+        KeyBinding::new(KeyCode::Char('f'), CTRL),
+    ),
+    (
+        PaletteAction::ShowAst,
+        KeyBinding::new(KeyCode::Char('t'), CTRL),
+    ),
+    (
+        PaletteAction::ToggleOffline,
+        KeyBinding::new(KeyCode::Char('o'), CTRL),
+    ),
+    (
+        PaletteAction::ExportResults,
+        KeyBinding::new(KeyCode::Char('s'), CTRL),
+    ),
+    (
+        PaletteAction::PinResult,
+        KeyBinding::new(KeyCode::Char('p'), NONE),
+    ),
+    (
+        PaletteAction::HideColumn,
+        KeyBinding::new(KeyCode::Char('h'), NONE),
+    ),
+    (
+        PaletteAction::ShowAllColumns,
+        KeyBinding::new(KeyCode::Char('h'), SHIFT),
+    ),
+    (
+        PaletteAction::PinColumn,
+        KeyBinding::new(KeyCode::Char('f'), NONE),
+    ),
+    (
+        PaletteAction::MoveColumnLeft,
+        KeyBinding::new(KeyCode::Char('<'), NONE),
+    ),
+    (
+        PaletteAction::MoveColumnRight,
+        KeyBinding::new(KeyCode::Char('>'), NONE),
+    ),
+    (
+        PaletteAction::CompareWithPinned,
+        KeyBinding::new(KeyCode::Char('c'), NONE),
+    ),
+    (
+        PaletteAction::ToggleWrap,
+        KeyBinding::new(KeyCode::Char('w'), NONE),
+    ),
+    (
+        PaletteAction::ToggleChart,
+        KeyBinding::new(KeyCode::Char('g'), NONE),
+    ),
+    (
+        PaletteAction::ToggleRelativeTime,
+        KeyBinding::new(KeyCode::Char('t'), NONE),
+    ),
+    (
+        PaletteAction::IncreaseLimit,
+        KeyBinding::new(KeyCode::Char('+'), NONE),
+    ),
+    (
+        PaletteAction::DecreaseLimit,
+        KeyBinding::new(KeyCode::Char('-'), NONE),
+    ),
+    (
+        PaletteAction::NextPage,
+        KeyBinding::new(KeyCode::Char('n'), NONE),
+    ),
+    (
+        PaletteAction::PreviousPage,
+        KeyBinding::new(KeyCode::Char('n'), SHIFT),
+    ),
+    (
+        PaletteAction::CancelAndDisconnect,
+        KeyBinding::new(KeyCode::F(12), NONE),
+    ),
+];
+
+/// Key bindings for the remappable actions: the defaults above, with any
+/// `[keybindings]` overrides from `config.toml` applied.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(PaletteAction, KeyBinding)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            bindings: DEFAULT_BINDINGS.to_vec(),
+        }
+    }
+}
+
+impl Keymap {
+    /// Apply overrides on top of the defaults. Each listed action's bindings
+    /// are replaced; an empty list unbinds it. Unknown action names and
+    /// unparsable keys are skipped and reported.
+    pub fn with_overrides(overrides: &BTreeMap<String, KeySpec>) -> (Self, Vec<String>) {
+        let mut keymap = Self::default();
+        let mut errors = Vec::new();
+
+        for (name, spec) in overrides {
+            let Some(action) = PaletteAction::from_name(name) else {
+                errors.push(format!("Unknown action '{}' in [keybindings]", name));
+                continue;
+            };
+            let mut parsed = Vec::new();
+            for key in spec.keys() {
+                match KeyBinding::parse(key) {
+                    Ok(binding) => parsed.push((action, binding)),
+                    Err(e) => errors.push(e),
+                }
+            }
+            keymap.bindings.retain(|(a, _)| *a != action);
+            keymap.bindings.extend(parsed);
+        }
+
+        (keymap, errors)
+    }
+
+    /// The action bound to `key` in `context`, if any.
+    pub fn action_for(&self, context: KeyContext, key: &KeyEvent) -> Option<PaletteAction> {
+        self.bindings
+            .iter()
+            .find(|(action, binding)| action.context() == context && binding.matches(key))
+            .map(|(action, _)| *action)
+    }
+
+    /// Human-readable bindings for `action`, e.g. "F5 / Ctrl+Enter".
+    pub fn describe(&self, action: PaletteAction) -> Option<String> {
+        let keys: Vec<String> = self
+            .bindings
+            .iter()
+            .filter(|(a, _)| *a == action)
+            .map(|(_, binding)| binding.to_string())
+            .collect();
+        if keys.is_empty() {
+            None
+        } else {
+            Some(keys.join(" / "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_parse_key_binding() {
+        assert_eq!(
+            KeyBinding::parse("Ctrl+Enter"),
+            Ok(KeyBinding::new(KeyCode::Enter, CTRL))
+        );
+        assert_eq!(
+            KeyBinding::parse("f5"),
+            Ok(KeyBinding::new(KeyCode::F(5), NONE))
+        );
+        assert_eq!(
+            KeyBinding::parse("ctrl+shift+z"),
+            Ok(KeyBinding::new(
+                KeyCode::Char('z'),
+                CTRL | KeyModifiers::SHIFT
+            ))
+        );
+        assert_eq!(
+            KeyBinding::parse("Ctrl+K"),
+            Ok(KeyBinding::new(KeyCode::Char('k'), CTRL))
+        );
+        assert_eq!(
+            KeyBinding::parse("P"),
+            Ok(KeyBinding::new(KeyCode::Char('p'), KeyModifiers::SHIFT))
+        );
+        assert_eq!(
+            KeyBinding::parse("Alt++"),
+            Ok(KeyBinding::new(KeyCode::Char('+'), KeyModifiers::ALT))
+        );
+        assert!(KeyBinding::parse("Hyper+x").is_err());
+        assert!(KeyBinding::parse("F13").is_err());
+        assert!(KeyBinding::parse("Ctrl+Nope").is_err());
+    }
+
+    #[test]
+    fn test_binding_matches_terminal_events() {
+        let shift_p = KeyBinding::parse("Shift+p").unwrap();
+        assert!(shift_p.matches(&key(KeyCode::Char('P'), KeyModifiers::SHIFT)));
+        assert!(!shift_p.matches(&key(KeyCode::Char('p'), NONE)));
+
+        let question = KeyBinding::parse("?").unwrap();
+        assert!(question.matches(&key(KeyCode::Char('?'), KeyModifiers::SHIFT)));
+        assert!(question.matches(&key(KeyCode::Char('?'), NONE)));
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        for spec in ["Ctrl+Enter", "F6", "Ctrl+Shift+Z", "w", "Alt+Space"] {
+            let binding = KeyBinding::parse(spec).unwrap();
+            assert_eq!(binding.to_string(), spec);
+        }
+    }
+
+    #[test]
+    fn test_default_keymap() {
+        let keymap = Keymap::default();
+        assert_eq!(
+            keymap.action_for(KeyContext::Editor, &key(KeyCode::F(5), NONE)),
+            Some(PaletteAction::ExecuteQuery)
+        );
+        // Results bindings do not fire in the editor
+        assert_eq!(
+            keymap.action_for(KeyContext::Editor, &key(KeyCode::Char('p'), NONE)),
+            None
+        );
+        assert_eq!(
+            keymap.describe(PaletteAction::ExecuteQuery).as_deref(),
+            Some("F5 / Ctrl+Enter")
+        );
+        assert_eq!(keymap.describe(PaletteAction::ToggleTheme), None);
+    }
+
+    #[test]
+    fn test_overrides_replace_defaults() {
+        let mut overrides = BTreeMap::new();
+        overrides.insert(
+            "clear_editor".to_string(),
+            KeySpec::One("Ctrl+K".to_string()),
+        );
+        overrides.insert("toggle_theme".to_string(), KeySpec::One("F9".to_string()));
+        overrides.insert("pin_result".to_string(), KeySpec::Many(vec![]));
+        overrides.insert("bogus".to_string(), KeySpec::One("F1".to_string()));
+
+        let (keymap, errors) = Keymap::with_overrides(&overrides);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            keymap.action_for(KeyContext::Editor, &key(KeyCode::Char('k'), CTRL)),
+            Some(PaletteAction::ClearEditor)
+        );
+        assert_eq!(
+            keymap.action_for(KeyContext::Editor, &key(KeyCode::Char('l'), CTRL)),
+            None
+        );
+        assert_eq!(
+            keymap.action_for(KeyContext::Global, &key(KeyCode::F(9), NONE)),
+            Some(PaletteAction::ToggleTheme)
+        );
+        assert_eq!(
+            keymap.action_for(KeyContext::Results, &key(KeyCode::Char('p'), NONE)),
+            None
+        );
+    }
+}
@@ -1,7 +1,15 @@
 mod app;
+mod cell_format;
+mod column_view;
 mod components;
+mod keymap;
+mod palette;
 mod theme;
 
 pub use app::*;
+pub use cell_format::*;
+pub use column_view::*;
 pub use components::*;
+pub use keymap::*;
+pub use palette::*;
 pub use theme::*;
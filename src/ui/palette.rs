@@ -0,0 +1,308 @@
+use crate::ui::KeyContext;
+
+/// Actions reachable from the command palette (Ctrl+P).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PaletteAction {
+    ExecuteQuery,
+    ExecuteAll,
+    ExecuteAllInTransaction,
+    ExplainQuery,
+    CostCheck,
+    CountRows,
+    ClearEditor,
+    TrimWhitespace,
+    SelectStatement,
+    GoToLine,
+    Find,
+    ShowAst,
+    ToggleOffline,
+    ToggleVimMode,
+    ToggleAutocomplete,
+    ExportResults,
+    PinResult,
+    HideColumn,
+    ShowAllColumns,
+    PinColumn,
+    MoveColumnLeft,
+    MoveColumnRight,
+    CompareWithPinned,
+    ToggleWrap,
+    ToggleChart,
+    ToggleRelativeTime,
+    IncreaseLimit,
+    DecreaseLimit,
+    NextPage,
+    PreviousPage,
+    ToggleTheme,
+    SwitchDatabase,
+    OpenConnectionDialog,
+    CancelAndDisconnect,
+    ShowHelp,
+    Quit,
+}
+
+pub const PALETTE_ACTIONS: &[PaletteAction] = &[
+    PaletteAction::ExecuteQuery,
+    PaletteAction::ExecuteAll,
+    PaletteAction::ExecuteAllInTransaction,
+    PaletteAction::ExplainQuery,
+    PaletteAction::CostCheck,
+    PaletteAction::CountRows,
+    PaletteAction::ClearEditor,
+    PaletteAction::TrimWhitespace,
+    PaletteAction::SelectStatement,
+    PaletteAction::GoToLine,
+    PaletteAction::Find,
+    PaletteAction::ShowAst,
+    PaletteAction::ToggleOffline,
+    PaletteAction::ToggleVimMode,
+    PaletteAction::ToggleAutocomplete,
+    PaletteAction::ExportResults,
+    PaletteAction::PinResult,
+    PaletteAction::HideColumn,
+    PaletteAction::ShowAllColumns,
+    PaletteAction::PinColumn,
+    PaletteAction::MoveColumnLeft,
+    PaletteAction::MoveColumnRight,
+    PaletteAction::CompareWithPinned,
+    PaletteAction::ToggleWrap,
+    PaletteAction::ToggleChart,
+    PaletteAction::ToggleRelativeTime,
+    PaletteAction::IncreaseLimit,
+    PaletteAction::DecreaseLimit,
+    PaletteAction::NextPage,
+    PaletteAction::PreviousPage,
+    PaletteAction::ToggleTheme,
+    PaletteAction::SwitchDatabase,
+    PaletteAction::OpenConnectionDialog,
+    PaletteAction::CancelAndDisconnect,
+    PaletteAction::ShowHelp,
+    PaletteAction::Quit,
+];
+
+impl PaletteAction {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PaletteAction::ExecuteQuery => "Run query at cursor",
+            PaletteAction::ExecuteAll => "Run all statements",
+            PaletteAction::ExecuteAllInTransaction => "Run all statements in one transaction",
+            PaletteAction::ExplainQuery => "Explain query at cursor",
+            PaletteAction::CostCheck => "Estimate query cost",
+            PaletteAction::CountRows => "Count rows of query at cursor",
+            PaletteAction::ClearEditor => "Clear editor",
+            PaletteAction::TrimWhitespace => "Trim trailing whitespace",
+            PaletteAction::SelectStatement => "Select statement at cursor",
+            PaletteAction::GoToLine => "Go to line",
+            PaletteAction::Find => "Find in editor",
+            PaletteAction::ShowAst => "Show query AST",
+            PaletteAction::ToggleOffline => "Toggle offline mode",
+            PaletteAction::ToggleVimMode => "Toggle vim mode",
+            PaletteAction::ToggleAutocomplete => "Toggle autocomplete while typing",
+            PaletteAction::ExportResults => "Export results",
+            PaletteAction::PinResult => "Pin current result",
+            PaletteAction::HideColumn => "Hide selected result column",
+            PaletteAction::ShowAllColumns => "Show all result columns",
+            PaletteAction::PinColumn => "Move result column to the front",
+            PaletteAction::MoveColumnLeft => "Move result column left",
+            PaletteAction::MoveColumnRight => "Move result column right",
+            PaletteAction::CompareWithPinned => "Compare with pinned result",
+            PaletteAction::ToggleWrap => "Toggle wrapping the selected row",
+            PaletteAction::ToggleChart => "Toggle bar chart of the selected column",
+            PaletteAction::ToggleRelativeTime => "Toggle relative timestamps",
+            PaletteAction::IncreaseLimit => "Re-run with double the LIMIT",
+            PaletteAction::DecreaseLimit => "Re-run with half the LIMIT",
+            PaletteAction::NextPage => "Re-run the next page (OFFSET)",
+            PaletteAction::PreviousPage => "Re-run the previous page (OFFSET)",
+            PaletteAction::ToggleTheme => "Toggle light/dark theme",
+            PaletteAction::SwitchDatabase => "Switch database",
+            PaletteAction::OpenConnectionDialog => "Open connection dialog",
+            PaletteAction::CancelAndDisconnect => "Cancel all and disconnect",
+            PaletteAction::ShowHelp => "Show keyboard shortcuts",
+            PaletteAction::Quit => "Quit",
+        }
+    }
+
+    /// Name used for the action in the `[keybindings]` config table.
+    pub fn name(&self) -> &'static str {
+        match self {
+            PaletteAction::ExecuteQuery => "execute_query",
+            PaletteAction::ExecuteAll => "execute_all",
+            PaletteAction::ExecuteAllInTransaction => "execute_all_in_transaction",
+            PaletteAction::ExplainQuery => "explain_query",
+            PaletteAction::CostCheck => "cost_check",
+            PaletteAction::CountRows => "count_rows",
+            PaletteAction::ClearEditor => "clear_editor",
+            PaletteAction::TrimWhitespace => "trim_whitespace",
+            PaletteAction::SelectStatement => "select_statement",
+            PaletteAction::GoToLine => "go_to_line",
+            PaletteAction::Find => "find",
+            PaletteAction::ShowAst => "show_ast",
+            PaletteAction::ToggleOffline => "toggle_offline",
+            PaletteAction::ToggleVimMode => "toggle_vim_mode",
+            PaletteAction::ToggleAutocomplete => "toggle_autocomplete",
+            PaletteAction::ExportResults => "export_results",
+            PaletteAction::PinResult => "pin_result",
+            PaletteAction::HideColumn => "hide_column",
+            PaletteAction::ShowAllColumns => "show_all_columns",
+            PaletteAction::PinColumn => "pin_column",
+            PaletteAction::MoveColumnLeft => "move_column_left",
+            PaletteAction::MoveColumnRight => "move_column_right",
+            PaletteAction::CompareWithPinned => "compare_with_pinned",
+            PaletteAction::ToggleWrap => "toggle_wrap",
+            PaletteAction::ToggleChart => "toggle_chart",
+            PaletteAction::ToggleRelativeTime => "toggle_relative_time",
+            PaletteAction::IncreaseLimit => "increase_limit",
+            PaletteAction::DecreaseLimit => "decrease_limit",
+            PaletteAction::NextPage => "next_page",
+            PaletteAction::PreviousPage => "previous_page",
+            PaletteAction::ToggleTheme => "toggle_theme",
+            PaletteAction::SwitchDatabase => "switch_database",
+            PaletteAction::OpenConnectionDialog => "open_connection_dialog",
+            PaletteAction::CancelAndDisconnect => "cancel_and_disconnect",
+            PaletteAction::ShowHelp => "show_help",
+            PaletteAction::Quit => "quit",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        PALETTE_ACTIONS.iter().copied().find(|a| a.name() == name)
+    }
+
+    /// Pane in which a keymap binding for this action is active.
+    pub fn context(&self) -> KeyContext {
+        match self {
+            PaletteAction::ExecuteQuery
+            | PaletteAction::ExecuteAll
+            | PaletteAction::ExecuteAllInTransaction
+            | PaletteAction::ExplainQuery
+            | PaletteAction::CostCheck
+            | PaletteAction::CountRows
+            | PaletteAction::ClearEditor
+            | PaletteAction::TrimWhitespace
+            | PaletteAction::SelectStatement
+            | PaletteAction::GoToLine
+            | PaletteAction::Find
+            | PaletteAction::ShowAst
+            | PaletteAction::ToggleOffline
+            | PaletteAction::ToggleVimMode
+            | PaletteAction::ToggleAutocomplete => KeyContext::Editor,
+            PaletteAction::ExportResults
+            | PaletteAction::PinResult
+            | PaletteAction::HideColumn
+            | PaletteAction::ShowAllColumns
+            | PaletteAction::PinColumn
+            | PaletteAction::MoveColumnLeft
+            | PaletteAction::MoveColumnRight
+            | PaletteAction::CompareWithPinned
+            | PaletteAction::ToggleWrap
+            | PaletteAction::ToggleChart
+            | PaletteAction::ToggleRelativeTime
+            | PaletteAction::IncreaseLimit
+            | PaletteAction::DecreaseLimit
+            | PaletteAction::NextPage
+            | PaletteAction::PreviousPage => KeyContext::Results,
+            PaletteAction::ToggleTheme
+            | PaletteAction::SwitchDatabase
+            | PaletteAction::OpenConnectionDialog
+            | PaletteAction::CancelAndDisconnect
+            | PaletteAction::ShowHelp
+            | PaletteAction::Quit => KeyContext::Global,
+        }
+    }
+
+    /// Built-in key for actions handled outside the keymap, shown in the
+    /// palette when the keymap has no binding for them.
+    pub fn fixed_keybinding(&self) -> &'static str {
+        match self {
+            PaletteAction::SwitchDatabase => "\\c",
+            PaletteAction::OpenConnectionDialog => "Ctrl+C",
+            PaletteAction::ShowHelp => "?",
+            PaletteAction::Quit => "Ctrl+Q",
+            _ => "",
+        }
+    }
+}
+
+/// Score `text` against a fuzzy `query`: every query character must appear in
+/// order (case-insensitive). Consecutive matches and matches at word starts
+/// score higher. Returns `None` when `text` does not match.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut prev_match: Option<usize> = None;
+
+    for qc in query.chars().flat_map(char::to_lowercase) {
+        if qc.is_whitespace() {
+            continue;
+        }
+        let idx = pos + text[pos..].iter().position(|&c| c == qc)?;
+        score += 1;
+        if prev_match.is_some_and(|p| p + 1 == idx) {
+            score += 3;
+        }
+        if idx == 0 || !text[idx - 1].is_alphanumeric() {
+            score += 2;
+        }
+        prev_match = Some(idx);
+        pos = idx + 1;
+    }
+    Some(score)
+}
+
+/// Actions matching `query`, best match first. An empty query lists every
+/// action in its default order.
+pub fn filter_actions(query: &str) -> Vec<PaletteAction> {
+    let mut scored: Vec<(i32, PaletteAction)> = PALETTE_ACTIONS
+        .iter()
+        .filter_map(|&action| fuzzy_score(query, action.label()).map(|s| (s, action)))
+        .collect();
+    scored.sort_by_key(|(score, _)| -score);
+    scored.into_iter().map(|(_, action)| action).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score_subsequence() {
+        assert!(fuzzy_score("clr", "Clear editor").is_some());
+        assert!(fuzzy_score("CLEAR", "Clear editor").is_some());
+        assert!(fuzzy_score("xyz", "Clear editor").is_none());
+        assert!(fuzzy_score("ralc", "Clear").is_none());
+        assert_eq!(fuzzy_score("", "Quit"), Some(0));
+    }
+
+    #[test]
+    fn test_fuzzy_score_ranking() {
+        // Word starts beat mid-word matches
+        assert!(fuzzy_score("sd", "Switch database") > fuzzy_score("sd", "Used by"));
+        // Consecutive matches beat scattered ones
+        assert!(fuzzy_score("cle", "Clear") > fuzzy_score("cle", "Compile"));
+    }
+
+    #[test]
+    fn test_filter_actions() {
+        assert_eq!(filter_actions("").len(), PALETTE_ACTIONS.len());
+        assert_eq!(filter_actions("")[0], PaletteAction::ExecuteQuery);
+        assert_eq!(filter_actions("theme"), vec![PaletteAction::ToggleTheme]);
+        assert_eq!(filter_actions("export")[0], PaletteAction::ExportResults);
+        assert!(filter_actions("zzz").is_empty());
+    }
+
+    #[test]
+    fn test_every_action_has_label() {
+        for action in PALETTE_ACTIONS {
+            assert!(!action.label().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_action_names_round_trip() {
+        for &action in PALETTE_ACTIONS {
+            assert_eq!(PaletteAction::from_name(action.name()), Some(action));
+        }
+        assert_eq!(PaletteAction::from_name("no_such_action"), None);
+    }
+}
@@ -1,5 +1,7 @@
 use ratatui::style::{Color, Modifier, Style};
 
+use crate::db::Environment;
+
 #[allow(dead_code)]
 pub struct Theme {
     // Background colors
@@ -35,6 +37,10 @@ pub struct Theme {
     pub border_focused: Color,
     pub cursor: Color,
     pub selection: Color,
+
+    /// Tint of the connected environment, drawn on unfocused borders and
+    /// the header. `None` keeps the plain palette.
+    pub environment: Option<Color>,
 }
 
 impl Default for Theme {
@@ -80,6 +86,8 @@ impl Theme {
             border_focused: Color::Rgb(100, 180, 255),
             cursor: Color::Rgb(255, 255, 255),
             selection: Color::Rgb(60, 80, 120),
+
+            environment: None,
         }
     }
 
@@ -113,19 +121,46 @@ impl Theme {
             border_focused: Color::Rgb(0, 120, 200),
             cursor: Color::Rgb(0, 0, 0),
             selection: Color::Rgb(180, 200, 240),
+
+            environment: None,
         }
     }
 
     // Style helpers
+    /// Look up a built-in theme by the name used in `prefs.toml`.
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            _ => None,
+        }
+    }
+
     pub fn normal(&self) -> Style {
         Style::default().fg(self.text_primary).bg(self.bg_primary)
     }
 
     pub fn header(&self) -> Style {
-        Style::default()
-            .fg(self.text_primary)
-            .bg(self.bg_secondary)
-            .add_modifier(Modifier::BOLD)
+        match self.environment {
+            Some(color) => Style::default()
+                .fg(self.bg_primary)
+                .bg(color)
+                .add_modifier(Modifier::BOLD),
+            None => Style::default()
+                .fg(self.text_primary)
+                .bg(self.bg_secondary)
+                .add_modifier(Modifier::BOLD),
+        }
+    }
+
+    /// The color signalling `env`: green for dev, amber for staging and red
+    /// for prod.
+    pub fn environment_color(&self, env: Environment) -> Color {
+        match env {
+            Environment::Dev => self.success,
+            Environment::Staging => self.warning,
+            Environment::Prod => self.error,
+        }
     }
 
     pub fn selected(&self) -> Style {
@@ -140,7 +175,7 @@ impl Theme {
         if focused {
             Style::default().fg(self.border_focused)
         } else {
-            Style::default().fg(self.border)
+            Style::default().fg(self.environment.unwrap_or(self.border))
         }
     }
 
@@ -755,12 +790,59 @@ pub fn is_sql_keyword(word: &str) -> bool {
     SQL_KEYWORDS.contains(&word.to_uppercase().as_str())
 }
 
+/// Schema-qualified names like `pg_catalog.int4` are matched on their last segment.
 pub fn is_sql_type(word: &str) -> bool {
-    SQL_TYPES.contains(&word.to_uppercase().as_str())
+    SQL_TYPES.contains(&unqualified(word).to_uppercase().as_str())
 }
 
+/// Schema-qualified names like `pg_catalog.now` are matched on their last segment.
 pub fn is_sql_function(word: &str) -> bool {
-    SQL_FUNCTIONS.contains(&word.to_uppercase().as_str())
+    SQL_FUNCTIONS.contains(&unqualified(word).to_uppercase().as_str())
+}
+
+/// Recognize PostgreSQL numeric literals: integers and decimals with optional
+/// `_` separators and exponent (`1_000`, `1.5e-3`), hex/octal/binary
+/// (`0xFF`, `0o17`, `0b101`), and an optional leading minus sign.
+pub fn is_numeric_literal(word: &str) -> bool {
+    let word = word.strip_prefix('-').unwrap_or(word).to_ascii_lowercase();
+
+    for (prefix, radix) in [("0x", 16), ("0o", 8), ("0b", 2)] {
+        if let Some(digits) = word.strip_prefix(prefix) {
+            return is_digit_group(digits, radix);
+        }
+    }
+
+    let (mantissa, exponent) = match word.split_once('e') {
+        Some((m, e)) => (m, Some(e)),
+        None => (word.as_str(), None),
+    };
+    if let Some(exponent) = exponent {
+        let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+        if !is_digit_group(exponent, 10) {
+            return false;
+        }
+    }
+    match mantissa.split_once('.') {
+        Some(("", "")) => false,
+        Some((int, frac)) => {
+            (int.is_empty() || is_digit_group(int, 10))
+                && (frac.is_empty() || is_digit_group(frac, 10))
+        }
+        None => is_digit_group(mantissa, 10),
+    }
+}
+
+/// Digits in `radix`, with single `_` separators between them.
+fn is_digit_group(s: &str, radix: u32) -> bool {
+    !s.is_empty()
+        && !s.starts_with('_')
+        && !s.ends_with('_')
+        && !s.contains("__")
+        && s.chars().all(|c| c == '_' || c.is_digit(radix))
+}
+
+fn unqualified(word: &str) -> &str {
+    word.rsplit('.').next().unwrap_or(word)
 }
 
 #[cfg(test)]
@@ -795,6 +877,14 @@ mod tests {
         assert_eq!(default_theme.bg_primary, dark_theme.bg_primary);
     }
 
+    #[test]
+    fn test_theme_by_name() {
+        let light = Theme::by_name("Light").unwrap();
+        assert_eq!(light.bg_primary, Theme::light().bg_primary);
+        assert!(Theme::by_name("dark").is_some());
+        assert!(Theme::by_name("solarized").is_none());
+    }
+
     // --- Style helpers ---
 
     #[test]
@@ -821,6 +911,15 @@ mod tests {
         assert_eq!(unfocused.fg, Some(theme.border));
     }
 
+    #[test]
+    fn test_environment_tint() {
+        let mut theme = Theme::dark();
+        theme.environment = Some(theme.environment_color(Environment::Prod));
+        assert_eq!(theme.border_style(false).fg, Some(theme.error));
+        assert_eq!(theme.border_style(true).fg, Some(theme.border_focused));
+        assert_eq!(theme.header().bg, Some(theme.error));
+    }
+
     #[test]
     fn test_status_styles() {
         let theme = Theme::dark();
@@ -962,4 +1061,24 @@ mod tests {
             assert!(seen.insert(*f), "Duplicate function: {}", f);
         }
     }
+
+    #[test]
+    fn test_schema_qualified_names() {
+        assert!(is_sql_function("pg_catalog.now"));
+        assert!(is_sql_type("pg_catalog.TIMESTAMPTZ"));
+        assert!(!is_sql_function("public.my_func"));
+        assert!(!is_sql_type("users.email"));
+    }
+
+    #[test]
+    fn test_numeric_literals() {
+        for word in [
+            "42", "3.14", "1e5", "1.5E-3", "2e+10", "1_000", "0xFF", "0o17", "0b101", "-7", "5.",
+        ] {
+            assert!(is_numeric_literal(word), "{} should be numeric", word);
+        }
+        for word in ["e5", "1e", "1__0", "_1", "0xG", "abc", ".", "-", "1.2.3"] {
+            assert!(!is_numeric_literal(word), "{} should not be numeric", word);
+        }
+    }
 }